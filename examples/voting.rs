@@ -1,5 +1,4 @@
 use ddclient_rs::{ApiError, Client};
-use std::collections::HashMap;
 
 #[tokio::main]
 async fn main() -> Result<(), ApiError> {
@@ -7,41 +6,38 @@ async fn main() -> Result<(), ApiError> {
         "xapp-1-615817b2fc2700f23eea-c507dt3co5dknpst59c0beqkgm4n26nrr6akqjum".to_string(),
     )
     .api_url("https://api-demo.directdecisions.com/".to_string())
-    .build();
+    .try_build()
+    .expect("Invalid client configuration");
 
     let v = client
-        .create_voting(vec![
-            "Einstein".to_string(),
-            "Maxwell".to_string(),
-            "Newton".to_string(),
-        ])
+        .create_voting(["Einstein", "Maxwell", "Newton"])
         .await?;
 
     println!("Created voting: {:?}", &v);
 
     let _ = client
-        .vote(
+        .vote_ordered(
             &v.id,
             "Leonardo",
-            HashMap::from([
-                ("Einstein".to_string(), 1),
-                ("Maxwell".to_string(), 2),
-                ("Newton".to_string(), 3),
-            ]),
+            vec![
+                "Einstein".to_string(),
+                "Maxwell".to_string(),
+                "Newton".to_string(),
+            ],
         )
         .await?;
 
     println!("Leonardo voted for Einstein in voting: {}", &v.id);
 
     let _ = client
-        .vote(
+        .vote_ordered(
             &v.id,
             "Michelangelo",
-            HashMap::from([
-                ("Einstein".to_string(), 3),
-                ("Maxwell".to_string(), 2),
-                ("Newton".to_string(), 1),
-            ]),
+            vec![
+                "Newton".to_string(),
+                "Maxwell".to_string(),
+                "Einstein".to_string(),
+            ],
         )
         .await?;
 
@@ -0,0 +1,175 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::{ApiError, Choices, Client, Rank, ReportFormat, Voting, VotingResults};
+#[cfg(feature = "experimental")]
+use crate::{BallotsPage, ListOptions, VotingsPage};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+/// The operations of the Direct Decisions API, implemented by the `Client`.
+///
+/// Application code can depend on this trait instead of the `Client`, and replace the client
+/// with a fake implementation, or one generated by `mockall`, in unit tests.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddclient_rs::{ApiError, Client, DirectDecisionsApi};
+///
+/// async fn winner(api: &impl DirectDecisionsApi, voting_id: &str) -> Result<String, ApiError> {
+///     let results = api.get_voting_results(voting_id).await?;
+///     Ok(results.results[0].choice.clone())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+///     let winner = winner(&client, "voting-id").await.unwrap();
+///     println!("Winner: {}", winner);
+/// }
+/// ```
+///
+/// With `mockall`, a mock can be generated with the `mock!` macro and an
+/// `#[async_trait::async_trait]` annotated implementation of this trait.
+#[async_trait]
+pub trait DirectDecisionsApi: Send + Sync {
+    /// Creates a new voting with the specified choices.
+    async fn create_voting(&self, choices: Vec<String>) -> Result<Voting, ApiError>;
+
+    /// Retrieves a voting by its ID.
+    async fn get_voting(&self, id: &str) -> Result<Voting, ApiError>;
+
+    /// Retrieves a page of the votings owned by the API key.
+    ///
+    /// This method is available with the `experimental` feature.
+    #[cfg(feature = "experimental")]
+    async fn list_votings(&self, options: &ListOptions) -> Result<VotingsPage, ApiError>;
+
+    /// Deletes a voting by its ID.
+    async fn delete_voting(&self, id: &str) -> Result<(), ApiError>;
+
+    /// Sets a choice in a voting at the specified index.
+    async fn set_choice(
+        &self,
+        voting_id: &str,
+        choice: &str,
+        index: i32,
+    ) -> Result<Choices, ApiError>;
+
+    /// Submits a vote in a voting, and returns whether the voter voted before.
+    async fn vote(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        ballot: HashMap<String, Rank>,
+    ) -> Result<bool, ApiError>;
+
+    /// Removes the ballot of a voter from a voting.
+    async fn unvote(&self, voting_id: &str, voter_id: &str) -> Result<(), ApiError>;
+
+    /// Retrieves the ballot of a voter in a voting.
+    async fn get_ballot(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+    ) -> Result<HashMap<String, Rank>, ApiError>;
+
+    /// Retrieves a page of the ballots submitted in a voting.
+    #[cfg(feature = "experimental")]
+    async fn list_ballots(
+        &self,
+        voting_id: &str,
+        options: &ListOptions,
+    ) -> Result<BallotsPage, ApiError>;
+
+    /// Retrieves the results of a voting.
+    async fn get_voting_results(&self, voting_id: &str) -> Result<VotingResults, ApiError>;
+
+    /// Retrieves the results of a voting, including the duels between the choices.
+    async fn get_voting_results_duels(&self, voting_id: &str) -> Result<VotingResults, ApiError>;
+
+    /// Retrieves the results of a voting and renders them as a report in the given format.
+    async fn summarize_voting(
+        &self,
+        voting_id: &str,
+        format: ReportFormat,
+    ) -> Result<String, ApiError>;
+}
+
+#[async_trait]
+impl DirectDecisionsApi for Client {
+    async fn create_voting(&self, choices: Vec<String>) -> Result<Voting, ApiError> {
+        Client::create_voting(self, choices).await
+    }
+
+    async fn get_voting(&self, id: &str) -> Result<Voting, ApiError> {
+        Client::get_voting(self, id).await
+    }
+
+    #[cfg(feature = "experimental")]
+    async fn list_votings(&self, options: &ListOptions) -> Result<VotingsPage, ApiError> {
+        Client::list_votings(self, options).await
+    }
+
+    async fn delete_voting(&self, id: &str) -> Result<(), ApiError> {
+        Client::delete_voting(self, id).await
+    }
+
+    async fn set_choice(
+        &self,
+        voting_id: &str,
+        choice: &str,
+        index: i32,
+    ) -> Result<Choices, ApiError> {
+        Client::set_choice(self, voting_id, choice, index).await
+    }
+
+    async fn vote(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        ballot: HashMap<String, Rank>,
+    ) -> Result<bool, ApiError> {
+        Client::vote(self, voting_id, voter_id, ballot).await
+    }
+
+    async fn unvote(&self, voting_id: &str, voter_id: &str) -> Result<(), ApiError> {
+        Client::unvote(self, voting_id, voter_id).await
+    }
+
+    async fn get_ballot(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+    ) -> Result<HashMap<String, Rank>, ApiError> {
+        Client::get_ballot(self, voting_id, voter_id).await
+    }
+
+    #[cfg(feature = "experimental")]
+    async fn list_ballots(
+        &self,
+        voting_id: &str,
+        options: &ListOptions,
+    ) -> Result<BallotsPage, ApiError> {
+        Client::list_ballots(self, voting_id, options).await
+    }
+
+    async fn get_voting_results(&self, voting_id: &str) -> Result<VotingResults, ApiError> {
+        Client::get_voting_results(self, voting_id).await
+    }
+
+    async fn get_voting_results_duels(&self, voting_id: &str) -> Result<VotingResults, ApiError> {
+        Client::get_voting_results_duels(self, voting_id).await
+    }
+
+    async fn summarize_voting(
+        &self,
+        voting_id: &str,
+        format: ReportFormat,
+    ) -> Result<String, ApiError> {
+        Client::summarize_voting(self, voting_id, format).await
+    }
+}
@@ -0,0 +1,422 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use ddclient_rs::Rank;
+use std::fmt;
+use std::time::Duration;
+
+/// The interval between the polls of `results --watch`, unless set with `--interval`.
+const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+pub const USAGE: &str = "\
+Usage: ddclient [OPTIONS] <COMMAND> [ARGS]
+
+Commands:
+  create <CHOICE>...                      Create a voting with the choices
+  get <VOTING_ID>                         Show a voting
+  delete <VOTING_ID>                      Delete a voting
+  set-choice <VOTING_ID> <CHOICE> <INDEX> Add, move or, with index -1, remove a choice
+  vote <VOTING_ID> <VOTER_ID> <CHOICE=RANK>...
+                                          Submit the ballot of a voter
+  vote --interactive <VOTING_ID> <VOTER_ID>
+                                          Rank the choices in the terminal and submit them
+  unvote <VOTING_ID> <VOTER_ID>           Remove the ballot of a voter
+  ballot <VOTING_ID> <VOTER_ID>           Show the ballot of a voter
+  results <VOTING_ID>                     Show the results of a voting
+  results --watch <VOTING_ID>             Redraw the results as they change, until a unique
+                                          leader holds the lead for 3 polls, or Ctrl-C
+
+Options:
+  --api-url <URL>         The API URL, instead of DDCLIENT_API_URL or the config file
+  --output <json|table>   The output format, json by default
+  -i, --interactive       Rank the choices of the vote command interactively
+  -w, --watch             Poll the results of the results command
+  --interval <SECONDS>    The polling interval of --watch, 5 seconds by default
+  --until-closed          Watch until the voting is closed instead, in experimental builds
+  -h, --help              Print this help
+
+The API key is read from the DDCLIENT_API_KEY environment variable, or the api_key field
+of the JSON config file at DDCLIENT_CONFIG, or ~/.config/ddclient/config.json.
+";
+
+/// The output format of the command results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Output {
+    #[default]
+    Json,
+    Table,
+}
+
+/// When `results --watch` stops polling the results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Until {
+    /// Once a unique leader leads the results for several polls in a row.
+    #[default]
+    Winner,
+    /// Once the voting is closed, with `--until-closed`. Requires the `experimental` feature,
+    /// as the voting status is not part of the documented API yet.
+    #[cfg(feature = "experimental")]
+    Closed,
+}
+
+/// A command of the CLI, with its arguments.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Create {
+        choices: Vec<String>,
+    },
+    Get {
+        voting_id: String,
+    },
+    Delete {
+        voting_id: String,
+    },
+    SetChoice {
+        voting_id: String,
+        choice: String,
+        index: i32,
+    },
+    Vote {
+        voting_id: String,
+        voter_id: String,
+        ballot: Vec<(String, Rank)>,
+    },
+    VoteInteractive {
+        voting_id: String,
+        voter_id: String,
+    },
+    Unvote {
+        voting_id: String,
+        voter_id: String,
+    },
+    Ballot {
+        voting_id: String,
+        voter_id: String,
+    },
+    Results {
+        voting_id: String,
+    },
+    WatchResults {
+        voting_id: String,
+        interval: Duration,
+        until: Until,
+    },
+    Help,
+}
+
+/// The parsed command line.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Args {
+    pub api_url: Option<String>,
+    pub output: Output,
+    pub command: Command,
+}
+
+/// An invalid command line, reported with the usage.
+#[derive(Debug, PartialEq, Eq)]
+pub struct UsageError(String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+fn usage_error(message: impl Into<String>) -> UsageError {
+    UsageError(message.into())
+}
+
+impl Args {
+    /// Parses the command line arguments, without the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, UsageError> {
+        let mut api_url = None;
+        let mut output = Output::default();
+        let mut interactive = false;
+        let mut watch = false;
+        let mut interval = None;
+        #[cfg_attr(not(feature = "experimental"), allow(unused_mut))]
+        let mut until = Until::default();
+        let mut positional = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => {
+                    return Ok(Args {
+                        api_url,
+                        output,
+                        command: Command::Help,
+                    })
+                }
+                "--api-url" => api_url = Some(value(&mut args, "--api-url")?),
+                "--output" => {
+                    output = match value(&mut args, "--output")?.as_str() {
+                        "json" => Output::Json,
+                        "table" => Output::Table,
+                        other => return Err(usage_error(format!("unknown output {:?}", other))),
+                    }
+                }
+                "-i" | "--interactive" => interactive = true,
+                "-w" | "--watch" => watch = true,
+                "--interval" => {
+                    let seconds = value(&mut args, "--interval")?;
+                    interval = match seconds.parse::<u64>() {
+                        Ok(seconds) if seconds > 0 => Some(Duration::from_secs(seconds)),
+                        _ => return Err(usage_error(format!("invalid interval {:?}", seconds))),
+                    }
+                }
+                #[cfg(feature = "experimental")]
+                "--until-closed" => until = Until::Closed,
+                "--" => positional.extend(args.by_ref()),
+                flag if flag.starts_with("--") => {
+                    return Err(usage_error(format!("unknown option {}", flag)))
+                }
+                _ => positional.push(arg),
+            }
+        }
+
+        let watch = match (watch, interval) {
+            (true, interval) => Some((interval.unwrap_or(DEFAULT_WATCH_INTERVAL), until)),
+            (false, None) if until == Until::default() => None,
+            (false, None) => return Err(usage_error("--until-closed requires --watch")),
+            (false, Some(_)) => return Err(usage_error("--interval requires --watch")),
+        };
+
+        Ok(Args {
+            api_url,
+            output,
+            command: parse_command(positional, interactive, watch)?,
+        })
+    }
+}
+
+fn value(args: &mut impl Iterator<Item = String>, name: &str) -> Result<String, UsageError> {
+    args.next()
+        .ok_or_else(|| usage_error(format!("{} requires a value", name)))
+}
+
+fn parse_command(
+    args: Vec<String>,
+    interactive: bool,
+    watch: Option<(Duration, Until)>,
+) -> Result<Command, UsageError> {
+    let Some((name, args)) = args.split_first() else {
+        return Err(usage_error("missing command"));
+    };
+
+    if let Some((interval, until)) = watch {
+        return match (name.as_str(), args) {
+            _ if interactive => Err(usage_error("--watch cannot be interactive")),
+            ("results", [voting_id]) => Ok(Command::WatchResults {
+                voting_id: voting_id.clone(),
+                interval,
+                until,
+            }),
+            ("results", _) => Err(usage_error("invalid arguments of results --watch")),
+            _ => Err(usage_error(format!("{} cannot be watched", name))),
+        };
+    }
+
+    if interactive {
+        return match (name.as_str(), args) {
+            ("vote", [voting_id, voter_id]) => Ok(Command::VoteInteractive {
+                voting_id: voting_id.clone(),
+                voter_id: voter_id.clone(),
+            }),
+            ("vote", _) => Err(usage_error("invalid arguments of vote --interactive")),
+            _ => Err(usage_error(format!("{} cannot be interactive", name))),
+        };
+    }
+
+    let command = match (name.as_str(), args) {
+        ("create", choices) if !choices.is_empty() => Command::Create {
+            choices: choices.to_vec(),
+        },
+        ("get", [voting_id]) => Command::Get {
+            voting_id: voting_id.clone(),
+        },
+        ("delete", [voting_id]) => Command::Delete {
+            voting_id: voting_id.clone(),
+        },
+        ("set-choice", [voting_id, choice, index]) => Command::SetChoice {
+            voting_id: voting_id.clone(),
+            choice: choice.clone(),
+            index: index
+                .parse()
+                .map_err(|_| usage_error(format!("invalid index {:?}", index)))?,
+        },
+        ("vote", [voting_id, voter_id, ballot @ ..]) if !ballot.is_empty() => Command::Vote {
+            voting_id: voting_id.clone(),
+            voter_id: voter_id.clone(),
+            ballot: ballot
+                .iter()
+                .map(|pair| parse_rank(pair))
+                .collect::<Result<_, _>>()?,
+        },
+        ("unvote", [voting_id, voter_id]) => Command::Unvote {
+            voting_id: voting_id.clone(),
+            voter_id: voter_id.clone(),
+        },
+        ("ballot", [voting_id, voter_id]) => Command::Ballot {
+            voting_id: voting_id.clone(),
+            voter_id: voter_id.clone(),
+        },
+        ("results", [voting_id]) => Command::Results {
+            voting_id: voting_id.clone(),
+        },
+        ("help", []) => Command::Help,
+        (
+            "create" | "get" | "delete" | "set-choice" | "vote" | "unvote" | "ballot" | "results",
+            _,
+        ) => return Err(usage_error(format!("invalid arguments of {}", name))),
+        _ => return Err(usage_error(format!("unknown command {}", name))),
+    };
+    Ok(command)
+}
+
+/// Parses a `choice=rank` ballot argument. The last `=` separates the rank, so choices
+/// may contain `=`.
+fn parse_rank(pair: &str) -> Result<(String, Rank), UsageError> {
+    let invalid = || {
+        usage_error(format!(
+            "invalid ballot entry {:?}, expected CHOICE=RANK",
+            pair
+        ))
+    };
+    let (choice, rank) = pair.rsplit_once('=').ok_or_else(invalid)?;
+    let rank = rank
+        .parse::<u32>()
+        .ok()
+        .and_then(Rank::new)
+        .ok_or_else(invalid)?;
+    Ok((choice.to_string(), rank))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, UsageError> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(
+            parse(&["--output", "table", "results", "abc"]),
+            Ok(Args {
+                api_url: None,
+                output: Output::Table,
+                command: Command::Results {
+                    voting_id: "abc".to_string()
+                },
+            })
+        );
+
+        assert_eq!(
+            parse(&[
+                "vote",
+                "abc",
+                "einstein",
+                "Kant=1",
+                "a=b=2",
+                "--api-url",
+                "http://x"
+            ]),
+            Ok(Args {
+                api_url: Some("http://x".to_string()),
+                output: Output::Json,
+                command: Command::Vote {
+                    voting_id: "abc".to_string(),
+                    voter_id: "einstein".to_string(),
+                    ballot: vec![
+                        ("Kant".to_string(), Rank::HIGHEST),
+                        ("a=b".to_string(), Rank::new(2).unwrap()),
+                    ],
+                },
+            })
+        );
+
+        assert_eq!(
+            parse(&["set-choice", "abc", "--", "--Kant", "-1"])
+                .unwrap()
+                .command,
+            Command::SetChoice {
+                voting_id: "abc".to_string(),
+                choice: "--Kant".to_string(),
+                index: -1,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_interactive_test() {
+        assert_eq!(
+            parse(&["vote", "--interactive", "abc", "einstein"])
+                .unwrap()
+                .command,
+            Command::VoteInteractive {
+                voting_id: "abc".to_string(),
+                voter_id: "einstein".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_watch_test() {
+        assert_eq!(
+            parse(&["results", "--watch", "abc"]).unwrap().command,
+            Command::WatchResults {
+                voting_id: "abc".to_string(),
+                interval: Duration::from_secs(5),
+                until: Until::Winner,
+            }
+        );
+        assert_eq!(
+            parse(&["-w", "--interval", "30", "results", "abc"])
+                .unwrap()
+                .command,
+            Command::WatchResults {
+                voting_id: "abc".to_string(),
+                interval: Duration::from_secs(30),
+                until: Until::Winner,
+            }
+        );
+    }
+
+    #[cfg(feature = "experimental")]
+    #[test]
+    fn parse_watch_until_closed_test() {
+        assert_eq!(
+            parse(&["results", "--watch", "--until-closed", "abc"])
+                .unwrap()
+                .command,
+            Command::WatchResults {
+                voting_id: "abc".to_string(),
+                interval: Duration::from_secs(5),
+                until: Until::Closed,
+            }
+        );
+        assert!(parse(&["--until-closed", "results", "abc"]).is_err());
+    }
+
+    #[test]
+    fn parse_errors_test() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["publish", "abc"]).is_err());
+        assert!(parse(&["get"]).is_err());
+        assert!(parse(&["vote", "abc", "einstein"]).is_err());
+        assert!(parse(&["vote", "abc", "einstein", "Kant"]).is_err());
+        assert!(parse(&["vote", "abc", "einstein", "Kant=0"]).is_err());
+        assert!(parse(&["set-choice", "abc", "Kant", "first"]).is_err());
+        assert!(parse(&["--output", "yaml", "get", "abc"]).is_err());
+        assert!(parse(&["-i", "vote", "abc", "einstein", "Kant=1"]).is_err());
+        assert!(parse(&["--interactive", "get", "abc"]).is_err());
+        assert!(parse(&["--watch", "get", "abc"]).is_err());
+        assert!(parse(&["--interval", "30", "results", "abc"]).is_err());
+        assert!(parse(&["--watch", "--interval", "0", "results", "abc"]).is_err());
+        assert_eq!(parse(&["--help"]).unwrap().command, Command::Help);
+    }
+}
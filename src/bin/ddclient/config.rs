@@ -0,0 +1,50 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use serde::Deserialize;
+use std::path::PathBuf;
+
+const ENV_API_KEY: &str = "DDCLIENT_API_KEY";
+const ENV_API_URL: &str = "DDCLIENT_API_URL";
+const ENV_CONFIG: &str = "DDCLIENT_CONFIG";
+
+/// The configuration of the CLI, read from the environment and the config file.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct Config {
+    pub api_key: Option<String>,
+    pub api_url: Option<String>,
+}
+
+impl Config {
+    /// Loads the configuration. The environment variables take precedence over the fields
+    /// of the config file, which is optional.
+    pub fn load() -> Result<Self, String> {
+        let mut config = match config_path() {
+            Some(path) if path.exists() => {
+                let data = std::fs::read(&path)
+                    .map_err(|err| format!("reading {}: {}", path.display(), err))?;
+                serde_json::from_slice(&data)
+                    .map_err(|err| format!("parsing {}: {}", path.display(), err))?
+            }
+            _ => Config::default(),
+        };
+
+        if let Ok(api_key) = std::env::var(ENV_API_KEY) {
+            config.api_key = Some(api_key);
+        }
+        if let Ok(api_url) = std::env::var(ENV_API_URL) {
+            config.api_url = Some(api_url);
+        }
+        Ok(config)
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(ENV_CONFIG) {
+        return Some(PathBuf::from(path));
+    }
+    let home = std::env::var_os("HOME")?;
+    Some(PathBuf::from(home).join(".config/ddclient/config.json"))
+}
@@ -0,0 +1,303 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use ddclient_rs::Rank;
+use std::fmt::Write as _;
+#[cfg(unix)]
+use std::fs::{File, OpenOptions};
+use std::io;
+#[cfg(unix)]
+use std::io::{Read, Write};
+#[cfg(unix)]
+use std::process::{Command, Stdio};
+
+const HELP: &str = "\
+Up/Down: select   Space: pick up or drop the selected choice   t: tie with the choice above
+Enter: submit the ballot   q: cancel";
+
+/// A key pressed in the interactive ranking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Pick,
+    Tie,
+    Submit,
+    Cancel,
+    Other,
+}
+
+/// The result of a key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Continue,
+    Submit,
+    Cancel,
+}
+
+/// The ballot being ranked: the choices ordered from the most preferred, where a choice
+/// may be tied with the choice above it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ranking {
+    choices: Vec<String>,
+    tied: Vec<bool>,
+    cursor: usize,
+    picked: bool,
+}
+
+impl Ranking {
+    pub fn new(choices: Vec<String>) -> Self {
+        Self {
+            tied: vec![false; choices.len()],
+            choices,
+            cursor: 0,
+            picked: false,
+        }
+    }
+
+    /// Applies a key press. While a choice is picked up, `Up` and `Down` move it instead of
+    /// the selection.
+    pub fn handle(&mut self, key: Key) -> Outcome {
+        let last = self.choices.len().saturating_sub(1);
+        match key {
+            Key::Up if self.cursor > 0 => {
+                if self.picked {
+                    self.swap(self.cursor - 1);
+                }
+                self.cursor -= 1;
+            }
+            Key::Down if self.cursor < last => {
+                if self.picked {
+                    self.swap(self.cursor + 1);
+                }
+                self.cursor += 1;
+            }
+            Key::Pick => self.picked = !self.picked,
+            Key::Tie if self.cursor > 0 => self.tied[self.cursor] = !self.tied[self.cursor],
+            Key::Submit => return Outcome::Submit,
+            Key::Cancel => return Outcome::Cancel,
+            _ => {}
+        }
+        Outcome::Continue
+    }
+
+    /// Swaps the selected choice with the choice at `other`. The ties stay in place, so
+    /// the moved choice takes the rank of its new position.
+    fn swap(&mut self, other: usize) {
+        self.choices.swap(self.cursor, other);
+    }
+
+    /// Returns the ranks of the choices, where tied choices share the rank of the first
+    /// choice of their group.
+    pub fn ranks(&self) -> Vec<u32> {
+        let mut rank = 0;
+        self.tied
+            .iter()
+            .map(|tied| {
+                if !tied {
+                    rank += 1;
+                }
+                rank.max(1)
+            })
+            .collect()
+    }
+
+    /// Returns the ballot of the ranked choices.
+    pub fn ballot(&self) -> Vec<(String, Rank)> {
+        self.choices
+            .iter()
+            .cloned()
+            .zip(self.ranks())
+            .map(|(choice, rank)| (choice, Rank::new(rank).expect("ranks start from 1")))
+            .collect()
+    }
+
+    /// Renders the ranking with the help text.
+    pub fn render(&self, title: &str) -> String {
+        let mut out = format!("{}\n\n", title);
+        for (index, (choice, rank)) in self.choices.iter().zip(self.ranks()).enumerate() {
+            let marker = match (index == self.cursor, self.picked) {
+                (true, true) => "=>",
+                (true, false) => "> ",
+                _ => "  ",
+            };
+            writeln!(out, "{} {:>3}. {}", marker, rank, choice).unwrap();
+        }
+        out.push('\n');
+        out.push_str(HELP);
+        out.push('\n');
+        out
+    }
+}
+
+/// Lets the user rank the choices in the terminal, and returns the ballot, or `None` if
+/// the user cancelled.
+#[cfg(unix)]
+pub fn rank(title: &str, choices: Vec<String>) -> io::Result<Option<Vec<(String, Rank)>>> {
+    let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+    let _raw = RawMode::enable()?;
+    let mut ranking = Ranking::new(choices);
+
+    loop {
+        // clear the screen and move the cursor home before every redraw
+        let screen = ranking.render(title).replace('\n', "\r\n");
+        write!(tty, "\x1b[2J\x1b[H{}", screen)?;
+        tty.flush()?;
+
+        match ranking.handle(read_key(&mut tty)?) {
+            Outcome::Continue => {}
+            Outcome::Submit => return Ok(Some(ranking.ballot())),
+            Outcome::Cancel => return Ok(None),
+        }
+    }
+}
+
+/// The interactive ranking needs a Unix terminal, which is configured with `stty`.
+#[cfg(not(unix))]
+pub fn rank(_title: &str, _choices: Vec<String>) -> io::Result<Option<Vec<(String, Rank)>>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "interactive ranking is only supported on Unix terminals, pass the ballot as CHOICE=RANK arguments",
+    ))
+}
+
+/// Reads a key press, decoding the escape sequences of the arrow keys.
+#[cfg(unix)]
+fn read_key(tty: &mut File) -> io::Result<Key> {
+    let mut byte = [0u8; 1];
+    tty.read_exact(&mut byte)?;
+    let key = match byte[0] {
+        b'\x1b' => {
+            let mut sequence = [0u8; 2];
+            tty.read_exact(&mut sequence)?;
+            match sequence {
+                [b'[', b'A'] | [b'O', b'A'] => Key::Up,
+                [b'[', b'B'] | [b'O', b'B'] => Key::Down,
+                _ => Key::Other,
+            }
+        }
+        b'k' => Key::Up,
+        b'j' => Key::Down,
+        b' ' => Key::Pick,
+        b't' => Key::Tie,
+        b'\r' | b'\n' => Key::Submit,
+        b'q' | 3 => Key::Cancel,
+        _ => Key::Other,
+    };
+    Ok(key)
+}
+
+/// Switches the terminal to unbuffered input without echo with `stty`, and restores the
+/// previous settings when dropped.
+///
+/// Signals are disabled too, so Ctrl-C is read as the cancel key instead of killing the
+/// process before the settings are restored.
+#[cfg(unix)]
+struct RawMode {
+    saved: String,
+}
+
+#[cfg(unix)]
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "-isig", "min", "1"])?;
+        Ok(Self {
+            saved: saved.trim().to_string(),
+        })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[self.saved.as_str()]);
+    }
+}
+
+#[cfg(unix)]
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::from(File::open("/dev/tty")?))
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranking() -> Ranking {
+        Ranking::new(vec![
+            "Spinoza".to_string(),
+            "Kant".to_string(),
+            "Hume".to_string(),
+        ])
+    }
+
+    fn ballot(ranking: &Ranking) -> Vec<(&str, u32)> {
+        ranking
+            .choices
+            .iter()
+            .map(String::as_str)
+            .zip(ranking.ranks())
+            .collect()
+    }
+
+    #[test]
+    fn move_choice_test() {
+        let mut ranking = ranking();
+
+        // pick up Spinoza and move it to the bottom
+        for key in [Key::Pick, Key::Down, Key::Down, Key::Down, Key::Pick] {
+            assert_eq!(ranking.handle(key), Outcome::Continue);
+        }
+        assert_eq!(ballot(&ranking), [("Kant", 1), ("Hume", 2), ("Spinoza", 3)]);
+
+        // the selection moves without a picked choice
+        ranking.handle(Key::Up);
+        assert_eq!(ranking.cursor, 1);
+        assert_eq!(ballot(&ranking), [("Kant", 1), ("Hume", 2), ("Spinoza", 3)]);
+
+        assert_eq!(ranking.handle(Key::Submit), Outcome::Submit);
+        assert_eq!(ranking.handle(Key::Cancel), Outcome::Cancel);
+    }
+
+    #[test]
+    fn tie_test() {
+        let mut ranking = ranking();
+
+        // the first choice cannot be tied
+        ranking.handle(Key::Tie);
+        assert_eq!(ballot(&ranking), [("Spinoza", 1), ("Kant", 2), ("Hume", 3)]);
+
+        ranking.handle(Key::Down);
+        ranking.handle(Key::Tie);
+        assert_eq!(ballot(&ranking), [("Spinoza", 1), ("Kant", 1), ("Hume", 2)]);
+
+        ranking.handle(Key::Down);
+        ranking.handle(Key::Tie);
+        assert_eq!(ballot(&ranking), [("Spinoza", 1), ("Kant", 1), ("Hume", 1)]);
+
+        assert_eq!(ranking.ballot()[2], ("Hume".to_string(), Rank::HIGHEST));
+    }
+
+    #[test]
+    fn render_test() {
+        let mut ranking = ranking();
+        ranking.handle(Key::Down);
+        ranking.handle(Key::Pick);
+
+        assert!(ranking
+            .render("Voting abc")
+            .starts_with("Voting abc\n\n     1. Spinoza\n=>   2. Kant\n     3. Hume\n"));
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+//! `ddclient` is a command line client of the Direct Decisions API.
+//!
+//! Run `ddclient --help` for the list of commands. This binary is built with the `cli`
+//! feature.
+
+mod args;
+mod config;
+mod interactive;
+mod output;
+mod watch;
+
+use args::{Args, Command, Output, USAGE};
+use config::Config;
+use ddclient_rs::{Client, Rank, Voting};
+use serde_json::json;
+use std::error::Error;
+use std::process::ExitCode;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    if args.command == Command::Help {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let client = match client(&args) {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&client, args.command, args.output).await {
+        Ok(out) => {
+            print!("{}", out);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Creates the client from the command line options and the configuration.
+fn client(args: &Args) -> Result<Client, String> {
+    let config = Config::load()?;
+    let api_key = config
+        .api_key
+        .ok_or("missing API key, set DDCLIENT_API_KEY or api_key in the config file".to_string())?;
+
+    let mut builder = Client::builder(api_key);
+    if let Some(api_url) = args.api_url.clone().or(config.api_url) {
+        builder = builder.api_url(api_url);
+    }
+    builder.try_build().map_err(|err| err.to_string())
+}
+
+/// Runs the command, and returns its output.
+async fn run(
+    client: &Client,
+    command: Command,
+    format: Output,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let table = format == Output::Table;
+
+    let out = match command {
+        Command::Create { choices } => {
+            let voting = client.create_voting(choices).await?;
+            if table {
+                output::voting_table(&voting)
+            } else {
+                output::json(&voting)
+            }
+        }
+        Command::Get { voting_id } => {
+            let voting = client.get_voting(&voting_id).await?;
+            if table {
+                output::voting_table(&voting)
+            } else {
+                output::json(&voting)
+            }
+        }
+        Command::Delete { voting_id } => {
+            client.delete_voting(&voting_id).await?;
+            if table {
+                format!("Deleted voting {}\n", voting_id)
+            } else {
+                output::json(&json!({"deleted": true}))
+            }
+        }
+        Command::SetChoice {
+            voting_id,
+            choice,
+            index,
+        } => {
+            let choices = client.set_choice(&voting_id, &choice, index).await?;
+            if table {
+                output::choices_table(choices.iter())
+            } else {
+                output::json(&json!({"choices": choices}))
+            }
+        }
+        Command::Vote {
+            voting_id,
+            voter_id,
+            ballot,
+        } => vote(client, &voting_id, &voter_id, ballot, table).await?,
+        Command::VoteInteractive {
+            voting_id,
+            voter_id,
+        } => {
+            let voting = client.get_voting(&voting_id).await?;
+            let title = ballot_title(&voting, &voter_id);
+            let choices = voting.choices.into_vec();
+
+            let ballot =
+                tokio::task::spawn_blocking(move || interactive::rank(&title, choices)).await??;
+            let Some(ballot) = ballot else {
+                return Err("cancelled, the ballot was not submitted".into());
+            };
+            vote(client, &voting_id, &voter_id, ballot, table).await?
+        }
+        Command::Unvote {
+            voting_id,
+            voter_id,
+        } => {
+            client.unvote(&voting_id, &voter_id).await?;
+            if table {
+                format!("Removed the ballot of {}\n", voter_id)
+            } else {
+                output::json(&json!({"removed": true}))
+            }
+        }
+        Command::Ballot {
+            voting_id,
+            voter_id,
+        } => {
+            let ballot = client.get_ballot(&voting_id, &voter_id).await?;
+            if table {
+                output::ballot_table(&ballot)
+            } else {
+                output::json(&json!({"ballot": ballot}))
+            }
+        }
+        Command::Results { voting_id } => {
+            let results = client.get_voting_results(&voting_id).await?;
+            if table {
+                output::results_table(&results)
+            } else {
+                output::json(&results)
+            }
+        }
+        Command::WatchResults {
+            voting_id,
+            interval,
+            until,
+        } => watch::watch(client, &voting_id, interval, until, table).await?,
+        Command::Help => USAGE.to_string(),
+    };
+    Ok(out)
+}
+
+/// Submits the ballot, and returns the output of the vote command.
+async fn vote(
+    client: &Client,
+    voting_id: &str,
+    voter_id: &str,
+    ballot: Vec<(String, Rank)>,
+    table: bool,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let revoted = client.vote(voting_id, voter_id, ballot).await?;
+    let out = if table {
+        match revoted {
+            true => format!("Replaced the ballot of {}\n", voter_id),
+            false => format!("Submitted the ballot of {}\n", voter_id),
+        }
+    } else {
+        output::json(&json!({"revoted": revoted}))
+    };
+    Ok(out)
+}
+
+/// Returns the title of the interactive ballot of the voter, with the title of the voting
+/// if it is known.
+fn ballot_title(voting: &Voting, voter_id: &str) -> String {
+    #[cfg(feature = "experimental")]
+    if let Some(title) = &voting.title {
+        return format!("{} - ballot of {}", title, voter_id);
+    }
+    format!("Voting {} - ballot of {}", voting.id, voter_id)
+}
@@ -0,0 +1,90 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use ddclient_rs::{render_table, Rank, TableFormat, Voting, VotingResults};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Renders a value as pretty printed JSON.
+pub fn json<T: Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string_pretty(value).expect("values of the API serialize to JSON") + "\n"
+}
+
+/// Renders a voting as a table of its choices.
+pub fn voting_table(voting: &Voting) -> String {
+    let mut table = format!("Voting {}\n", voting.id);
+    #[cfg(feature = "experimental")]
+    if let Some(title) = &voting.title {
+        writeln!(table, "{}", title).unwrap();
+    }
+    table.push('\n');
+    table.push_str(&choices_table(voting.choices.iter()));
+    table
+}
+
+/// Renders the choices of a voting with their indices.
+pub fn choices_table<S: AsRef<str>>(choices: impl IntoIterator<Item = S>) -> String {
+    let mut table = String::from("Index  Choice\n-----  ------\n");
+    for (index, choice) in choices.into_iter().enumerate() {
+        writeln!(table, "{:>5}  {}", index, choice.as_ref()).unwrap();
+    }
+    table
+}
+
+/// Renders a ballot as a table of its choices, ordered by their ranks.
+pub fn ballot_table(ballot: &HashMap<String, Rank>) -> String {
+    let mut entries: Vec<(&String, &Rank)> = ballot.iter().collect();
+    entries.sort_by(|a, b| a.1.cmp(b.1).then_with(|| a.0.cmp(b.0)));
+
+    let mut table = String::from("Rank  Choice\n----  ------\n");
+    for (choice, rank) in entries {
+        writeln!(table, "{:>4}  {}", rank.get(), choice).unwrap();
+    }
+    table
+}
+
+/// Renders the results of a voting as a table of the standings.
+pub fn results_table(results: &VotingResults) -> String {
+    let mut table = render_table(results, TableFormat::Ascii);
+    if results.tie {
+        table.push_str("\nThe voting is tied.\n");
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ballot_table_test() {
+        let ballot = HashMap::from([
+            ("Kant".to_string(), Rank::new(2).unwrap()),
+            ("Spinoza".to_string(), Rank::HIGHEST),
+            ("Hume".to_string(), Rank::new(2).unwrap()),
+        ]);
+
+        assert_eq!(
+            ballot_table(&ballot),
+            "Rank  Choice\n\
+             ----  ------\n   \
+                1  Spinoza\n   \
+                2  Hume\n   \
+                2  Kant\n"
+        );
+    }
+
+    #[test]
+    fn choices_table_test() {
+        assert_eq!(
+            choices_table(["Spinoza", "Kant"]),
+            "Index  Choice\n\
+             -----  ------\n    \
+                 0  Spinoza\n    \
+                 1  Kant\n"
+        );
+    }
+}
@@ -0,0 +1,328 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::args::Until;
+use crate::output;
+#[cfg(feature = "experimental")]
+use ddclient_rs::ApiError;
+use ddclient_rs::{diff, format_percentage, ChoiceChange, Client, Percentage, VotingResults};
+use serde_json::json;
+use std::error::Error;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+/// The number of polls in a row a unique leader must lead the results for the watch to
+/// end with it as the winner.
+const STABLE_POLLS: u32 = 3;
+
+/// Polls the results of the voting, and redraws them whenever they change, with the changes
+/// since the previous poll. Returns the winner once a unique leader leads the results for
+/// `STABLE_POLLS` polls in a row, or, with `Until::Closed`, once the voting is closed.
+/// Returns an empty output when interrupted with Ctrl-C.
+///
+/// With the JSON output, every change of the results is printed on a separate line instead.
+pub async fn watch(
+    client: &Client,
+    voting_id: &str,
+    interval: Duration,
+    until: Until,
+    table: bool,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let mut polls = tokio::time::interval(interval);
+    polls.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    let mut previous: Option<VotingResults> = None;
+    let mut stable = 0;
+    let mut stdout = io::stdout();
+    loop {
+        tokio::select! {
+            _ = polls.tick() => {}
+            _ = &mut ctrl_c => return Ok(String::new()),
+        }
+
+        // the status is polled before the results, so the results of a closed voting are final
+        #[cfg(feature = "experimental")]
+        let closed = until == Until::Closed && is_closed(client, voting_id).await?;
+        let results = match client.get_voting_results(voting_id).await {
+            Ok(results) => results,
+            // the results are polled again once the rate limit allows requests
+            Err(err) if err.is_rate_limited() => continue,
+            Err(err) => return Err(err.into()),
+        };
+
+        let leader = winner(&results);
+        stable = match (leader, previous.as_ref().and_then(winner)) {
+            (Some(leader), Some(last)) if leader == last => stable + 1,
+            (Some(_), _) => 1,
+            (None, _) => 0,
+        };
+        if previous.as_ref() != Some(&results) {
+            draw(&mut stdout, &results, previous.as_ref(), table)?;
+        }
+        previous = Some(results);
+
+        let done = match until {
+            Until::Winner => stable >= STABLE_POLLS,
+            #[cfg(feature = "experimental")]
+            Until::Closed => closed,
+        };
+        if done {
+            break;
+        }
+    }
+
+    let winner = previous.as_ref().and_then(winner);
+    Ok(match (table, winner) {
+        (true, Some(winner)) => format!("\nWinner: {}\n", winner),
+        (true, None) => "\nNo winner, the results are tied\n".to_string(),
+        (false, _) => output::json(&json!({ "winner": winner })),
+    })
+}
+
+/// Reports whether the voting is closed, or not yet known to be closed while the requests
+/// are rate limited.
+#[cfg(feature = "experimental")]
+async fn is_closed(client: &Client, voting_id: &str) -> Result<bool, ApiError> {
+    match client.get_voting(voting_id).await {
+        Ok(voting) => Ok(voting.is_closed()),
+        Err(err) if err.is_rate_limited() => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Prints the results, as a redrawn table or as a line of JSON.
+fn draw(
+    stdout: &mut io::Stdout,
+    results: &VotingResults,
+    previous: Option<&VotingResults>,
+    table: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if table {
+        // clear the screen and move the cursor home before every redraw
+        write!(stdout, "\x1b[2J\x1b[H{}", frame(results, previous))?;
+    } else {
+        writeln!(stdout, "{}", serde_json::to_string(results)?)?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Returns the choice with the most wins, unless the results are tied.
+fn winner(results: &VotingResults) -> Option<&str> {
+    if results.tie {
+        return None;
+    }
+    let wins = results.results.iter().map(|r| r.wins).max()?;
+    let mut leaders = results.results.iter().filter(|r| r.wins == wins);
+    match (leaders.next(), leaders.next()) {
+        (Some(winner), None) => Some(&winner.choice),
+        _ => None,
+    }
+}
+
+/// Renders the results table, followed by the changes since the previous results.
+fn frame(results: &VotingResults, previous: Option<&VotingResults>) -> String {
+    let mut out = output::results_table(results);
+    let Some(previous) = previous else {
+        return out;
+    };
+
+    let changes = diff(previous, results);
+    if changes.is_empty() {
+        return out;
+    }
+    out.push_str("\nChanges:\n");
+    for change in changes.changed() {
+        writeln!(out, "  {}", describe(change)).unwrap();
+    }
+    for choice in &changes.removed {
+        writeln!(out, "  {} removed", choice).unwrap();
+    }
+    for group in &changes.new_ties {
+        writeln!(out, "  new tie between {}", group.join(", ")).unwrap();
+    }
+    out
+}
+
+fn describe(change: &ChoiceChange) -> String {
+    let mut line = match change.position_change() {
+        None => format!("{} new at position {}", change.choice, change.new_position),
+        Some(0) => format!("{} at position {}", change.choice, change.new_position),
+        Some(up) if up > 0 => format!(
+            "{} up {} to position {}",
+            change.choice, up, change.new_position
+        ),
+        Some(down) => format!(
+            "{} down {} to position {}",
+            change.choice, -down, change.new_position
+        ),
+    };
+    if change.percentage_delta != Percentage::default() {
+        let sign = if change.percentage_delta > Percentage::default() {
+            "+"
+        } else {
+            ""
+        };
+        write!(
+            line,
+            ", {}{}%",
+            sign,
+            format_percentage(change.percentage_delta, 2)
+        )
+        .unwrap();
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ddclient_rs::{schulze, Rank};
+    use httpmock::prelude::*;
+    use std::collections::HashMap;
+
+    fn results(ballots: &[&[&str]]) -> VotingResults {
+        let choices = ["Spinoza", "Kant", "Hume"].map(String::from);
+        let ballots: Vec<HashMap<String, Rank>> = ballots
+            .iter()
+            .map(|ballot| {
+                ballot
+                    .iter()
+                    .zip(1..)
+                    .map(|(choice, rank)| (choice.to_string(), Rank::new(rank).unwrap()))
+                    .collect()
+            })
+            .collect();
+        schulze::compute(&choices, &ballots)
+    }
+
+    #[test]
+    fn winner_test() {
+        assert_eq!(winner(&results(&[])), None);
+        assert_eq!(winner(&results(&[&["Kant", "Hume"]])), Some("Kant"));
+    }
+
+    #[test]
+    fn frame_test() {
+        let before = results(&[&["Kant", "Hume"]]);
+        let after = results(&[&["Kant", "Hume"], &["Hume", "Kant"], &["Hume", "Spinoza"]]);
+
+        assert_eq!(frame(&before, None), output::results_table(&before));
+
+        let frame = frame(&after, Some(&before));
+        assert!(frame.starts_with(&output::results_table(&after)));
+        assert!(frame.ends_with(
+            "\nChanges:\n  \
+             Hume up 1 to position 1, +50.00%\n  \
+             Kant down 1 to position 2, -50.00%\n"
+        ));
+    }
+
+    /// Serves the results of a voting, from the given ballots.
+    fn serve_results<'a>(server: &'a MockServer, ballots: &[&[&str]]) -> httpmock::Mock<'a> {
+        let results = serde_json::to_value(results(ballots)).unwrap();
+        server.mock(|when, then| {
+            when.method(GET).path("/v1/votings/abc/results");
+            then.status(200).json_body(results);
+        })
+    }
+
+    fn client(server: &MockServer) -> Client {
+        Client::builder("test-token".to_string())
+            .api_url(server.base_url())
+            .try_build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn watch_winner_test() {
+        let server = MockServer::start();
+        let results = serve_results(&server, &[&["Kant", "Hume"]]);
+        let client = client(&server);
+
+        let out = watch(
+            &client,
+            "abc",
+            Duration::from_millis(20),
+            Until::Winner,
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(out, "\nWinner: Kant\n");
+        assert_eq!(results.hits(), STABLE_POLLS as usize);
+    }
+
+    #[tokio::test]
+    async fn watch_tie_test() {
+        let server = MockServer::start();
+        let results = serve_results(&server, &[&["Kant", "Hume"], &["Hume", "Kant"]]);
+        let client = client(&server);
+
+        // tied results do not end the watch
+        let watch = watch(
+            &client,
+            "abc",
+            Duration::from_millis(20),
+            Until::Winner,
+            false,
+        );
+        assert!(tokio::time::timeout(Duration::from_millis(300), watch)
+            .await
+            .is_err());
+        assert!(results.hits() > STABLE_POLLS as usize);
+    }
+
+    #[cfg(feature = "experimental")]
+    fn serve_status<'a>(server: &'a MockServer, status: &str) -> httpmock::Mock<'a> {
+        server.mock(|when, then| {
+            when.method(GET).path("/v1/votings/abc");
+            then.status(200).json_body(json!({
+                "id": "abc",
+                "choices": ["Spinoza", "Kant", "Hume"],
+                "status": status,
+            }));
+        })
+    }
+
+    #[cfg(feature = "experimental")]
+    #[tokio::test]
+    async fn watch_until_closed_test() {
+        let server = MockServer::start();
+        serve_results(&server, &[&["Kant", "Hume"]]);
+        let mut open = serve_status(&server, "open");
+        let client = client(&server);
+
+        // a stable leader does not end the watch while the voting is open
+        let watch_open = watch(
+            &client,
+            "abc",
+            Duration::from_millis(20),
+            Until::Closed,
+            false,
+        );
+        assert!(tokio::time::timeout(Duration::from_millis(300), watch_open)
+            .await
+            .is_err());
+        assert!(open.hits() > STABLE_POLLS as usize);
+
+        open.delete();
+        serve_status(&server, "closed");
+        let out = watch(
+            &client,
+            "abc",
+            Duration::from_millis(20),
+            Until::Closed,
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(out, "\nWinner: Kant\n");
+    }
+}
@@ -0,0 +1,1461 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+//! A blocking client for the Direct Decisions API.
+//!
+//! The blocking `Client` mirrors the asynchronous `ddclient_rs::Client`, but its methods
+//! block the current thread until the response is received, so it can be used from CLI
+//! tools and scripts without setting up an async runtime.
+//!
+//! This module is available with the `blocking` feature. The blocking client must not be
+//! used from within an async runtime, use the asynchronous client there instead.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use ddclient_rs::blocking::Client;
+//!
+//! let client = Client::try_new("my-api-key".to_string()).unwrap();
+//! let voting = client
+//!     .create_voting(vec!["Einstein".to_string(), "Newton".to_string()])
+//!     .unwrap();
+//! println!("Created voting: {:?}", voting);
+//! ```
+
+use crate::bulk::{rate_limit_delay, should_retry, RateLimitPause};
+use crate::choices::set_choices_plan;
+use crate::client::{
+    found, ordered_ballot, ranked_ballot, rename_choice_index, set_choice_index, validate_api_url,
+    validate_set_choices, validate_token, Ballot, OkResponse, SetChoiceRequest, SetChoiceResponse,
+    VoteResponse, VotingRequest, REDACTED,
+};
+use crate::endpoint::Endpoint;
+use crate::http_config::HttpConfig;
+use crate::limiter::BlockingLimiter;
+use crate::options::{is_mutating, HEADER_IDEMPOTENCY_KEY};
+use crate::rate::{EndpointFamily, EndpointRates};
+#[cfg(feature = "rate-limit")]
+use crate::rate_limiter::RateLimiter;
+use crate::retry::is_transient;
+use crate::strict::{self, StrictResponses};
+use crate::throttle::Throttle;
+use crate::validation::{rejected, validate_choice};
+use crate::{
+    api_error, request_id, ApiError, ApiVersion, BadRequestError, BuildError, BulkOptions,
+    ChoiceIndex, Choices, ClientError, DeleteVotingsReport, ErrorContext, HttpVersion,
+    LastErrorContext, PairwiseMatrix, Rank, Rate, ReportFormat, RequestOptions, RetryPolicy,
+    Voting, VotingResults, VotingSummary, CONTENT_TYPE, USER_AGENT,
+};
+#[cfg(feature = "experimental")]
+use crate::{BallotsPage, ListOptions, VoterBallot, VotingMetadata, VotingsPage};
+
+use reqwest::blocking::Response;
+use reqwest::header::{self, HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, StatusCode};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// A blocking client for accessing the Direct Decisions API.
+///
+/// This struct provides the same methods as the asynchronous `ddclient_rs::Client`, which
+/// block until the response is received.
+/// All possible Error responses are described in the ApiError enum.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddclient_rs::blocking::Client;
+///
+/// let client = Client::try_new("my-api-key".to_string()).unwrap();
+/// // Use client to interact with the API...
+/// ```
+pub struct Client {
+    /// The `Authorization` header value of the API token, created once with the client.
+    authorization: HeaderValue,
+    client: reqwest::blocking::Client,
+    api_url: String,
+    api_version: ApiVersion,
+    rate: Arc<Mutex<Option<Rate>>>,
+    endpoint_rates: EndpointRates,
+    last_request_id: Mutex<Option<String>>,
+    last_error_context: LastErrorContext,
+    retry_policy: Option<RetryPolicy>,
+    idempotency_keys: bool,
+    validate_ballots: bool,
+    strict_responses: bool,
+    throttle: Option<Throttle>,
+    limiter: Option<BlockingLimiter>,
+    #[cfg(feature = "rate-limit")]
+    rate_limiter: Option<RateLimiter>,
+    timeout: Option<Duration>,
+    default_headers: HeaderMap,
+}
+
+/// Formats the configuration of the client, with the API token redacted.
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("token", &format_args!("{}", REDACTED))
+            .field("api_url", &self.api_url)
+            .field("api_version", &self.api_version)
+            .field("retry_policy", &self.retry_policy)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Constructs a new blocking `Client` with the given API token, and the default API URL.
+    /// If you need to configure the client, use `Client::builder` instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the token is empty or contains characters which are not allowed in a header,
+    /// or if the internal Reqwest client cannot be created. Use `Client::try_new` to handle
+    /// these errors instead.
+    #[deprecated(
+        since = "0.1.4",
+        note = "use `try_new`, which returns an error instead of panicking"
+    )]
+    pub fn new(token: String) -> Self {
+        Self::try_new(token).expect("Invalid client configuration")
+    }
+
+    /// Constructs a new blocking `Client` with the given API token, and the default API URL.
+    /// See `ddclient_rs::Client::try_new`.
+    pub fn try_new(token: String) -> Result<Self, BuildError> {
+        Self::builder(token).try_build()
+    }
+
+    /// Creates a new `ClientBuilder` for constructing a blocking `Client`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddclient_rs::blocking::Client;
+    ///
+    /// let client = Client::builder("my-api-key".to_string())
+    ///     .api_url("https://custom-api.directdecisions.com".to_string())
+    ///     .try_build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder(token: String) -> ClientBuilder {
+        ClientBuilder::new(token)
+    }
+
+    /// Retrieves the current rate limit information.
+    ///
+    /// This method returns the most recent rate limit information as received
+    /// from the Direct Decisions API, if available.
+    pub fn get_rate(&self) -> Option<Rate> {
+        let rate = self.rate.lock().unwrap();
+        rate.clone()
+    }
+
+    /// Retrieves the rate limit of the last response from an endpoint of the family.
+    ///
+    /// See `ddclient_rs::Client::get_rate_for`.
+    pub fn get_rate_for(&self, family: EndpointFamily) -> Option<Rate> {
+        self.endpoint_rates.get(family)
+    }
+
+    /// Returns the version of the API used by the client, set with
+    /// `ClientBuilder::api_version`.
+    pub fn api_version(&self) -> ApiVersion {
+        self.api_version
+    }
+
+    /// Retrieves the ID of the last request, as sent by the API in the `X-Request-Id`
+    /// response header.
+    pub fn get_last_request_id(&self) -> Option<String> {
+        self.last_request_id.lock().unwrap().clone()
+    }
+
+    /// Returns the context of the last request that failed with an `ApiError`.
+    ///
+    /// See `ddclient_rs::Client::last_error_context`.
+    pub fn last_error_context(&self) -> Option<ErrorContext> {
+        self.last_error_context.get()
+    }
+
+    fn request<T: serde::Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<T>,
+        options: &RequestOptions,
+    ) -> Result<Response, ClientError> {
+        let _permit = self.limiter.as_ref().map(BlockingLimiter::acquire);
+
+        if let Some(throttle) = &self.throttle {
+            let delay = throttle.delay(self.rate.lock().unwrap().as_ref());
+            if !delay.is_zero() {
+                std::thread::sleep(delay);
+            }
+        }
+
+        #[cfg(feature = "rate-limit")]
+        if let Some(rate_limiter) = &self.rate_limiter {
+            let delay = rate_limiter.delay();
+            if !delay.is_zero() {
+                std::thread::sleep(delay);
+            }
+        }
+
+        let url = format!("{}{}", self.api_url, path);
+        let context = ErrorContext::new(method.clone(), path);
+        let generate_key = self.idempotency_keys && is_mutating(&method);
+        let replayable = method.is_idempotent()
+            || options.has_idempotency_key(generate_key)
+            || self.default_headers.contains_key(HEADER_IDEMPOTENCY_KEY);
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header(header::AUTHORIZATION, self.authorization.clone())
+            .header(header::ACCEPT, HeaderValue::from_static(CONTENT_TYPE))
+            .header(header::USER_AGENT, HeaderValue::from_static(USER_AGENT));
+
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+
+        if !self.default_headers.is_empty() {
+            request = request.headers(self.default_headers.clone());
+        }
+
+        request = options.apply_blocking(request, generate_key);
+
+        if let Some(b) = body {
+            request = request.header(header::CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE));
+            request = request.json(&b);
+        }
+
+        let mut attempt = 1;
+        let response = loop {
+            let retry = self
+                .retry_policy
+                .as_ref()
+                .filter(|policy| policy.should_retry(attempt))
+                .and_then(|policy| Some((policy.delay(attempt), request.try_clone()?)));
+
+            let response = request.send();
+
+            if let Ok(response) = &response {
+                let rate_update = Rate::from_headers(response.headers());
+                if let Some(rate) = &rate_update {
+                    self.endpoint_rates.update(path, rate);
+                }
+                let mut rate = self.rate.lock().unwrap();
+                *rate = rate_update;
+                *self.last_request_id.lock().unwrap() = request_id(response.headers());
+            }
+
+            match retry {
+                Some((delay, next))
+                    if is_transient(response.as_ref().map(Response::status), replayable) =>
+                {
+                    std::thread::sleep(delay);
+                    request = next;
+                    attempt += 1;
+                }
+                _ => break response,
+            }
+        };
+
+        match response {
+            Ok(mut response) => {
+                response.extensions_mut().insert(context);
+                response
+                    .extensions_mut()
+                    .insert(self.last_error_context.clone());
+                if self.strict_responses {
+                    response.extensions_mut().insert(StrictResponses);
+                }
+                Ok(response)
+            }
+            Err(err) => {
+                self.last_error_context.set(context);
+                Err(ClientError::HttpRequestError(err.without_url()))
+            }
+        }
+    }
+
+    /// Creates a new voting with the specified choices.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::blocking::Client;
+    ///
+    /// let client = Client::try_new("my-api-key".to_string()).unwrap();
+    /// let result = client.create_voting(["Option 1", "Option 2"]);
+    /// // Handle result...
+    /// ```
+    pub fn create_voting(
+        &self,
+        choices: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Voting, ApiError> {
+        self.create_voting_with_options(choices, RequestOptions::none())
+    }
+
+    /// Same as `Client::create_voting`, but applies the given `RequestOptions` to the request.
+    pub fn create_voting_with_options(
+        &self,
+        choices: impl IntoIterator<Item = impl Into<String>>,
+        request_options: &RequestOptions,
+    ) -> Result<Voting, ApiError> {
+        let choices = choices.into_iter().map(Into::into).collect();
+        self.post_voting(VotingRequest::new(choices), request_options)
+    }
+
+    /// Returns a `CreateVotingBuilder` for creating a voting with the optional parameters
+    /// of a new voting.
+    ///
+    /// See `ddclient_rs::Client::create_voting_builder`.
+    pub fn create_voting_builder(&self) -> CreateVotingBuilder<'_> {
+        CreateVotingBuilder {
+            client: self,
+            request: VotingRequest::default(),
+            request_options: RequestOptions::new(),
+        }
+    }
+
+    fn post_voting(
+        &self,
+        request: VotingRequest,
+        request_options: &RequestOptions,
+    ) -> Result<Voting, ApiError> {
+        request.validate()?;
+
+        let response = self.request(
+            Method::POST,
+            Endpoint::votings(self.api_version).as_str(),
+            Some(request),
+            request_options,
+        )?;
+
+        handle_api_response(response)
+    }
+
+    /// Retrieves a voting by its ID.
+    pub fn get_voting(&self, id: &str) -> Result<Voting, ApiError> {
+        self.get_voting_with_options(id, RequestOptions::none())
+    }
+
+    /// Same as `Client::get_voting`, but applies the given `RequestOptions` to the request.
+    pub fn get_voting_with_options(
+        &self,
+        id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<Voting, ApiError> {
+        let uri = Endpoint::voting(self.api_version, id);
+
+        let response = self.request::<Voting>(Method::GET, uri.as_str(), None, request_options)?;
+
+        handle_api_response(response)
+    }
+
+    /// Retrieves a voting by its ID, returning `Ok(None)` if it is not found.
+    pub fn get_voting_opt(&self, id: &str) -> Result<Option<Voting>, ApiError> {
+        found(self.get_voting(id))
+    }
+
+    /// Retrieves the metadata of a voting, such as its title and description.
+    ///
+    /// This method is available with the `experimental` feature. See
+    /// `ddclient_rs::Client::get_voting_metadata`.
+    #[cfg(feature = "experimental")]
+    pub fn get_voting_metadata(&self, voting_id: &str) -> Result<VotingMetadata, ApiError> {
+        self.get_voting_metadata_with_options(voting_id, RequestOptions::none())
+    }
+
+    /// Same as `Client::get_voting_metadata`, but applies the given `RequestOptions` to the
+    /// request.
+    #[cfg(feature = "experimental")]
+    pub fn get_voting_metadata_with_options(
+        &self,
+        voting_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<VotingMetadata, ApiError> {
+        let uri = Endpoint::voting(self.api_version, voting_id).segment("metadata");
+
+        let response =
+            self.request::<VotingMetadata>(Method::GET, uri.as_str(), None, request_options)?;
+
+        handle_api_response(response)
+    }
+
+    /// Sets the metadata of a voting, replacing its current metadata.
+    ///
+    /// This method is available with the `experimental` feature. See
+    /// `ddclient_rs::Client::set_voting_metadata`.
+    #[cfg(feature = "experimental")]
+    pub fn set_voting_metadata(
+        &self,
+        voting_id: &str,
+        metadata: VotingMetadata,
+    ) -> Result<VotingMetadata, ApiError> {
+        self.set_voting_metadata_with_options(voting_id, metadata, RequestOptions::none())
+    }
+
+    /// Same as `Client::set_voting_metadata`, but applies the given `RequestOptions` to the
+    /// request.
+    #[cfg(feature = "experimental")]
+    pub fn set_voting_metadata_with_options(
+        &self,
+        voting_id: &str,
+        metadata: VotingMetadata,
+        request_options: &RequestOptions,
+    ) -> Result<VotingMetadata, ApiError> {
+        let uri = Endpoint::voting(self.api_version, voting_id).segment("metadata");
+
+        let response = self.request(Method::PUT, uri.as_str(), Some(metadata), request_options)?;
+
+        handle_api_response(response)
+    }
+
+    /// Lists the votings created with the API key of the client, one page at a time.
+    ///
+    /// This method is available with the `experimental` feature. See
+    /// `ddclient_rs::Client::list_votings`.
+    #[cfg(feature = "experimental")]
+    pub fn list_votings(&self, options: &ListOptions) -> Result<VotingsPage, ApiError> {
+        self.list_votings_with_options(options, RequestOptions::none())
+    }
+
+    /// Same as `Client::list_votings`, but applies the given `RequestOptions` to the request.
+    #[cfg(feature = "experimental")]
+    pub fn list_votings_with_options(
+        &self,
+        options: &ListOptions,
+        request_options: &RequestOptions,
+    ) -> Result<VotingsPage, ApiError> {
+        let uri = options.append_query(Endpoint::votings(self.api_version));
+
+        let response = self.request::<Voting>(Method::GET, uri.as_str(), None, request_options)?;
+
+        handle_api_response(response)
+    }
+
+    /// Deletes a voting by its ID.
+    pub fn delete_voting(&self, id: &str) -> Result<(), ApiError> {
+        self.delete_voting_with_options(id, RequestOptions::none())
+    }
+
+    /// Same as `Client::delete_voting`, but applies the given `RequestOptions` to the request.
+    pub fn delete_voting_with_options(
+        &self,
+        id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<(), ApiError> {
+        let uri = Endpoint::voting(self.api_version, id);
+
+        let response =
+            self.request::<OkResponse>(Method::DELETE, uri.as_str(), None, request_options)?;
+
+        let _ = handle_api_response::<OkResponse>(response)?;
+
+        Ok(())
+    }
+
+    /// Closes a voting, so it stops accepting ballots.
+    ///
+    /// This method is available with the `experimental` feature. See
+    /// `ddclient_rs::Client::close_voting`.
+    #[cfg(feature = "experimental")]
+    pub fn close_voting(&self, id: &str) -> Result<Voting, ApiError> {
+        self.close_voting_with_options(id, RequestOptions::none())
+    }
+
+    /// Same as `Client::close_voting`, but applies the given `RequestOptions` to the request.
+    #[cfg(feature = "experimental")]
+    pub fn close_voting_with_options(
+        &self,
+        id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<Voting, ApiError> {
+        let response = self.request::<Voting>(
+            Method::POST,
+            Endpoint::voting(self.api_version, id)
+                .segment("close")
+                .as_str(),
+            None,
+            request_options,
+        )?;
+
+        handle_api_response(response)
+    }
+
+    /// Reopens a closed voting, so it accepts ballots again.
+    ///
+    /// This method is available with the `experimental` feature. See
+    /// `ddclient_rs::Client::reopen_voting`.
+    #[cfg(feature = "experimental")]
+    pub fn reopen_voting(&self, id: &str) -> Result<Voting, ApiError> {
+        self.reopen_voting_with_options(id, RequestOptions::none())
+    }
+
+    /// Same as `Client::reopen_voting`, but applies the given `RequestOptions` to the request.
+    #[cfg(feature = "experimental")]
+    pub fn reopen_voting_with_options(
+        &self,
+        id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<Voting, ApiError> {
+        let response = self.request::<Voting>(
+            Method::POST,
+            Endpoint::voting(self.api_version, id)
+                .segment("reopen")
+                .as_str(),
+            None,
+            request_options,
+        )?;
+
+        handle_api_response(response)
+    }
+
+    /// Deletes many votings by their IDs concurrently, from up to
+    /// `BulkOptions::concurrency` threads.
+    ///
+    /// See `ddclient_rs::Client::delete_votings`.
+    pub fn delete_votings<I>(&self, ids: I, options: &BulkOptions) -> DeleteVotingsReport
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let results = run_bulk(ids.into_iter().collect(), options, |id| {
+            self.delete_voting(id)
+        });
+        DeleteVotingsReport::from_results(results)
+    }
+
+    /// Sets or updates a choice in a voting.
+    ///
+    /// See `ddclient_rs::Client::set_choice` for the meaning of the index.
+    pub fn set_choice(
+        &self,
+        voting_id: &str,
+        choice: &str,
+        index: i32,
+    ) -> Result<Choices, ApiError> {
+        self.set_choice_with_options(voting_id, choice, index, RequestOptions::none())
+    }
+
+    /// Same as `Client::set_choice`, but applies the given `RequestOptions` to the request.
+    pub fn set_choice_with_options(
+        &self,
+        voting_id: &str,
+        choice: &str,
+        index: i32,
+        request_options: &RequestOptions,
+    ) -> Result<Choices, ApiError> {
+        let uri = Endpoint::voting(self.api_version, voting_id).segment("choices");
+        validate_choice(choice).map_err(rejected)?;
+
+        let response = self.request(
+            Method::POST,
+            uri.as_str(),
+            Some(SetChoiceRequest {
+                choice: choice.to_string(),
+                index,
+            }),
+            request_options,
+        )?;
+
+        let resp = handle_api_response::<SetChoiceResponse>(response)?;
+
+        Ok(resp.choices)
+    }
+
+    /// Adds a choice at the end of the choices of a voting, or moves an existing choice
+    /// to the end.
+    pub fn append_choice(&self, voting_id: &str, choice: &str) -> Result<Choices, ApiError> {
+        self.set_choice(voting_id, choice, 0)
+    }
+
+    /// Adds a choice at the beginning of the choices of a voting, or moves an existing
+    /// choice to the beginning.
+    pub fn prepend_choice(&self, voting_id: &str, choice: &str) -> Result<Choices, ApiError> {
+        self.insert_choice_at(voting_id, choice, ChoiceIndex::new(0))
+    }
+
+    /// Adds a choice at the given position of the choices of a voting, or moves an existing
+    /// choice to that position.
+    ///
+    /// See `ddclient_rs::Client::insert_choice_at` for the additional request it takes.
+    pub fn insert_choice_at(
+        &self,
+        voting_id: &str,
+        choice: &str,
+        position: ChoiceIndex,
+    ) -> Result<Choices, ApiError> {
+        let voting = self.get_voting(voting_id)?;
+        let index = set_choice_index(&voting.choices, choice, position)
+            .ok_or_else(|| rejected(BadRequestError::InvalidData))?;
+        self.set_choice(voting_id, choice, index)
+    }
+
+    /// Moves an existing choice to the given position of the choices of a voting.
+    pub fn move_choice(
+        &self,
+        voting_id: &str,
+        choice: &str,
+        position: ChoiceIndex,
+    ) -> Result<Choices, ApiError> {
+        self.insert_choice_at(voting_id, choice, position)
+    }
+
+    /// Replaces the choices of a voting with the given list.
+    ///
+    /// See `ddclient_rs::Client::set_choices` for the requests it takes.
+    pub fn set_choices(&self, voting_id: &str, choices: Vec<String>) -> Result<Choices, ApiError> {
+        validate_set_choices(&choices).map_err(rejected)?;
+
+        let mut current = self.get_voting(voting_id)?.choices;
+        for (choice, index) in set_choices_plan(&current, &choices) {
+            current = self.set_choice(voting_id, &choice, index)?;
+        }
+        Ok(current)
+    }
+
+    /// Renames a choice of a voting, keeping its position.
+    ///
+    /// See `ddclient_rs::Client::rename_choice` for the order of the changes and the
+    /// rollback if the removal of the old choice fails.
+    pub fn rename_choice(
+        &self,
+        voting_id: &str,
+        old: &str,
+        new: &str,
+    ) -> Result<Choices, ApiError> {
+        validate_choice(new).map_err(rejected)?;
+
+        let voting = self.get_voting(voting_id)?;
+        let Some(index) = rename_choice_index(&voting.choices, old, new)? else {
+            return Ok(voting.choices);
+        };
+
+        self.set_choice(voting_id, new, index)?;
+        match self.set_choice(voting_id, old, -1) {
+            Ok(choices) => Ok(choices),
+            Err(err) => {
+                let _ = self.set_choice(voting_id, new, -1);
+                Err(err)
+            }
+        }
+    }
+
+    /// Removes a choice from a voting.
+    pub fn remove_choice(&self, voting_id: &str, choice: &str) -> Result<Choices, ApiError> {
+        self.set_choice(voting_id, choice, -1)
+    }
+
+    /// Submits a vote on a specific voting.
+    ///
+    /// Returns a `Result` which is `Ok` indicating whether the vote was a revote,
+    /// or an `Err` with an `ApiError` if the voting is not found or the request fails.
+    pub fn vote(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        ballot: impl IntoIterator<Item = (impl Into<String>, Rank)>,
+    ) -> Result<bool, ApiError> {
+        self.vote_with_options(voting_id, voter_id, ballot, RequestOptions::none())
+    }
+
+    /// Same as `Client::vote`, but applies the given `RequestOptions` to the request.
+    pub fn vote_with_options(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        ballot: impl IntoIterator<Item = (impl Into<String>, Rank)>,
+        request_options: &RequestOptions,
+    ) -> Result<bool, ApiError> {
+        let ballot: HashMap<String, Rank> = ballot
+            .into_iter()
+            .map(|(choice, rank)| (choice.into(), rank))
+            .collect();
+        request_options.validate_ballot(&ballot, self.validate_ballots)?;
+
+        let uri = Endpoint::ballot(self.api_version, voting_id, voter_id);
+
+        let response = self.request(
+            Method::POST,
+            uri.as_str(),
+            Some(Ballot { ballot }),
+            request_options,
+        )?;
+
+        let response = handle_api_response::<VoteResponse>(response)?;
+
+        Ok(response.revoted)
+    }
+
+    /// Submits a vote on a specific voting, with the choices given in groups of equally
+    /// ranked choices.
+    ///
+    /// See `ddclient_rs::Client::vote_ranked` for the conversion of the groups to ranks.
+    pub fn vote_ranked(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        ranks: Vec<Vec<String>>,
+    ) -> Result<bool, ApiError> {
+        self.vote(voting_id, voter_id, ranked_ballot(ranks))
+    }
+
+    /// Submits a vote on a specific voting, with the choices ordered from the most
+    /// preferred.
+    ///
+    /// The choices get ranks from 1 in the given order. A repeated choice keeps its first
+    /// rank.
+    pub fn vote_ordered(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        choices: Vec<String>,
+    ) -> Result<bool, ApiError> {
+        self.vote(voting_id, voter_id, ordered_ballot(choices))
+    }
+
+    /// Submits the ballots of many voters on a specific voting concurrently, from up to
+    /// `BulkOptions::concurrency` threads.
+    ///
+    /// See `ddclient_rs::Client::vote_many`.
+    pub fn vote_many<I>(
+        &self,
+        voting_id: &str,
+        ballots: I,
+        options: &BulkOptions,
+    ) -> Vec<(String, Result<bool, ApiError>)>
+    where
+        I: IntoIterator<Item = (String, HashMap<String, Rank>)>,
+    {
+        run_bulk(
+            ballots.into_iter().collect(),
+            options,
+            |(voter_id, ballot)| self.vote(voting_id, voter_id, ballot.clone()),
+        )
+        .into_iter()
+        .map(|((voter_id, _), result)| (voter_id, result))
+        .collect()
+    }
+
+    /// Removes a voter's ballot from a specific voting.
+    pub fn unvote(&self, voting_id: &str, voter_id: &str) -> Result<(), ApiError> {
+        self.unvote_with_options(voting_id, voter_id, RequestOptions::none())
+    }
+
+    /// Same as `Client::unvote`, but applies the given `RequestOptions` to the request.
+    pub fn unvote_with_options(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<(), ApiError> {
+        let uri = Endpoint::ballot(self.api_version, voting_id, voter_id);
+
+        let response =
+            self.request::<OkResponse>(Method::DELETE, uri.as_str(), None, request_options)?;
+
+        let _ = handle_api_response::<OkResponse>(response)?;
+
+        Ok(())
+    }
+
+    /// Retrieves a ballot for a specific voting and voter.
+    pub fn get_ballot(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+    ) -> Result<HashMap<String, Rank>, ApiError> {
+        self.get_ballot_with_options(voting_id, voter_id, RequestOptions::none())
+    }
+
+    /// Same as `Client::get_ballot`, but applies the given `RequestOptions` to the request.
+    pub fn get_ballot_with_options(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<HashMap<String, Rank>, ApiError> {
+        let uri = Endpoint::ballot(self.api_version, voting_id, voter_id);
+
+        let response = self.request::<Ballot>(Method::GET, uri.as_str(), None, request_options)?;
+
+        let response = handle_api_response::<Ballot>(response)?;
+
+        Ok(response.ballot)
+    }
+
+    /// Retrieves a ballot for a specific voting and voter, returning `Ok(None)` if it is not
+    /// found.
+    pub fn get_ballot_opt(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+    ) -> Result<Option<HashMap<String, Rank>>, ApiError> {
+        found(self.get_ballot(voting_id, voter_id))
+    }
+
+    /// Checks whether a voter has submitted a ballot to a specific voting.
+    ///
+    /// See `ddclient_rs::Client::has_ballot`.
+    pub fn has_ballot(&self, voting_id: &str, voter_id: &str) -> Result<bool, ApiError> {
+        let ballot = self.get_ballot_opt(voting_id, voter_id)?;
+        Ok(ballot.is_some())
+    }
+
+    /// Lists the ballots submitted to a specific voting, one page at a time.
+    ///
+    /// To iterate over all ballots without handling pagination, use `Client::ballots_iter`.
+    ///
+    /// This method is available with the `experimental` feature. See
+    /// `ddclient_rs::Client::list_ballots`.
+    #[cfg(feature = "experimental")]
+    pub fn list_ballots(
+        &self,
+        voting_id: &str,
+        options: &ListOptions,
+    ) -> Result<BallotsPage, ApiError> {
+        self.list_ballots_with_options(voting_id, options, RequestOptions::none())
+    }
+
+    /// Same as `Client::list_ballots`, but applies the given `RequestOptions` to the request.
+    #[cfg(feature = "experimental")]
+    pub fn list_ballots_with_options(
+        &self,
+        voting_id: &str,
+        options: &ListOptions,
+        request_options: &RequestOptions,
+    ) -> Result<BallotsPage, ApiError> {
+        let uri =
+            options.append_query(Endpoint::voting(self.api_version, voting_id).segment("ballots"));
+
+        let response = self.request::<Ballot>(Method::GET, uri.as_str(), None, request_options)?;
+
+        handle_api_response(response)
+    }
+
+    /// Returns an iterator over all ballots submitted to a specific voting.
+    ///
+    /// The iterator yields `(voter_id, ballot)` pairs and transparently follows pagination,
+    /// fetching the next page only after all ballots of the previous page were consumed.
+    /// The iterator ends after the first error.
+    ///
+    /// This method is available with the `experimental` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::blocking::Client;
+    ///
+    /// fn main() -> Result<(), ddclient_rs::ApiError> {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///
+    ///     for ballot in client.ballots_iter("voting_id") {
+    ///         let (voter_id, ballot) = ballot?;
+    ///         println!("{}: {:?}", voter_id, ballot);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "experimental")]
+    pub fn ballots_iter<'a>(&'a self, voting_id: &'a str) -> BallotsIter<'a> {
+        self.ballots_iter_with_options(voting_id, RequestOptions::none())
+    }
+
+    /// Same as `Client::ballots_iter`, but applies the given `RequestOptions` to the requests.
+    #[cfg(feature = "experimental")]
+    pub fn ballots_iter_with_options<'a>(
+        &'a self,
+        voting_id: &'a str,
+        request_options: &'a RequestOptions,
+    ) -> BallotsIter<'a> {
+        BallotsIter {
+            client: self,
+            voting_id,
+            request_options,
+            next_page: Some(ListOptions::new()),
+            ballots: Vec::new().into_iter(),
+        }
+    }
+
+    /// Watches the results of a specific voting by polling them at the given interval.
+    ///
+    /// The returned iterator blocks until the results change. See
+    /// `ddclient_rs::Client::watch_results`.
+    pub fn watch_results<'a>(&'a self, voting_id: &'a str, interval: Duration) -> WatchResults<'a> {
+        WatchResults {
+            client: self,
+            voting_id,
+            interval,
+            delay: Duration::ZERO,
+            last: None,
+        }
+    }
+
+    /// Retrieves the results of a specific voting, without the duels information.
+    pub fn get_voting_results(&self, voting_id: &str) -> Result<VotingResults, ApiError> {
+        self.get_voting_results_with_options(voting_id, RequestOptions::none())
+    }
+
+    /// Same as `Client::get_voting_results`, but applies the given `RequestOptions` to the request.
+    pub fn get_voting_results_with_options(
+        &self,
+        voting_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<VotingResults, ApiError> {
+        let uri = Endpoint::voting(self.api_version, voting_id).segment("results");
+
+        let response =
+            self.request::<VotingResults>(Method::GET, uri.as_str(), None, request_options)?;
+
+        handle_api_response(response)
+    }
+
+    /// Retrieves the results of a specific voting, including the duels information.
+    ///
+    /// The `duels` field of the returned `VotingResults` is always `Some`, and contains an
+    /// empty list if the voting has no duels.
+    pub fn get_voting_results_duels(&self, voting_id: &str) -> Result<VotingResults, ApiError> {
+        self.get_voting_results_duels_with_options(voting_id, RequestOptions::none())
+    }
+
+    /// Same as `Client::get_voting_results_duels`, but applies the given `RequestOptions` to the request.
+    pub fn get_voting_results_duels_with_options(
+        &self,
+        voting_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<VotingResults, ApiError> {
+        let uri = Endpoint::voting(self.api_version, voting_id).segment("results/duels");
+
+        let response =
+            self.request::<VotingResults>(Method::GET, uri.as_str(), None, request_options)?;
+
+        let mut results: VotingResults = handle_api_response(response)?;
+        results.duels.get_or_insert_with(Vec::new);
+
+        Ok(results)
+    }
+
+    /// Retrieves the pairwise preferences of a specific voting.
+    ///
+    /// See `ddclient_rs::Client::get_pairwise_matrix`.
+    pub fn get_pairwise_matrix(&self, voting_id: &str) -> Result<PairwiseMatrix, ApiError> {
+        self.get_pairwise_matrix_with_options(voting_id, RequestOptions::none())
+    }
+
+    /// Same as `Client::get_pairwise_matrix`, but applies the given `RequestOptions` to the
+    /// request.
+    pub fn get_pairwise_matrix_with_options(
+        &self,
+        voting_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<PairwiseMatrix, ApiError> {
+        let results = self.get_voting_results_duels_with_options(voting_id, request_options)?;
+
+        Ok(PairwiseMatrix::from_results(&results))
+    }
+
+    /// Creates a human-readable summary of a voting in the given format.
+    ///
+    /// Fetches the voting and its results including the duels information one after another.
+    /// See `ddclient_rs::Client::summarize_voting`.
+    pub fn summarize_voting(
+        &self,
+        voting_id: &str,
+        format: ReportFormat,
+    ) -> Result<String, ApiError> {
+        self.summarize_voting_with_options(voting_id, format, RequestOptions::none())
+    }
+
+    /// Same as `Client::summarize_voting`, but applies the given `RequestOptions` to the requests.
+    pub fn summarize_voting_with_options(
+        &self,
+        voting_id: &str,
+        format: ReportFormat,
+        request_options: &RequestOptions,
+    ) -> Result<String, ApiError> {
+        let voting = self.get_voting_with_options(voting_id, request_options)?;
+        let results = self.get_voting_results_duels_with_options(voting_id, request_options)?;
+
+        Ok(VotingSummary::new(voting, results).render(format))
+    }
+}
+
+/// A builder for creating a voting with optional parameters, returned by
+/// `Client::create_voting_builder`.
+///
+/// See `ddclient_rs::CreateVotingBuilder`.
+pub struct CreateVotingBuilder<'a> {
+    client: &'a Client,
+    request: VotingRequest,
+    request_options: RequestOptions,
+}
+
+impl CreateVotingBuilder<'_> {
+    /// Sets the choices of the voting, replacing the choices added before.
+    pub fn choices(mut self, choices: Vec<String>) -> Self {
+        self.request.choices = choices;
+        self
+    }
+
+    /// Adds a choice after the choices added before.
+    pub fn choice(mut self, choice: String) -> Self {
+        self.request.choices.push(choice);
+        self
+    }
+
+    /// Sets the title of the voting.
+    pub fn title(mut self, title: String) -> Self {
+        self.request.title = Some(title);
+        self
+    }
+
+    /// Sets the description of the voting.
+    pub fn description(mut self, description: String) -> Self {
+        self.request.description = Some(description);
+        self
+    }
+
+    /// Sets a parameter of the new voting by its name in the API payload.
+    pub fn option(mut self, name: String, value: serde_json::Value) -> Self {
+        self.request.options.insert(name, value);
+        self
+    }
+
+    /// Sets the `RequestOptions` applied to the request.
+    pub fn request_options(mut self, request_options: RequestOptions) -> Self {
+        self.request_options = request_options;
+        self
+    }
+
+    /// Creates the voting.
+    pub fn send(self) -> Result<Voting, ApiError> {
+        self.client.post_voting(self.request, &self.request_options)
+    }
+}
+
+/// An endless iterator over the changing results of a voting, returned by
+/// `Client::watch_results`.
+pub struct WatchResults<'a> {
+    client: &'a Client,
+    voting_id: &'a str,
+    interval: Duration,
+    delay: Duration,
+    last: Option<VotingResults>,
+}
+
+impl Iterator for WatchResults<'_> {
+    type Item = Result<VotingResults, ApiError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            thread::sleep(self.delay);
+            self.delay = self.interval;
+
+            match self.client.get_voting_results(self.voting_id) {
+                Ok(results) if self.last.as_ref() == Some(&results) => {}
+                Ok(results) => {
+                    self.last = Some(results.clone());
+                    return Some(Ok(results));
+                }
+                Err(err) if err.is_rate_limited() => {
+                    self.delay = rate_limit_delay(&err).max(self.interval);
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// An iterator over all ballots of a voting, returned by `Client::ballots_iter`.
+#[cfg(feature = "experimental")]
+pub struct BallotsIter<'a> {
+    client: &'a Client,
+    voting_id: &'a str,
+    request_options: &'a RequestOptions,
+    next_page: Option<ListOptions>,
+    ballots: std::vec::IntoIter<VoterBallot>,
+}
+
+#[cfg(feature = "experimental")]
+impl Iterator for BallotsIter<'_> {
+    type Item = Result<(String, HashMap<String, Rank>), ApiError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(b) = self.ballots.next() {
+                return Some(Ok((b.voter_id, b.ballot)));
+            }
+
+            let options = self.next_page.take()?;
+            let page = match self.client.list_ballots_with_options(
+                self.voting_id,
+                &options,
+                self.request_options,
+            ) {
+                Ok(page) => page,
+                Err(err) => return Some(Err(err)),
+            };
+            self.next_page = page.next_cursor.map(|cursor| options.cursor(cursor));
+            self.ballots = page.ballots.into_iter();
+        }
+    }
+}
+
+/// A builder for creating an instance of the blocking `Client`.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::blocking::Client;
+/// use std::time::Duration;
+///
+/// let client = Client::builder("my-api-key".to_string())
+///     .timeout(Duration::from_secs(10))
+///     .try_build()
+///     .unwrap();
+/// ```
+pub struct ClientBuilder {
+    token: String,
+    api_url: Option<String>,
+    api_version: ApiVersion,
+    reqwest_client: Option<reqwest::blocking::Client>,
+    retry_policy: Option<RetryPolicy>,
+    idempotency_keys: bool,
+    validate_ballots: bool,
+    strict_responses: bool,
+    throttle_threshold: Option<u32>,
+    max_concurrent_requests: Option<usize>,
+    #[cfg(feature = "rate-limit")]
+    max_requests_per_second: Option<u32>,
+    timeout: Option<Duration>,
+    http: HttpConfig,
+    default_headers: HeaderMap,
+}
+
+/// Formats the configuration of the builder, with the API token redacted.
+impl fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientBuilder")
+            .field("token", &format_args!("{}", REDACTED))
+            .field("api_url", &self.api_url)
+            .field("api_version", &self.api_version)
+            .field("retry_policy", &self.retry_policy)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClientBuilder {
+    fn new(token: String) -> Self {
+        ClientBuilder {
+            token,
+            api_url: None,
+            api_version: ApiVersion::default(),
+            reqwest_client: None,
+            retry_policy: None,
+            idempotency_keys: false,
+            validate_ballots: false,
+            strict_responses: false,
+            throttle_threshold: None,
+            max_concurrent_requests: None,
+            #[cfg(feature = "rate-limit")]
+            max_requests_per_second: None,
+            timeout: None,
+            http: HttpConfig::default(),
+            default_headers: HeaderMap::new(),
+        }
+    }
+
+    /// Sets a custom API URL for the `Client`.
+    ///
+    /// If not set, a default URL is used.
+    pub fn api_url(mut self, api_url: String) -> Self {
+        self.api_url = Some(api_url);
+        self
+    }
+
+    /// See `ddclient_rs::ClientBuilder::api_version`.
+    pub fn api_version(mut self, version: ApiVersion) -> Self {
+        self.api_version = version;
+        self
+    }
+
+    /// Sets a custom blocking Reqwest client for the `Client`.
+    ///
+    /// If not set, a default blocking Reqwest client is used.
+    pub fn reqwest_client(mut self, client: reqwest::blocking::Client) -> Self {
+        self.reqwest_client = Some(client);
+        self
+    }
+
+    /// Sets the total timeout of each request, from sending the request until the response
+    /// body is received.
+    ///
+    /// If not set, the default timeout of the blocking Reqwest client applies, which is
+    /// 30 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for establishing a connection to the API.
+    ///
+    /// The connect timeout has no effect if a custom Reqwest client is set with
+    /// `reqwest_client`, configure it on that client instead.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Routes all requests through the proxy at the given URL.
+    ///
+    /// By default, the proxies configured with the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY`
+    /// environment variables are used. The proxy is applied to the internal Reqwest client
+    /// and has no effect if a custom Reqwest client is set with `reqwest_client`.
+    ///
+    /// An invalid proxy URL fails `try_build` with `BuildError::InvalidProxy`.
+    pub fn proxy(mut self, url: String) -> Self {
+        self.http.proxy = Some(url);
+        self
+    }
+
+    /// Sets the credentials sent to the proxy set with `proxy`, using basic authentication.
+    pub fn proxy_auth(mut self, username: String, password: String) -> Self {
+        self.http.proxy_auth = Some((username, password));
+        self
+    }
+
+    /// Disables the proxies configured with environment variables.
+    ///
+    /// A proxy set with `proxy` is still used.
+    pub fn no_proxy(mut self) -> Self {
+        self.http.no_proxy = true;
+        self
+    }
+
+    /// Sets the HTTP protocol versions used by the client.
+    ///
+    /// See `ddclient_rs::ClientBuilder::http_version`.
+    pub fn http_version(mut self, version: HttpVersion) -> Self {
+        self.http.http_version = version;
+        self
+    }
+
+    /// Enables or disables the adaptive flow control of HTTP/2 connections.
+    ///
+    /// See `ddclient_rs::ClientBuilder::http2_adaptive_window`.
+    pub fn http2_adaptive_window(mut self, enabled: bool) -> Self {
+        self.http.http2_adaptive_window = Some(enabled);
+        self
+    }
+
+    /// Adds a trusted root certificate, in addition to the system trust store.
+    ///
+    /// Use it when the API traffic is routed through an internal TLS-intercepting gateway.
+    /// The certificate is applied to the internal Reqwest client and has no effect if a
+    /// custom Reqwest client is set with `reqwest_client`.
+    ///
+    /// This method is available with the `native-tls` or `rustls` feature.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub fn add_root_certificate(mut self, certificate: reqwest::Certificate) -> Self {
+        self.http.root_certificates.push(certificate);
+        self
+    }
+
+    /// Sets the client certificate presented to the server, for mutual TLS.
+    ///
+    /// The identity is applied to the internal Reqwest client and has no effect if a custom
+    /// Reqwest client is set with `reqwest_client`.
+    ///
+    /// This method is available with the `native-tls` or `rustls` feature. The identity
+    /// must be created with a constructor supported by the enabled TLS backend.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub fn identity(mut self, identity: reqwest::Identity) -> Self {
+        self.http.identity = Some(identity);
+        self
+    }
+
+    /// Adds a header that is sent with every request.
+    ///
+    /// Default headers replace the headers of the same name set by the client, and are
+    /// replaced by the headers set with `RequestOptions::header`.
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.default_headers.append(name, value);
+        self
+    }
+
+    /// Enables automatic retries of requests that failed with a transient error.
+    ///
+    /// If not set, requests are not retried.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Enables automatic idempotency keys for mutating requests.
+    ///
+    /// When enabled, every `POST` and `DELETE` request gets a random `Idempotency-Key`
+    /// header that is reused by its retries, unless a key is set with
+    /// `RequestOptions::idempotency_key`.
+    pub fn idempotency_keys(mut self, enabled: bool) -> Self {
+        self.idempotency_keys = enabled;
+        self
+    }
+
+    /// Enables client-side validation of ballots before they are sent.
+    ///
+    /// When enabled, `Client::vote` rejects invalid ballots with `ApiError::Validation`
+    /// without sending a request, see `validate_ballot`.
+    pub fn validate_ballots(mut self, enabled: bool) -> Self {
+        self.validate_ballots = enabled;
+        self
+    }
+
+    /// See `ddclient_rs::ClientBuilder::strict_responses`.
+    pub fn strict_responses(mut self, enabled: bool) -> Self {
+        self.strict_responses = enabled;
+        self
+    }
+
+    /// Enables proactive throttling based on the rate limit information.
+    ///
+    /// When the number of remaining requests drops to `threshold` or below, requests
+    /// block until their slot is due. If not set, requests are never delayed.
+    pub fn throttle(mut self, threshold: u32) -> Self {
+        self.throttle_threshold = Some(threshold);
+        self
+    }
+
+    /// Limits the number of requests in flight at the same time. Requests over the limit
+    /// block until an earlier request receives its response.
+    ///
+    /// See `ddclient_rs::ClientBuilder::max_concurrent_requests`.
+    pub fn max_concurrent_requests(mut self, limit: usize) -> Self {
+        self.max_concurrent_requests = Some(limit);
+        self
+    }
+
+    /// Limits the rate of requests sent by the client, independently of the rate limit
+    /// reported by the API.
+    ///
+    /// See `ddclient_rs::ClientBuilder::max_requests_per_second`.
+    #[cfg(feature = "rate-limit")]
+    pub fn max_requests_per_second(mut self, requests: u32) -> Self {
+        self.max_requests_per_second = Some(requests);
+        self
+    }
+
+    /// Builds and returns a new blocking `Client` instance.
+    ///
+    /// Returns a `Result` which is `Ok` containing the `Client` if the configuration is valid,
+    /// or an `Err` with a `BuildError` if the API URL or token is invalid, or if the internal
+    /// Reqwest client cannot be created.
+    pub fn try_build(self) -> Result<Client, BuildError> {
+        let api_url = validate_api_url(self.api_url)?;
+        let authorization = validate_token(self.token)?;
+
+        let client = match self.reqwest_client {
+            Some(client) => client,
+            None => self.http.build_blocking()?,
+        };
+
+        Ok(Client {
+            authorization,
+            client,
+            api_url,
+            api_version: self.api_version,
+            rate: Arc::new(Mutex::new(None)),
+            endpoint_rates: EndpointRates::default(),
+            last_request_id: Mutex::new(None),
+            last_error_context: LastErrorContext::default(),
+            retry_policy: self.retry_policy,
+            idempotency_keys: self.idempotency_keys,
+            validate_ballots: self.validate_ballots,
+            strict_responses: self.strict_responses,
+            throttle: self.throttle_threshold.map(Throttle::new),
+            limiter: self.max_concurrent_requests.map(BlockingLimiter::new),
+            #[cfg(feature = "rate-limit")]
+            rate_limiter: self.max_requests_per_second.map(RateLimiter::new),
+            timeout: self.timeout,
+            default_headers: self.default_headers,
+        })
+    }
+}
+
+/// Runs a request for every item on up to `BulkOptions::concurrency` threads, and returns
+/// the items with the results of their requests in the order of the items.
+fn run_bulk<T, R, F>(items: Vec<T>, options: &BulkOptions, f: F) -> Vec<(T, Result<R, ApiError>)>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> Result<R, ApiError> + Sync,
+{
+    let pause = RateLimitPause::default();
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(items.len()));
+
+    thread::scope(|scope| {
+        for _ in 0..options.get_concurrency().min(items.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                let mut retries = 0;
+                let result = loop {
+                    thread::sleep(pause.remaining());
+                    match f(item) {
+                        Err(err) if should_retry(options, retries, &err) => {
+                            pause.pause(&err);
+                            retries += 1;
+                        }
+                        result => break result,
+                    }
+                };
+                results.lock().unwrap().push((index, result));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    items
+        .into_iter()
+        .zip(results)
+        .map(|(item, (_, result))| (item, result))
+        .collect()
+}
+
+fn handle_api_response<T: serde::de::DeserializeOwned + serde::Serialize>(
+    mut response: Response,
+) -> Result<T, ApiError> {
+    match response.status() {
+        StatusCode::OK if response.extensions().get::<StrictResponses>().is_some() => {
+            let body = response.bytes().map_err(ClientError::HttpRequestError)?;
+            Ok(strict::from_slice(&body)?)
+        }
+        StatusCode::OK => response
+            .json()
+            .map_err(|err| ApiError::Client(ClientError::HttpRequestError(err))),
+        status => {
+            let headers = response.headers().clone();
+            let extensions = std::mem::take(response.extensions_mut());
+            Err(api_error(
+                status,
+                &headers,
+                &extensions,
+                response.text().unwrap_or_default(),
+            ))
+        }
+    }
+}
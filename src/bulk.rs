@@ -0,0 +1,233 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::ApiError;
+use futures_util::{stream, Future, StreamExt};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const DEFAULT_CONCURRENCY: usize = 8;
+const DEFAULT_RATE_LIMIT_RETRIES: u32 = 3;
+const DEFAULT_RATE_LIMIT_DELAY: Duration = Duration::from_secs(1);
+
+/// Options of the bulk operations of the client, such as `Client::vote_many`.
+///
+/// Bulk operations send up to `concurrency` requests at the same time. When a request is
+/// rate limited, all requests of the operation pause until the rate limit allows them
+/// again, and the rate limited request is retried up to `rate_limit_retries` times.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::BulkOptions;
+///
+/// let options = BulkOptions::new().concurrency(4).rate_limit_retries(5);
+/// assert_eq!(options.get_concurrency(), 4);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkOptions {
+    concurrency: usize,
+    rate_limit_retries: u32,
+}
+
+impl BulkOptions {
+    /// Creates new `BulkOptions` with the default concurrency of 8 requests and 3 retries
+    /// of rate limited requests.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of requests sent at the same time. A concurrency of `0`
+    /// is treated as `1`.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets how many times a rate limited request is retried before its error is returned.
+    pub fn rate_limit_retries(mut self, retries: u32) -> Self {
+        self.rate_limit_retries = retries;
+        self
+    }
+
+    /// Returns the maximum number of requests sent at the same time.
+    pub fn get_concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Returns how many times a rate limited request is retried.
+    pub fn get_rate_limit_retries(&self) -> u32 {
+        self.rate_limit_retries
+    }
+}
+
+impl Default for BulkOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_CONCURRENCY,
+            rate_limit_retries: DEFAULT_RATE_LIMIT_RETRIES,
+        }
+    }
+}
+
+/// The outcome of deleting many votings with `Client::delete_votings`.
+#[derive(Debug, Default)]
+pub struct DeleteVotingsReport {
+    /// The IDs of the deleted votings.
+    pub deleted: Vec<String>,
+    /// The IDs of the votings which were not found, and were skipped.
+    pub not_found: Vec<String>,
+    /// The IDs of the votings which could not be deleted, with their errors.
+    pub failed: Vec<(String, ApiError)>,
+}
+
+impl DeleteVotingsReport {
+    /// Reports whether no deletion failed. Votings which were not found do not count as
+    /// failures.
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    pub(crate) fn from_results(results: Vec<(String, Result<(), ApiError>)>) -> Self {
+        let mut report = Self::default();
+        for (id, result) in results {
+            match result {
+                Ok(()) => report.deleted.push(id),
+                Err(ApiError::NotFound) => report.not_found.push(id),
+                Err(err) => report.failed.push((id, err)),
+            }
+        }
+        report
+    }
+}
+
+/// Pauses the requests of a bulk operation after one of them was rate limited.
+#[derive(Debug, Default)]
+pub(crate) struct RateLimitPause {
+    resume_at: Mutex<Option<Instant>>,
+}
+
+impl RateLimitPause {
+    /// Returns how long the next request should wait.
+    pub(crate) fn remaining(&self) -> Duration {
+        self.resume_at
+            .lock()
+            .unwrap()
+            .map_or(Duration::ZERO, |resume_at| {
+                resume_at.saturating_duration_since(Instant::now())
+            })
+    }
+
+    /// Pauses the requests until the rate limit of the error allows them again.
+    pub(crate) fn pause(&self, err: &ApiError) {
+        let resume_at = Instant::now() + rate_limit_delay(err);
+        let mut current = self.resume_at.lock().unwrap();
+        if current.is_none_or(|current| current < resume_at) {
+            *current = Some(resume_at);
+        }
+    }
+}
+
+/// Returns how long to wait after a rate limited request, from the `Retry-After` header, or
+/// the retry time of the rate limit.
+pub(crate) fn rate_limit_delay(err: &ApiError) -> Duration {
+    if let Some(retry_after) = err.retry_after() {
+        return retry_after;
+    }
+    match err {
+        ApiError::RateLimited {
+            rate: Some(rate), ..
+        } => rate.retry_in().max(DEFAULT_RATE_LIMIT_DELAY),
+        _ => DEFAULT_RATE_LIMIT_DELAY,
+    }
+}
+
+/// Reports whether a failed request of a bulk operation should be retried after a pause.
+pub(crate) fn should_retry(options: &BulkOptions, retries: u32, err: &ApiError) -> bool {
+    err.is_rate_limited() && retries < options.rate_limit_retries
+}
+
+/// Runs a request for every item with the concurrency of the options, and returns the
+/// items with the results of their requests in the order of the items.
+pub(crate) async fn run<T, R, F, Fut>(
+    items: impl IntoIterator<Item = T>,
+    options: &BulkOptions,
+    f: F,
+) -> Vec<(T, Result<R, ApiError>)>
+where
+    T: Clone,
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = Result<R, ApiError>>,
+{
+    let pause = RateLimitPause::default();
+    stream::iter(items)
+        .map(|item| {
+            let (pause, f) = (&pause, &f);
+            async move {
+                let mut retries = 0;
+                loop {
+                    let delay = pause.remaining();
+                    if !delay.is_zero() {
+                        tokio::time::sleep(delay).await;
+                    }
+                    match f(item.clone()).await {
+                        Err(err) if should_retry(options, retries, &err) => {
+                            pause.pause(&err);
+                            retries += 1;
+                        }
+                        result => return (item, result),
+                    }
+                }
+            }
+        })
+        .buffered(options.concurrency)
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate_limited(retry_after: Option<Duration>) -> ApiError {
+        ApiError::RateLimited {
+            rate: None,
+            retry_after,
+        }
+    }
+
+    #[test]
+    fn bulk_options_test() {
+        let options = BulkOptions::new();
+        assert_eq!(options.get_concurrency(), 8);
+        assert_eq!(options.get_rate_limit_retries(), 3);
+        assert_eq!(BulkOptions::new().concurrency(0).get_concurrency(), 1);
+
+        let err = rate_limited(None);
+        let options = options.rate_limit_retries(1);
+        assert!(should_retry(&options, 0, &err));
+        assert!(!should_retry(&options, 1, &err));
+        assert!(!should_retry(&options, 0, &ApiError::NotFound));
+    }
+
+    #[test]
+    fn rate_limit_pause_test() {
+        let pause = RateLimitPause::default();
+        assert_eq!(pause.remaining(), Duration::ZERO);
+
+        pause.pause(&rate_limited(Some(Duration::from_secs(30))));
+        let remaining = pause.remaining();
+        assert!(remaining > Duration::from_secs(29) && remaining <= Duration::from_secs(30));
+
+        // a shorter pause does not shorten the current one
+        pause.pause(&rate_limited(Some(Duration::from_secs(1))));
+        assert!(pause.remaining() > Duration::from_secs(29));
+
+        assert_eq!(
+            rate_limit_delay(&rate_limited(None)),
+            Duration::from_secs(1)
+        );
+    }
+}
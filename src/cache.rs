@@ -0,0 +1,329 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::{ApiError, ClientError, Rank, Voting, VotingResults};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Identifies a cached response.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum CacheKey {
+    Voting(String),
+    Ballot(String, String),
+    Results(String),
+}
+
+impl CacheKey {
+    fn voting_id(&self) -> &str {
+        match self {
+            CacheKey::Voting(voting_id)
+            | CacheKey::Ballot(voting_id, _)
+            | CacheKey::Results(voting_id) => voting_id,
+        }
+    }
+}
+
+/// A cached response.
+#[derive(Debug, Clone)]
+pub(crate) enum CacheValue {
+    Voting(Voting),
+    Ballot(HashMap<String, Rank>),
+    Results(VotingResults),
+}
+
+#[derive(Debug)]
+struct Entry {
+    value: CacheValue,
+    inserted: Instant,
+}
+
+/// The number of responses kept for offline reads when the cache is not enabled.
+pub(crate) const DEFAULT_OFFLINE_CAPACITY: usize = 1024;
+
+/// Caches the responses of the read requests of a client in memory.
+///
+/// Entries expire `ttl` after they are inserted. When the cache is full, expired entries
+/// are removed first, and then the oldest entry. If `keep_stale` is set, expired entries
+/// are kept until they are evicted by newer ones, to be served by offline reads.
+#[derive(Debug)]
+pub(crate) struct Cache {
+    ttl: Duration,
+    capacity: usize,
+    keep_stale: bool,
+    entries: Mutex<HashMap<CacheKey, Entry>>,
+}
+
+impl Cache {
+    /// Creates a cache holding up to `capacity` entries for `ttl` each.
+    pub(crate) fn new(ttl: Duration, capacity: usize, keep_stale: bool) -> Self {
+        Self {
+            ttl,
+            capacity,
+            keep_stale,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached value of the key, if it is cached and not expired.
+    pub(crate) fn get(&self, key: &CacheKey) -> Option<CacheValue> {
+        self.get_at(key, Instant::now())
+    }
+
+    /// Returns the cached value of the key, even if it is expired, with its age.
+    pub(crate) fn get_stale(&self, key: &CacheKey) -> Option<(CacheValue, Duration)> {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(key)?;
+        Some((entry.value.clone(), entry.inserted.elapsed()))
+    }
+
+    /// Caches the value of the key.
+    pub(crate) fn insert(&self, key: CacheKey, value: CacheValue) {
+        self.insert_at(key, value, Instant::now())
+    }
+
+    /// Removes the cached values of the voting, its ballots and results.
+    pub(crate) fn invalidate_voting(&self, voting_id: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|key, _| key.voting_id() != voting_id);
+    }
+
+    /// Removes all cached values.
+    pub(crate) fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    fn get_at(&self, key: &CacheKey, now: Instant) -> Option<CacheValue> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some(entry) if now.duration_since(entry.inserted) < self.ttl => {
+                Some(entry.value.clone())
+            }
+            Some(_) => {
+                if !self.keep_stale {
+                    entries.remove(key);
+                }
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert_at(&self, key: CacheKey, value: CacheValue, now: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if !entries.contains_key(&key) && entries.len() >= self.capacity {
+            if !self.keep_stale {
+                entries.retain(|_, entry| now.duration_since(entry.inserted) < self.ttl);
+            }
+            if entries.len() >= self.capacity {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.inserted)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                value,
+                inserted: now,
+            },
+        );
+    }
+}
+
+/// A response of a read method with offline fallback, such as
+/// `Client::get_voting_or_stale`, either received from the API, or served from the cache
+/// because the API was unreachable.
+#[derive(Debug)]
+pub enum MaybeStale<T> {
+    /// The value was received from the API, or from the cache before it expired.
+    Fresh(T),
+    /// The API was unreachable, and the value is the last response cached by the client.
+    Stale {
+        /// The last cached value.
+        value: T,
+        /// The time elapsed since the value was received from the API.
+        age: Duration,
+        /// The error of the failed request.
+        error: ApiError,
+    },
+}
+
+impl<T> MaybeStale<T> {
+    /// Reports whether the value was served from the cache because the API was
+    /// unreachable.
+    pub fn is_stale(&self) -> bool {
+        matches!(self, MaybeStale::Stale { .. })
+    }
+
+    /// Returns the value, fresh or stale.
+    pub fn value(&self) -> &T {
+        match self {
+            MaybeStale::Fresh(value) | MaybeStale::Stale { value, .. } => value,
+        }
+    }
+
+    /// Returns the value, fresh or stale, discarding whether it is stale.
+    pub fn into_value(self) -> T {
+        match self {
+            MaybeStale::Fresh(value) | MaybeStale::Stale { value, .. } => value,
+        }
+    }
+
+    /// Returns the age of a stale value, or `None` if the value is fresh.
+    pub fn age(&self) -> Option<Duration> {
+        match self {
+            MaybeStale::Fresh(_) => None,
+            MaybeStale::Stale { age, .. } => Some(*age),
+        }
+    }
+}
+
+/// Reports whether the request failed because the API could not be reached, so a stale
+/// response may be served instead.
+pub(crate) fn is_unreachable(err: &ApiError) -> bool {
+    match err {
+        ApiError::Client(ClientError::HttpRequestError(err)) => {
+            err.is_connect() || err.is_timeout()
+        }
+        ApiError::Client(ClientError::BadGateway | ClientError::ServiceUnavailable) => true,
+        ApiError::Client(ClientError::Transport(_)) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ballot(choice: &str) -> CacheValue {
+        CacheValue::Ballot(HashMap::from([(choice.to_string(), Rank::new(1).unwrap())]))
+    }
+
+    fn cached_ballot(cache: &Cache, voter_id: &str, now: Instant) -> Option<String> {
+        match cache.get_at(&CacheKey::Ballot("v".into(), voter_id.into()), now)? {
+            CacheValue::Ballot(ballot) => ballot.into_keys().next(),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn ttl_test() {
+        let cache = Cache::new(Duration::from_secs(10), 10, false);
+        let now = Instant::now();
+
+        cache.insert_at(
+            CacheKey::Ballot("v".into(), "a".into()),
+            ballot("Kant"),
+            now,
+        );
+
+        assert_eq!(cached_ballot(&cache, "a", now).as_deref(), Some("Kant"));
+        assert_eq!(
+            cached_ballot(&cache, "a", now + Duration::from_secs(9)).as_deref(),
+            Some("Kant")
+        );
+        assert_eq!(
+            cached_ballot(&cache, "a", now + Duration::from_secs(10)),
+            None
+        );
+        assert!(cache.entries.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn capacity_test() {
+        let cache = Cache::new(Duration::from_secs(10), 2, false);
+        let now = Instant::now();
+
+        cache.insert_at(
+            CacheKey::Ballot("v".into(), "a".into()),
+            ballot("Kant"),
+            now,
+        );
+        cache.insert_at(
+            CacheKey::Ballot("v".into(), "b".into()),
+            ballot("Hume"),
+            now + Duration::from_secs(1),
+        );
+        cache.insert_at(
+            CacheKey::Ballot("v".into(), "c".into()),
+            ballot("Locke"),
+            now + Duration::from_secs(2),
+        );
+
+        // the oldest entry is evicted
+        let later = now + Duration::from_secs(3);
+        assert_eq!(cached_ballot(&cache, "a", later), None);
+        assert_eq!(cached_ballot(&cache, "b", later).as_deref(), Some("Hume"));
+        assert_eq!(cached_ballot(&cache, "c", later).as_deref(), Some("Locke"));
+
+        let cache = Cache::new(Duration::from_secs(10), 0, false);
+        cache.insert_at(
+            CacheKey::Ballot("v".into(), "a".into()),
+            ballot("Kant"),
+            now,
+        );
+        assert_eq!(cached_ballot(&cache, "a", now), None);
+    }
+
+    #[test]
+    fn keep_stale_test() {
+        let cache = Cache::new(Duration::from_secs(10), 2, true);
+        let now = Instant::now();
+        let key = CacheKey::Ballot("v".into(), "a".into());
+
+        cache.insert_at(key.clone(), ballot("Kant"), now);
+
+        let later = now + Duration::from_secs(20);
+        assert_eq!(cached_ballot(&cache, "a", later), None);
+        assert!(cache.get_stale(&key).is_some());
+
+        // expired entries are evicted when the cache is full, oldest first
+        cache.insert_at(
+            CacheKey::Ballot("v".into(), "b".into()),
+            ballot("Hume"),
+            later,
+        );
+        assert!(cache.get_stale(&key).is_some());
+        cache.insert_at(
+            CacheKey::Ballot("v".into(), "c".into()),
+            ballot("Locke"),
+            later,
+        );
+        assert!(cache.get_stale(&key).is_none());
+    }
+
+    #[test]
+    fn invalidate_voting_test() {
+        let cache = Cache::new(Duration::from_secs(10), 10, false);
+
+        cache.insert(CacheKey::Ballot("v".into(), "a".into()), ballot("Kant"));
+        cache.insert(CacheKey::Ballot("w".into(), "a".into()), ballot("Hume"));
+
+        cache.invalidate_voting("v");
+
+        assert!(cache
+            .get(&CacheKey::Ballot("v".into(), "a".into()))
+            .is_none());
+        assert!(cache
+            .get(&CacheKey::Ballot("w".into(), "a".into()))
+            .is_some());
+
+        cache.clear();
+        assert!(cache
+            .get(&CacheKey::Ballot("w".into(), "a".into()))
+            .is_none());
+    }
+}
@@ -0,0 +1,417 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::validation::validate_choices;
+use crate::{validate_ballot, BadRequestError, Rank, ValidationError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+
+/// Represents the position of a choice in the list of voting choices.
+///
+/// The same index type is used by `Voting`, `VotingResult` and `ChoiceStrength`, so an index
+/// taken from the results can be used directly to look up the choice in a voting.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::ChoiceIndex;
+///
+/// let choices = vec!["Einstein".to_string(), "Newton".to_string()];
+///
+/// let index = ChoiceIndex::checked(1, &choices).unwrap();
+/// assert_eq!(index.choice(&choices), Some("Newton"));
+/// assert!(ChoiceIndex::checked(2, &choices).is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ChoiceIndex(usize);
+
+impl ChoiceIndex {
+    /// Creates a new `ChoiceIndex` without checking it against a choice list.
+    pub const fn new(index: usize) -> Self {
+        Self(index)
+    }
+
+    /// Creates a new `ChoiceIndex`, returning `None` if the index is out of bounds
+    /// for the given choices.
+    pub fn checked(index: usize, choices: &[String]) -> Option<Self> {
+        (index < choices.len()).then_some(Self(index))
+    }
+
+    /// Returns the index as a `usize`.
+    pub const fn get(self) -> usize {
+        self.0
+    }
+
+    /// Returns `true` if the index points to an element of the given choices.
+    pub fn is_valid_for(self, choices: &[String]) -> bool {
+        self.0 < choices.len()
+    }
+
+    /// Returns the choice at this index, or `None` if the index is out of bounds.
+    pub fn choice(self, choices: &[String]) -> Option<&str> {
+        choices.get(self.0).map(String::as_str)
+    }
+}
+
+impl From<usize> for ChoiceIndex {
+    fn from(index: usize) -> Self {
+        Self(index)
+    }
+}
+
+impl From<ChoiceIndex> for usize {
+    fn from(index: ChoiceIndex) -> Self {
+        index.0
+    }
+}
+
+impl TryFrom<i32> for ChoiceIndex {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(index: i32) -> Result<Self, Self::Error> {
+        usize::try_from(index).map(Self)
+    }
+}
+
+impl TryFrom<isize> for ChoiceIndex {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(index: isize) -> Result<Self, Self::Error> {
+        usize::try_from(index).map(Self)
+    }
+}
+
+impl fmt::Display for ChoiceIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Represents the list of choices of a voting.
+///
+/// `Choices` is returned by `Client::create_voting`, `Client::get_voting` and
+/// `Client::set_choice`. It dereferences to a slice of choices, and adds lookups by
+/// `ChoiceIndex` and validation of choices and ballots.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{ChoiceIndex, Choices};
+///
+/// let choices = Choices::new(vec!["Einstein".to_string(), "Newton".to_string()]);
+///
+/// assert_eq!(choices.index_of("Newton"), Some(ChoiceIndex::new(1)));
+/// assert_eq!(choices.get(ChoiceIndex::new(0)), Some("Einstein"));
+/// assert!(!choices.contains("Bohr"));
+/// assert!(choices.validate().is_ok());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Choices(Vec<String>);
+
+impl Choices {
+    /// Creates new `Choices` from a list of choices.
+    pub fn new(choices: Vec<String>) -> Self {
+        Self(choices)
+    }
+
+    /// Returns the choice at the given index, or `None` if the index is out of bounds.
+    pub fn get(&self, index: ChoiceIndex) -> Option<&str> {
+        index.choice(&self.0)
+    }
+
+    /// Returns the index of the choice, or `None` if it is not in the list.
+    pub fn index_of(&self, choice: &str) -> Option<ChoiceIndex> {
+        self.0
+            .iter()
+            .position(|c| c == choice)
+            .map(ChoiceIndex::new)
+    }
+
+    /// Returns `true` if the choice is in the list.
+    pub fn contains(&self, choice: &str) -> bool {
+        self.0.iter().any(|c| c == choice)
+    }
+
+    /// Returns an iterator over the choices.
+    pub fn iter(&self) -> std::slice::Iter<'_, String> {
+        self.0.iter()
+    }
+
+    /// Returns the choices as a slice.
+    pub fn as_slice(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Returns the choices as a `Vec`.
+    pub fn into_vec(self) -> Vec<String> {
+        self.0
+    }
+
+    /// Validates that the choices of a voting are not missing and none of them is empty,
+    /// returning the first error the API would respond with.
+    ///
+    /// The maximum number and length of the choices are not checked, as the API does not
+    /// document them. It reports them with `BadRequestError::TooManyChoices` and
+    /// `BadRequestError::ChoiceTooLong`.
+    pub fn validate(&self) -> Result<(), BadRequestError> {
+        validate_choices(&self.0)
+    }
+
+    /// Validates a ballot for a voting with these choices, see `validate_ballot`.
+    pub fn validate_ballot(&self, ballot: &HashMap<String, Rank>) -> Result<(), ValidationError> {
+        validate_ballot(ballot, Some(&self.0))
+    }
+}
+
+impl Deref for Choices {
+    type Target = [String];
+
+    fn deref(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl From<Vec<String>> for Choices {
+    fn from(choices: Vec<String>) -> Self {
+        Self(choices)
+    }
+}
+
+impl From<Choices> for Vec<String> {
+    fn from(choices: Choices) -> Self {
+        choices.0
+    }
+}
+
+impl FromIterator<String> for Choices {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Extend<String> for Choices {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+impl IntoIterator for Choices {
+    type Item = String;
+    type IntoIter = std::vec::IntoIter<String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Choices {
+    type Item = &'a String;
+    type IntoIter = std::slice::Iter<'a, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<T: AsRef<str>> PartialEq<Vec<T>> for Choices {
+    fn eq(&self, other: &Vec<T>) -> bool {
+        self.0.len() == other.len() && self.0.iter().zip(other).all(|(a, b)| a == b.as_ref())
+    }
+}
+
+/// Returns the `set_choice` calls, as pairs of a choice and its index, which change the
+/// `current` choices to the `desired` ones.
+///
+/// The choices of the longest subsequence of `current` which is already ordered like in
+/// `desired` stay in place, and every other desired choice is placed after its predecessor
+/// in `desired`. The choices which are not desired are removed last, so the voting never
+/// runs out of choices in between.
+pub(crate) fn set_choices_plan(current: &[String], desired: &[String]) -> Vec<(String, i32)> {
+    let mut list = current.to_vec();
+    let mut plan = Vec::new();
+
+    let kept = ordered_subsequence(current, desired);
+    for (position, choice) in desired.iter().enumerate() {
+        if kept.contains(&choice.as_str()) {
+            continue;
+        }
+        list.retain(|c| c != choice);
+        let position = match position.checked_sub(1) {
+            Some(previous) => list
+                .iter()
+                .position(|c| *c == desired[previous])
+                .map_or(0, |p| p + 1),
+            None => 0,
+        };
+        plan.push((choice.clone(), (list.len() - position) as i32));
+        list.insert(position, choice.clone());
+    }
+
+    for choice in current {
+        if !desired.contains(choice) {
+            plan.push((choice.clone(), -1));
+        }
+    }
+    plan
+}
+
+/// Returns the choices of the longest subsequence of `current` whose choices are ordered
+/// like in `desired`.
+fn ordered_subsequence<'a>(current: &'a [String], desired: &[String]) -> Vec<&'a str> {
+    let positions: Vec<(usize, &str)> = current
+        .iter()
+        .filter_map(|choice| Some((desired.iter().position(|d| d == choice)?, choice.as_str())))
+        .collect();
+
+    // lengths[i] is the length of the longest ordered subsequence ending with positions[i],
+    // and previous[i] the index of the preceding element in that subsequence
+    let mut lengths = vec![1; positions.len()];
+    let mut previous: Vec<Option<usize>> = vec![None; positions.len()];
+    for i in 0..positions.len() {
+        for j in 0..i {
+            if positions[j].0 < positions[i].0 && lengths[j] + 1 > lengths[i] {
+                lengths[i] = lengths[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
+    }
+
+    let mut subsequence = Vec::new();
+    let mut next = (0..positions.len()).max_by_key(|&i| (lengths[i], std::cmp::Reverse(i)));
+    while let Some(i) = next {
+        subsequence.push(positions[i].1);
+        next = previous[i];
+    }
+    subsequence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choice_index_test() {
+        let choices = vec![
+            "Spinoza".to_string(),
+            "Kant".to_string(),
+            "Nietzsche".to_string(),
+        ];
+
+        assert_eq!(ChoiceIndex::checked(0, &choices), Some(ChoiceIndex::new(0)));
+        assert_eq!(ChoiceIndex::checked(2, &choices), Some(ChoiceIndex::new(2)));
+        assert_eq!(ChoiceIndex::checked(3, &choices), None);
+
+        assert_eq!(ChoiceIndex::new(1).choice(&choices), Some("Kant"));
+        assert_eq!(ChoiceIndex::new(3).choice(&choices), None);
+        assert!(!ChoiceIndex::new(3).is_valid_for(&choices));
+
+        assert_eq!(ChoiceIndex::try_from(2i32).unwrap().get(), 2);
+        assert!(ChoiceIndex::try_from(-1i32).is_err());
+        assert!(ChoiceIndex::try_from(-1isize).is_err());
+        assert_eq!(usize::from(ChoiceIndex::from(5)), 5);
+    }
+
+    #[test]
+    fn choice_index_serde_test() {
+        let index: ChoiceIndex = serde_json::from_str("4").unwrap();
+        assert_eq!(index, ChoiceIndex::new(4));
+        assert_eq!(serde_json::to_string(&index).unwrap(), "4");
+        assert!(serde_json::from_str::<ChoiceIndex>("-1").is_err());
+    }
+
+    #[test]
+    fn choices_test() {
+        let choices: Choices = ["Spinoza", "Kant"].iter().map(|c| c.to_string()).collect();
+
+        assert_eq!(choices.len(), 2);
+        assert_eq!(choices.index_of("Kant"), Some(ChoiceIndex::new(1)));
+        assert_eq!(choices.index_of("Hume"), None);
+        assert_eq!(choices.get(ChoiceIndex::new(0)), Some("Spinoza"));
+        assert!(choices.contains("Spinoza"));
+        assert_eq!(choices, vec!["Spinoza", "Kant"]);
+        assert_eq!(choices.iter().count(), 2);
+
+        assert_eq!(
+            Choices::default().validate(),
+            Err(BadRequestError::MissingChoices)
+        );
+        let ballot = HashMap::from([("Hume".to_string(), Rank::HIGHEST)]);
+        assert!(choices.validate_ballot(&ballot).is_err());
+
+        assert_eq!(
+            serde_json::to_string(&choices).unwrap(),
+            r#"["Spinoza","Kant"]"#
+        );
+        let parsed: Choices = serde_json::from_str(r#"["Spinoza","Kant"]"#).unwrap();
+        assert_eq!(parsed, choices);
+    }
+
+    /// Applies `set_choice` calls to the choices like the API does.
+    fn apply(choices: &mut Vec<String>, plan: &[(String, i32)]) {
+        for (choice, index) in plan {
+            choices.retain(|c| c != choice);
+            if *index >= 0 {
+                let position = choices.len() - *index as usize;
+                choices.insert(position, choice.clone());
+            }
+        }
+    }
+
+    fn strings(choices: &[&str]) -> Vec<String> {
+        choices.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn set_choices_plan_test() {
+        let cases = [
+            (vec!["A", "B", "C"], vec!["A", "B", "C"], 0),
+            (vec!["A", "B", "C"], vec!["B", "C", "A"], 1),
+            (vec!["A", "B", "C"], vec!["C", "B", "A"], 2),
+            (vec!["A", "B", "C"], vec!["A", "D", "C"], 2),
+            (vec!["A", "B", "C"], vec!["D", "E"], 5),
+            (vec!["C", "A", "B"], vec!["A", "B", "C", "D"], 2),
+            (vec![], vec!["A", "B"], 2),
+        ];
+
+        for (current, desired, calls) in cases {
+            let (current, desired) = (strings(&current), strings(&desired));
+            let plan = set_choices_plan(&current, &desired);
+            assert_eq!(plan.len(), calls, "{:?} -> {:?}", current, desired);
+
+            let mut choices = current.clone();
+            apply(&mut choices, &plan);
+            assert_eq!(choices, desired, "{:?} -> {:?}", current, desired);
+        }
+    }
+
+    #[test]
+    fn set_choices_plan_permutations_test() {
+        let choices = ["A", "B", "C", "D", "E"];
+        let mut seed = 42u64;
+        for _ in 0..200 {
+            let mut shuffled = strings(&choices);
+            for i in (1..shuffled.len()).rev() {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                shuffled.swap(i, (seed >> 33) as usize % (i + 1));
+            }
+            let desired: Vec<String> = shuffled
+                .iter()
+                .take(1 + seed as usize % 5)
+                .cloned()
+                .collect();
+
+            let current = strings(&choices);
+            let mut applied = current.clone();
+            apply(&mut applied, &set_choices_plan(&current, &desired));
+            assert_eq!(applied, desired);
+        }
+    }
+}
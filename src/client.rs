@@ -3,46 +3,204 @@
 // Use of this source code is governed by a BSD-style
 // license that can be found in the LICENSE file.
 
+use crate::bulk::{self, rate_limit_delay};
+use crate::cache::{is_unreachable, Cache, CacheKey, CacheValue, DEFAULT_OFFLINE_CAPACITY};
+use crate::choices::set_choices_plan;
+use crate::endpoint::Endpoint;
+use crate::hedge;
+use crate::http_config::HttpConfig;
+use crate::limiter::Limiter;
+use crate::middleware::{OnRequest, OnResponse};
+use crate::options::is_mutating;
+use crate::rate::{EndpointFamily, EndpointRates};
+#[cfg(feature = "rate-limit")]
+use crate::rate_limiter::RateLimiter;
+use crate::retry::{is_replayable, is_transient};
+use crate::strict::StrictResponses;
+use crate::throttle::Throttle;
+use crate::validation::{rejected, validate_choice, validate_choices};
 use crate::{
-    handle_api_response, ApiError, ClientError, Rate, Voting, VotingResults, CONTENT_TYPE,
-    DEFAULT_BASE_URL, USER_AGENT,
+    handle_api_response, request_id, ApiError, ApiResponse, ApiVersion, BadRequestError,
+    BuildError, BulkOptions, ChoiceIndex, Choices, ClientError, DeleteVotingsReport, ErrorContext,
+    HttpVersion, LastErrorContext, MaybeStale, MetricsObserver, Middleware, PairwiseMatrix, Rank,
+    Rate, RatePacer, ReportFormat, RequestOptions, RetryPolicy, TokenProvider, Transport, Voting,
+    VotingResults, VotingSummary, CONTENT_TYPE, DEFAULT_BASE_URL, USER_AGENT,
 };
 
-use reqwest::{Method, Response};
+#[cfg(feature = "sse")]
+use crate::events::{SseParser, EVENT_STREAM};
+#[cfg(feature = "tower")]
+use crate::TransportService;
+#[cfg(feature = "sse")]
+use crate::{response_error, VotingEvent};
+#[cfg(feature = "experimental")]
+use crate::{BallotsPage, ListOptions, VotingMetadata, VotingsPage};
+#[cfg(feature = "experimental")]
+use futures_util::TryStreamExt;
+use futures_util::{stream, Stream};
+use reqwest::header::{self, HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, Request, Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+#[cfg(feature = "sse")]
+use std::collections::VecDeque;
+use std::fmt;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
 
-#[derive(Debug, Serialize, Deserialize)]
-struct VotingRequest {
-    choices: Vec<String>,
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct VotingRequest {
+    pub(crate) choices: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) description: Option<String>,
+    #[serde(flatten)]
+    pub(crate) options: serde_json::Map<String, serde_json::Value>,
+}
+
+impl VotingRequest {
+    pub(crate) fn new(choices: Vec<String>) -> Self {
+        Self {
+            choices,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), ApiError> {
+        validate_choices(&self.choices).map_err(rejected)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct SetChoiceRequest {
-    choice: String,
-    index: i32,
+pub(crate) struct SetChoiceRequest {
+    pub(crate) choice: String,
+    pub(crate) index: i32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct SetChoiceResponse {
-    choices: Vec<String>,
+pub(crate) struct SetChoiceResponse {
+    pub(crate) choices: Choices,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct VoteResponse {
-    revoted: bool,
+pub(crate) struct VoteResponse {
+    pub(crate) revoted: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct Ballot {
-    ballot: HashMap<String, i32>,
+pub(crate) struct Ballot {
+    pub(crate) ballot: HashMap<String, Rank>,
+}
+
+/// Converts groups of equally ranked choices, ordered from the most preferred, to a ballot.
+/// Empty groups are skipped, and a choice repeated in a later group keeps its first rank.
+pub(crate) fn ranked_ballot(groups: Vec<Vec<String>>) -> HashMap<String, Rank> {
+    let mut ballot = HashMap::new();
+    let ranks = (1..=Rank::MAX.get()).filter_map(Rank::new);
+    for (rank, group) in ranks.zip(groups.into_iter().filter(|group| !group.is_empty())) {
+        for choice in group {
+            ballot.entry(choice).or_insert(rank);
+        }
+    }
+    ballot
+}
+
+/// Returns the `set_choice` index which places the choice at the position, or `None` if
+/// the position is past the end of the choices. The index counts positions from the end of
+/// the choices without the choice itself.
+pub(crate) fn set_choice_index(
+    choices: &Choices,
+    choice: &str,
+    position: ChoiceIndex,
+) -> Option<i32> {
+    let others = choices.len() - usize::from(choices.contains(choice));
+    let index = others.checked_sub(position.get())?;
+    i32::try_from(index).ok()
+}
+
+/// Validates the desired choices of `set_choices`, which must not repeat.
+pub(crate) fn validate_set_choices(choices: &[String]) -> Result<(), BadRequestError> {
+    validate_choices(choices)?;
+    let mut sorted: Vec<&String> = choices.iter().collect();
+    sorted.sort();
+    if sorted.windows(2).any(|pair| pair[0] == pair[1]) {
+        return Err(BadRequestError::InvalidData);
+    }
+    Ok(())
+}
+
+/// Returns the `set_choice` index which inserts the `new` choice right before the `old` one
+/// in the choices of a voting, or `None` if the choices do not change.
+pub(crate) fn rename_choice_index(
+    choices: &Choices,
+    old: &str,
+    new: &str,
+) -> Result<Option<i32>, ApiError> {
+    let position = choices.index_of(old).ok_or(ApiError::NotFound)?;
+    if old == new {
+        return Ok(None);
+    }
+    if choices.contains(new) {
+        return Err(rejected(BadRequestError::InvalidData));
+    }
+    Ok(set_choice_index(choices, new, position))
+}
+
+/// Converts choices ordered from the most preferred to a ballot with strictly increasing
+/// ranks.
+pub(crate) fn ordered_ballot(choices: Vec<String>) -> HashMap<String, Rank> {
+    ranked_ballot(choices.into_iter().map(|choice| vec![choice]).collect())
+}
+
+/// Converts an `ApiError::NotFound` error of a request to `Ok(None)`.
+pub(crate) fn found<T>(result: Result<T, ApiError>) -> Result<Option<T>, ApiError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(ApiError::NotFound) => Ok(None),
+        Err(err) => Err(err),
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct OkResponse {
-    code: i32,
-    message: String,
+pub(crate) struct OkResponse {
+    pub(crate) code: i32,
+    pub(crate) message: String,
+}
+
+/// The source of the API token of a `Client`.
+enum Token {
+    /// The `Authorization` header value of a static token, created once with the client.
+    Static(HeaderValue),
+    Provider(Arc<dyn TokenProvider>),
+}
+
+/// Replaces the API token in the `Debug` output of the clients and their builders.
+pub(crate) const REDACTED: &str = "[REDACTED]";
+
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Static(_) => f.write_str(REDACTED),
+            Token::Provider(_) => f.write_str("TokenProvider"),
+        }
+    }
+}
+
+impl Token {
+    /// Returns the `Authorization` header value of the token.
+    async fn authorization(&self) -> Result<HeaderValue, ClientError> {
+        match self {
+            Token::Static(authorization) => Ok(authorization.clone()),
+            Token::Provider(provider) => {
+                let token = provider.token().await.map_err(ClientError::TokenProvider)?;
+                authorization(&token).ok_or_else(|| {
+                    ClientError::TokenProvider("the token is not a valid header value".into())
+                })
+            }
+        }
+    }
 }
 
 /// A client for accessing the Direct Decisions API.
@@ -52,6 +210,9 @@ struct OkResponse {
 /// The api specification can be found at https://api.directdecisions.com/v1.
 /// All possible Error responses are described in the ApiError enum and the above documentation.
 ///
+/// Cloning a `Client` is cheap. Clones share the connection pool, the configuration and the
+/// rate limit information, so a client can be stored in application state and handed to tasks.
+///
 /// # Examples
 ///
 /// ```no_run
@@ -59,16 +220,60 @@ struct OkResponse {
 ///
 /// #[tokio::main]
 /// async fn main() {
-///     let client = Client::new("my-api-key".to_string());
+///     let client = Client::try_new("my-api-key".to_string()).unwrap();
 ///     // Use client to interact with the API...
+///
+///     let task_client = client.clone();
+///     tokio::spawn(async move {
+///         let result = task_client.get_voting("voting_id").await;
+///         // Handle result...
+///     });
 /// }
 /// ```
-
+#[derive(Clone)]
 pub struct Client {
-    token: String,
+    inner: Arc<ClientInner>,
+}
+
+struct ClientInner {
+    token: Token,
     client: reqwest::Client,
+    transport: Arc<dyn Transport>,
     api_url: String,
-    rate: Arc<Mutex<Option<Rate>>>,
+    api_version: ApiVersion,
+    rate: Mutex<Option<Rate>>,
+    endpoint_rates: EndpointRates,
+    rate_updates: watch::Sender<Option<Rate>>,
+    last_request_id: Mutex<Option<String>>,
+    last_error_context: LastErrorContext,
+    retry_policy: Option<RetryPolicy>,
+    idempotency_keys: bool,
+    validate_ballots: bool,
+    strict_responses: bool,
+    throttle: Option<Throttle>,
+    pacer: Option<RatePacer>,
+    limiter: Option<Limiter>,
+    #[cfg(feature = "rate-limit")]
+    rate_limiter: Option<RateLimiter>,
+    hedge_delay: Option<Duration>,
+    cache: Option<Cache>,
+    timeout: Option<Duration>,
+    default_headers: HeaderMap,
+    middleware: Vec<Arc<dyn Middleware>>,
+    metrics: Option<Arc<dyn MetricsObserver>>,
+}
+
+/// Formats the configuration of the client, with the API token redacted.
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("token", &self.inner.token)
+            .field("api_url", &self.inner.api_url)
+            .field("api_version", &self.inner.api_version)
+            .field("retry_policy", &self.inner.retry_policy)
+            .field("timeout", &self.inner.timeout)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Client {
@@ -80,6 +285,12 @@ impl Client {
     /// Client parses and stores received rate limit information which is updated after each request.
     /// To access the rate limit information, use `Client::get_rate`.
     ///
+    /// # Panics
+    ///
+    /// Panics if the token is empty or contains characters which are not allowed in a header,
+    /// or if the internal Reqwest client cannot be created. Use `Client::try_new` to handle
+    /// these errors instead.
+    ///
     /// # Arguments
     ///
     /// * `token` - The API token used for authenticating with the Direct Decisions API.
@@ -89,10 +300,40 @@ impl Client {
     /// ```no_run
     /// use ddclient_rs::Client;
     ///
+    /// #[allow(deprecated)]
     /// let client = Client::new("my-api-key".to_string());
     /// ```
+    #[deprecated(
+        since = "0.1.4",
+        note = "use `try_new`, which returns an error instead of panicking"
+    )]
     pub fn new(token: String) -> Self {
-        Self::builder(token).build()
+        Self::try_new(token).expect("Invalid client configuration")
+    }
+
+    /// Constructs a new `Client` with the given API token, and the default API URL.
+    /// If you need to configure the client, use `Client::builder` instead.
+    ///
+    /// Returns an `Err` with `BuildError::InvalidToken` if the token is empty or contains
+    /// characters which are not allowed in a header, or with `BuildError::HttpClient` if the
+    /// internal Reqwest client cannot be created.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The API token used for authenticating with the Direct Decisions API.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddclient_rs::{BuildError, Client};
+    ///
+    /// let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///
+    /// let result = Client::try_new(String::new());
+    /// assert!(matches!(result, Err(BuildError::InvalidToken)));
+    /// ```
+    pub fn try_new(token: String) -> Result<Self, BuildError> {
+        Self::builder(token).try_build()
     }
 
     /// Creates a new `ClientBuilder` for constructing a `Client`.
@@ -109,7 +350,8 @@ impl Client {
     /// use ddclient_rs::Client;
     ///
     /// let client = Client::builder("my-api-key".to_string())
-    ///     .build();
+    ///     .try_build()
+    ///     .unwrap();
     /// ```
     ///
     /// Advanced usage with custom configurations:
@@ -119,7 +361,8 @@ impl Client {
     ///
     /// let client = Client::builder("my-api-key".to_string())
     ///     .api_url("https://custom-api.directdecisions.com".to_string())
-    ///     .build();
+    ///     .try_build()
+    ///     .unwrap();
     /// ```
     pub fn builder(token: String) -> ClientBuilder {
         ClientBuilder::new(token)
@@ -138,8 +381,7 @@ impl Client {
     ///
     /// #[tokio::main]
     /// async fn main() {
-    ///     let client = Client::builder("my-api-key".to_string())
-    ///         .build();
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
     ///
     ///     if let Some(rate) = client.get_rate() {
     ///         println!("Current rate limit: {:?}", rate);
@@ -149,51 +391,26 @@ impl Client {
     /// }
     /// ```
     pub fn get_rate(&self) -> Option<Rate> {
-        let rate = self.rate.lock().unwrap();
+        let rate = self.inner.rate.lock().unwrap();
         rate.clone()
     }
 
-    async fn request<T: serde::Serialize>(
-        &self,
-        method: Method,
-        path: &str,
-        body: Option<T>,
-    ) -> Result<Response, ClientError> {
-        let url = format!("{}{}", self.api_url, path);
-
-        let mut request = self
-            .client
-            .request(method, url)
-            .header("Authorization", format!("Bearer {}", self.token))
-            .header("Accept", CONTENT_TYPE)
-            .header("User-Agent", USER_AGENT);
-
-        if let Some(b) = body {
-            request = request.header("Content-Type", CONTENT_TYPE);
-            request = request.json(&b);
-        }
-
-        let response = request
-            .send()
-            .await
-            .map_err(|err| ClientError::HttpRequestError(err.without_url()));
-
-        if let Ok(response) = &response {
-            let rate_update = Rate::from_headers(response.headers());
-            let mut rate = self.rate.lock().unwrap();
-            *rate = rate_update;
-        }
-
-        response
+    /// Returns the transport of the client as a `tower::Service`.
+    ///
+    /// The service sends the requests as they are, without the retries, rate limiting and
+    /// middleware of the client. See `ServiceTransport` to send the requests of a client
+    /// through a layered service.
+    #[cfg(feature = "tower")]
+    pub fn service(&self) -> TransportService {
+        TransportService::from_arc(self.inner.transport.clone())
     }
 
-    /// Creates a new voting.
-    ///
-    /// Sends a POST request to the Direct Decisions API to create a new voting
-    /// with the specified choices.
+    /// Returns a receiver of the rate limit, which is notified whenever a response with
+    /// the rate limit headers is received.
     ///
-    /// Returns a `Result` which is `Ok` containing the created `Voting` if successful,
-    /// or an `Err` with an `ApiError` if the request fails.
+    /// The receiver holds the latest rate limit, or `None` until the first one is received,
+    /// so a background task can log or alert on the quota consumption without polling
+    /// `Client::get_rate`.
     ///
     /// # Examples
     ///
@@ -202,239 +419,2102 @@ impl Client {
     ///
     /// #[tokio::main]
     /// async fn main() {
-    ///     let client = Client::builder("my-api-key".to_string()).build();
-    ///     let result = client.create_voting(vec!["Option 1".into(), "Option 2".into()]).await;
-    ///     // Handle result...
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let mut updates = client.rate_updates();
+    ///     tokio::spawn(async move {
+    ///         while updates.changed().await.is_ok() {
+    ///             if let Some(rate) = updates.borrow_and_update().as_ref() {
+    ///                 println!("{} of {} requests remaining", rate.remaining, rate.limit);
+    ///             }
+    ///         }
+    ///     });
     /// }
     /// ```
-    pub async fn create_voting(&self, choices: Vec<String>) -> Result<Voting, ApiError> {
-        let response = self
-            .request(Method::POST, "v1/votings", Some(VotingRequest { choices }))
-            .await?;
-
-        handle_api_response(response).await
-    }
-
-    /// Retrieves a voting by its ID.
-    ///
-    /// Returns a `Result` which is `Ok` containing the `Voting` if found,
-    /// or an `Err` with an `ApiError` if the voting is not found or the request fails.
-    pub async fn get_voting(&self, id: &str) -> Result<Voting, ApiError> {
-        let mut uri = "v1/votings/".to_string();
-        url_escape::encode_path_to_string(id, &mut uri);
-
-        let response = self.request::<Voting>(Method::GET, &uri, None).await?;
-
-        handle_api_response(response).await
+    pub fn rate_updates(&self) -> watch::Receiver<Option<Rate>> {
+        self.inner.rate_updates.subscribe()
     }
 
-    /// Deletes a voting by its ID.
+    /// Retrieves the rate limit of the last response from an endpoint of the family.
     ///
-    /// Returns a `Result` which is `Ok` if the voting was deleted successfully,
-    /// or an `Err` with an `ApiError` if the voting is not found or the request fails.
-    pub async fn delete_voting(&self, id: &str) -> Result<(), ApiError> {
-        let mut uri = "v1/votings/".to_string();
-        url_escape::encode_path_to_string(id, &mut uri);
-
-        let response = self
-            .request::<OkResponse>(Method::DELETE, &uri, None)
-            .await?;
-
-        let _ = handle_api_response::<OkResponse>(response).await?;
-
-        Ok(())
-    }
-
-    /// Sets or updates a choice in a voting.
-    //////
-    /// This endpoint combines all possible modifications of the choices list elements.
-    /// To add a new choice, provide its value as a string and an index where it should be placed in the list. For example, index 0 will append a new choice, while index equal to the number of choices will prepend it. For any other index number between, the choice will be inserted at that position.
-    /// To remove a choice, provide the exact choice value as the string and set index to -1 value.
-    /// To move an existing choice to a new position, provide the exact choice value as the string and its new position as the index.
+    /// The API may enforce different limits for the families of endpoints, while
+    /// `Client::get_rate` returns the rate limit of whichever request finished last.
     ///
-    /// Returns a `Result` with the updated list of choices if successful,
-    /// or an `Err` with an `ApiError` if the request fails.
     /// # Examples
     ///
     /// ```no_run
-    /// use ddclient_rs::Client;
+    /// use ddclient_rs::{Client, EndpointFamily};
     ///
     /// #[tokio::main]
     /// async fn main() {
-    ///     let client = Client::builder("my-api-key".to_string()).build();
-    ///     let result = client.set_choice("voting_id", "New Choice", 0).await;
-    ///     // Handle result...
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let _ = client.get_voting_results("voting_id").await;
+    ///
+    ///     if let Some(rate) = client.get_rate_for(EndpointFamily::Results) {
+    ///         println!("Results requests remaining: {}", rate.remaining);
+    ///     }
     /// }
     /// ```
-    pub async fn set_choice(
-        &self,
-        voting_id: &str,
-        choice: &str,
-        index: i32,
-    ) -> Result<Vec<String>, ApiError> {
-        let mut uri = "v1/votings/".to_string();
-        url_escape::encode_path_to_string(voting_id, &mut uri);
-        uri.push_str("/choices");
-
-        let response = self
-            .request(
-                Method::POST,
-                &uri,
-                Some(SetChoiceRequest {
-                    choice: choice.to_string(),
-                    index,
-                }),
-            )
-            .await?;
-
-        let resp = handle_api_response::<SetChoiceResponse>(response).await?;
-
-        Ok(resp.choices)
+    pub fn get_rate_for(&self, family: EndpointFamily) -> Option<Rate> {
+        self.inner.endpoint_rates.get(family)
     }
 
-    /// Submits a vote on a specific voting.
+    /// Retrieves the ID of the last request, as sent by the API in the `X-Request-Id`
+    /// response header.
     ///
-    /// Votes are submitted as a ballot, which is a map of choices to their ranks.
-    /// The ranks are integers starting from 1, where 1 is the highest rank.
-    /// Not all choices need to be included in the ballot.
+    /// The ID of the last failed request is also kept in its context, see
+    /// `Client::last_error_context`.
+    pub fn get_last_request_id(&self) -> Option<String> {
+        self.inner.last_request_id.lock().unwrap().clone()
+    }
+
+    /// Returns the context of the last request that failed with an `ApiError`: its method,
+    /// path, the voting and voter IDs it refers to, the response status and request ID,
+    /// and the payload fields rejected by the API.
     ///
-    /// Returns a `Result` which is `Ok` indicating whether the vote was a revote,
-    /// or an `Err` with an `ApiError` if the voting is not found or the request fails.
+    /// The context is kept until another request fails, and is shared by the clones of the
+    /// client, so it may belong to a concurrent request. Errors of requests rejected by the
+    /// client before they are sent, such as `ApiError::Validation`, have no context.
     ///
-
     /// # Examples
     ///
-    /// ```
+    /// ```no_run
     /// use ddclient_rs::Client;
-    /// use std::collections::HashMap;
     ///
     /// #[tokio::main]
     /// async fn main() {
-    ///     let client = Client::builder("my-api-key".to_string()).build();
-    ///     let ballot = HashMap::from([
-    ///         ("Choice 1".to_string(), 1),
-    ///         ("Choice 2".to_string(), 2),
-    ///     ]);
-    ///     let result = client.vote("voting_id", "voter_id", ballot).await;
-    ///     // Handle result...
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     if let Err(err) = client.get_voting("voting-id").await {
+    ///         let context = client.last_error_context().unwrap_or_default();
+    ///         eprintln!("{} ({:?} {:?})", err, context.path, context.request_id);
+    ///     }
     /// }
     /// ```
-    pub async fn vote(
-        &self,
-        voting_id: &str,
-        voter_id: &str,
-        ballot: HashMap<String, i32>,
-    ) -> Result<bool, ApiError> {
-        let mut uri = "v1/votings/".to_string();
-        url_escape::encode_path_to_string(voting_id, &mut uri);
-        uri.push_str("/ballots/");
-        url_escape::encode_path_to_string(voter_id, &mut uri);
-
-        let response = self
-            .request(Method::POST, &uri, Some(Ballot { ballot }))
-            .await?;
+    pub fn last_error_context(&self) -> Option<ErrorContext> {
+        self.inner.last_error_context.get()
+    }
 
-        let response = handle_api_response::<VoteResponse>(response).await?;
+    /// Returns the version of the API used by the client, set with
+    /// `ClientBuilder::api_version`.
+    pub fn api_version(&self) -> ApiVersion {
+        self.inner.api_version
+    }
 
-        Ok(response.revoted)
+    /// Removes the cached responses of a voting, its ballots and results, if the cache is
+    /// enabled with `ClientBuilder::cache`.
+    ///
+    /// Responses are removed automatically when the voting is changed through this client,
+    /// so this is needed only when the voting is changed by other clients.
+    pub fn invalidate_cache(&self, voting_id: &str) {
+        if let Some(cache) = &self.inner.cache {
+            cache.invalidate_voting(voting_id);
+        }
     }
 
-    /// Removes a voter's ballot from a specific voting.
-    pub async fn unvote(&self, voting_id: &str, voter_id: &str) -> Result<(), ApiError> {
-        let mut uri = "v1/votings/".to_string();
-        url_escape::encode_path_to_string(voting_id, &mut uri);
-        uri.push_str("/ballots/");
-        url_escape::encode_path_to_string(voter_id, &mut uri);
+    /// Removes all cached responses, if the cache is enabled with `ClientBuilder::cache`.
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.inner.cache {
+            cache.clear();
+        }
+    }
 
-        let response = self
-            .request::<OkResponse>(Method::DELETE, &uri, None)
-            .await?;
+    fn cached(&self, key: &CacheKey) -> Option<CacheValue> {
+        self.inner.cache.as_ref()?.get(key)
+    }
 
-        let _ = handle_api_response::<OkResponse>(response).await?;
+    /// Returns the last cached value of the key, if the request failed because the API is
+    /// unreachable.
+    fn stale(&self, key: &CacheKey, error: &ApiError) -> Option<(CacheValue, Duration)> {
+        if !is_unreachable(error) {
+            return None;
+        }
+        self.inner.cache.as_ref()?.get_stale(key)
+    }
 
-        Ok(())
+    fn cache(&self, key: CacheKey, value: impl FnOnce() -> CacheValue) {
+        if let Some(cache) = &self.inner.cache {
+            cache.insert(key, value());
+        }
     }
 
-    /// Retrieves a ballot for a specific voting and voter.
-    /// The ballot is returned as a map of choices to their ranks.
-    /// The ranks are integers starting from 1, where 1 is the highest rank.
-    pub async fn get_ballot(
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "request",
+            skip_all,
+            fields(
+                method = %method,
+                path = %path,
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+                rate_remaining = tracing::field::Empty,
+            )
+        )
+    )]
+    async fn request<T: serde::Serialize>(
         &self,
-        voting_id: &str,
-        voter_id: &str,
-    ) -> Result<HashMap<String, i32>, ApiError> {
-        let mut uri = "v1/votings/".to_string();
-        url_escape::encode_path_to_string(voting_id, &mut uri);
-        uri.push_str("/ballots/");
-        url_escape::encode_path_to_string(voter_id, &mut uri);
-
-        let response = self.request::<Ballot>(Method::GET, &uri, None).await?;
+        method: Method,
+        path: &str,
+        body: Option<T>,
+        options: &RequestOptions,
+    ) -> Result<Response, ApiError> {
+        let started = Instant::now();
+        if let Some(metrics) = &self.inner.metrics {
+            metrics.request_started(&method, path);
+        }
 
-        let response = handle_api_response::<Ballot>(response).await?;
+        let response = options
+            .interruptible(self.send(method.clone(), path, body, options))
+            .await
+            .and_then(|response| response.map_err(ApiError::from));
+        if response.is_err() {
+            self.inner
+                .last_error_context
+                .set(ErrorContext::new(method.clone(), path));
+        }
 
-        Ok(response.ballot)
-    }
+        if let Some(cache) = self.inner.cache.as_ref().filter(|_| method != Method::GET) {
+            if let Some(voting_id) = ErrorContext::new(method.clone(), path).voting_id {
+                cache.invalidate_voting(&voting_id);
+            }
+        }
 
-    /// Retrieves the results of a specific voting.
-    /// The results are returned as a list of choices with their wins, percentage, and index.
-    /// It does not include the duels information.
-    pub async fn get_voting_results(&self, voting_id: &str) -> Result<VotingResults, ApiError> {
-        let mut uri = "v1/votings/".to_string();
-        url_escape::encode_path_to_string(voting_id, &mut uri);
-        uri.push_str("/results");
+        let latency = started.elapsed();
+        let status = response.as_ref().ok().map(Response::status);
+        if let Some(metrics) = &self.inner.metrics {
+            if status == Some(StatusCode::TOO_MANY_REQUESTS) {
+                metrics.rate_limited(&method, path);
+            }
+            metrics.request_completed(&method, path, status, latency);
+        }
 
-        let response = self
-            .request::<VotingResults>(Method::GET, &uri, None)
-            .await?;
+        #[cfg(feature = "tracing")]
+        record_response(&response, latency, self.get_rate());
 
-        handle_api_response(response).await
+        response
     }
 
-    /// Retrieves the results of a specific voting.
-    /// The results are returned as a list of choices with their wins, percentage, and index.
-    /// The results also include the duels information between choices.
-    pub async fn get_voting_results_duels(
+    async fn send<T: serde::Serialize>(
         &self,
-        voting_id: &str,
-    ) -> Result<VotingResults, ApiError> {
-        let mut uri = "v1/votings/".to_string();
-        url_escape::encode_path_to_string(voting_id, &mut uri);
-        uri.push_str("/results/duels");
+        method: Method,
+        path: &str,
+        body: Option<T>,
+        options: &RequestOptions,
+    ) -> Result<Response, ClientError> {
+        let _permit = match &self.inner.limiter {
+            Some(limiter) => Some(limiter.acquire().await),
+            None => None,
+        };
 
-        let response = self
-            .request::<VotingResults>(Method::GET, &uri, None)
-            .await?;
+        if let Some(throttle) = &self.inner.throttle {
+            let delay = throttle.delay(self.inner.rate.lock().unwrap().as_ref());
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+        }
 
-        handle_api_response(response).await
-    }
-}
+        if let Some(pacer) = &self.inner.pacer {
+            pacer.acquire().await;
+        }
 
-/// A builder for creating an instance of `Client`.
-///
-/// This builder allows for configuring optional parameters for `Client`,
-/// such as a custom API URL or a custom Reqwest client.
-///
-/// # Examples
-///
-/// ```
-/// use ddclient_rs::{Client, ClientBuilder};
-///
-/// let client = Client::builder("my-api-key".to_string())
-///     .api_url("https://custom-api.directdecisions.com".to_string())
-///     .build();
-/// ```
-pub struct ClientBuilder {
-    token: String,
-    api_url: Option<String>,
-    reqwest_client: Option<reqwest::Client>,
-}
+        #[cfg(feature = "rate-limit")]
+        if let Some(rate_limiter) = &self.inner.rate_limiter {
+            let delay = rate_limiter.delay();
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+        }
+
+        let url = format!("{}{}", self.inner.api_url, path);
+        let context = ErrorContext::new(method.clone(), path);
+        let authorization = self.inner.token.authorization().await?;
+        let generate_key = self.inner.idempotency_keys && is_mutating(&method);
+
+        let mut request = self
+            .inner
+            .client
+            .request(method, url)
+            .header(header::AUTHORIZATION, authorization)
+            .header(header::ACCEPT, HeaderValue::from_static(CONTENT_TYPE))
+            .header(header::USER_AGENT, HeaderValue::from_static(USER_AGENT));
+
+        if let Some(timeout) = self.inner.timeout {
+            request = request.timeout(timeout);
+        }
+
+        if !self.inner.default_headers.is_empty() {
+            request = request.headers(self.inner.default_headers.clone());
+        }
+
+        request = options.apply(request, generate_key);
+
+        if let Some(b) = body {
+            request = request.header(header::CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE));
+            request = request.json(&b);
+        }
+
+        let mut request = request
+            .build()
+            .map_err(|err| ClientError::HttpRequestError(err.without_url()))?;
+
+        let mut attempt = 1;
+        let response = loop {
+            let retry = self
+                .inner
+                .retry_policy
+                .as_ref()
+                .filter(|policy| policy.should_retry(attempt))
+                .and_then(|policy| Some((policy.delay(attempt), request.try_clone()?)));
+
+            for middleware in &self.inner.middleware {
+                middleware.on_request(&mut request);
+            }
+            let replayable = is_replayable(request.method(), request.headers());
+
+            let response = match self.inner.hedge_delay {
+                Some(delay) => hedge::execute(self.inner.transport.as_ref(), request, delay).await,
+                None => self.inner.transport.execute(request).await,
+            };
+
+            if let Ok(response) = &response {
+                self.track_response(path, response);
+            }
+
+            match retry {
+                Some((delay, next)) if is_transient_result(&response, replayable) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(attempt, ?delay, "retrying request");
+
+                    if let Some(metrics) = &self.inner.metrics {
+                        metrics.request_retried(next.method(), path, attempt);
+                    }
+
+                    tokio::time::sleep(delay).await;
+                    request = next;
+                    attempt += 1;
+                }
+                _ => break response,
+            }
+        };
+
+        match response {
+            Ok(mut response) => {
+                response.extensions_mut().insert(context);
+                response
+                    .extensions_mut()
+                    .insert(self.inner.last_error_context.clone());
+                if self.inner.strict_responses {
+                    response.extensions_mut().insert(StrictResponses);
+                }
+                Ok(response)
+            }
+            Err(ClientError::HttpRequestError(err)) => {
+                Err(ClientError::HttpRequestError(err.without_url()))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Updates the rate limit and the last request ID from the headers of a response, and
+    /// passes it to the middleware.
+    fn track_response(&self, path: &str, response: &Response) {
+        let rate_update = Rate::from_headers(response.headers());
+        if let Some(rate) = &rate_update {
+            self.inner.endpoint_rates.update(path, rate);
+            if let Some(pacer) = &self.inner.pacer {
+                pacer.update(rate);
+            }
+            if let Some(metrics) = &self.inner.metrics {
+                metrics.rate_updated(rate);
+            }
+            self.inner.rate_updates.send_replace(Some(rate.clone()));
+        }
+        *self.inner.rate.lock().unwrap() = rate_update;
+        *self.inner.last_request_id.lock().unwrap() = request_id(response.headers());
+
+        for middleware in &self.inner.middleware {
+            middleware.on_response(response);
+        }
+    }
+
+    /// Returns a request to an endpoint of the API which is not covered by the client yet.
+    ///
+    /// The request is sent to the path relative to the API URL, which includes the version
+    /// of the API, such as `v1/votings/{id}/export`. It has the `Authorization`, `Accept`
+    /// and `User-Agent` headers of the client, its timeout and default headers, and can be
+    /// extended with a body, query parameters or other headers before it is sent with
+    /// `Client::send_raw`.
+    ///
+    /// Returns an `Err` with `ClientError::TokenProvider` if the token provider fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    /// use reqwest::Method;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let request = client
+    ///         .raw_request(Method::GET, "v1/votings/voting_id/export")
+    ///         .await
+    ///         .unwrap()
+    ///         .query(&[("format", "csv")]);
+    ///     let response = client.send_raw(request).await.unwrap();
+    ///     println!("{}", response.text().await.unwrap());
+    /// }
+    /// ```
+    pub async fn raw_request(
+        &self,
+        method: Method,
+        path: &str,
+    ) -> Result<reqwest::RequestBuilder, ApiError> {
+        let url = format!("{}{}", self.inner.api_url, path.trim_start_matches('/'));
+        let authorization = self.inner.token.authorization().await?;
+
+        let mut request = self
+            .inner
+            .client
+            .request(method, url)
+            .header(header::AUTHORIZATION, authorization)
+            .header(header::ACCEPT, HeaderValue::from_static(CONTENT_TYPE))
+            .header(header::USER_AGENT, HeaderValue::from_static(USER_AGENT));
+        if let Some(timeout) = self.inner.timeout {
+            request = request.timeout(timeout);
+        }
+        if !self.inner.default_headers.is_empty() {
+            request = request.headers(self.inner.default_headers.clone());
+        }
+        Ok(request)
+    }
+
+    /// Sends a request created with `Client::raw_request` with the transport and the
+    /// middleware of the client, and updates the rate limit and the last request ID from
+    /// the response.
+    ///
+    /// The response is returned as it is, regardless of its status, and the request is
+    /// not retried.
+    pub async fn send_raw(&self, request: reqwest::RequestBuilder) -> Result<Response, ApiError> {
+        let mut request = request
+            .build()
+            .map_err(|err| ClientError::HttpRequestError(err.without_url()))?;
+        for middleware in &self.inner.middleware {
+            middleware.on_request(&mut request);
+        }
+        let path = request.url().path().to_string();
+
+        let response = self
+            .inner
+            .transport
+            .execute(request)
+            .await
+            .map_err(|err| match err {
+                ClientError::HttpRequestError(err) => {
+                    ClientError::HttpRequestError(err.without_url())
+                }
+                err => err,
+            })?;
+        self.track_response(&path, &response);
+        Ok(response)
+    }
+
+    /// Creates a new voting.
+    ///
+    /// Sends a POST request to the Direct Decisions API to create a new voting
+    /// with the specified choices. The choices can be any iterator of strings, such as an
+    /// array of `&str` or a `Vec<String>`.
+    ///
+    /// Returns a `Result` which is `Ok` containing the created `Voting` if successful,
+    /// or an `Err` with an `ApiError` if the request fails.
+    ///
+    /// An empty list of choices, or an empty choice, is rejected with `ApiError::BadRequest`
+    /// before the request is sent.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let result = client.create_voting(["Option 1", "Option 2"]).await;
+    ///     // Handle result...
+    /// }
+    /// ```
+    pub async fn create_voting(
+        &self,
+        choices: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Voting, ApiError> {
+        self.create_voting_with_options(choices, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::create_voting`, but applies the given `RequestOptions` to the request.
+    pub async fn create_voting_with_options(
+        &self,
+        choices: impl IntoIterator<Item = impl Into<String>>,
+        request_options: &RequestOptions,
+    ) -> Result<Voting, ApiError> {
+        let choices = choices.into_iter().map(Into::into).collect();
+        self.post_voting(VotingRequest::new(choices), request_options)
+            .await
+    }
+
+    /// Returns a `CreateVotingBuilder` for creating a voting with the optional parameters
+    /// of a new voting, such as its title and description.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let result = client
+    ///         .create_voting_builder()
+    ///         .choices(vec!["Option 1".into(), "Option 2".into()])
+    ///         .title("Lunch".to_string())
+    ///         .send()
+    ///         .await;
+    ///     // Handle result...
+    /// }
+    /// ```
+    pub fn create_voting_builder(&self) -> CreateVotingBuilder<'_> {
+        CreateVotingBuilder {
+            client: self,
+            request: VotingRequest::default(),
+            request_options: RequestOptions::new(),
+        }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "create_voting", skip_all)
+    )]
+    async fn post_voting(
+        &self,
+        request: VotingRequest,
+        request_options: &RequestOptions,
+    ) -> Result<Voting, ApiError> {
+        request.validate()?;
+
+        let response = self
+            .request(
+                Method::POST,
+                Endpoint::votings(self.inner.api_version).as_str(),
+                Some(request),
+                request_options,
+            )
+            .await?;
+
+        handle_api_response(response).await
+    }
+
+    /// Retrieves a voting by its ID.
+    ///
+    /// Returns a `Result` which is `Ok` containing the `Voting` if found,
+    /// or an `Err` with an `ApiError` if the voting is not found or the request fails.
+    pub async fn get_voting(&self, id: &str) -> Result<Voting, ApiError> {
+        self.get_voting_with_options(id, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::get_voting`, but applies the given `RequestOptions` to the request.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "get_voting", skip_all, fields(id = %id))
+    )]
+    pub async fn get_voting_with_options(
+        &self,
+        id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<Voting, ApiError> {
+        let key = CacheKey::Voting(id.to_string());
+        if let Some(CacheValue::Voting(voting)) = self.cached(&key) {
+            return Ok(voting);
+        }
+
+        self.get_voting_with_meta(id, request_options)
+            .await
+            .map(ApiResponse::into_data)
+    }
+
+    /// Same as `Client::get_voting_with_options`, but returns the voting with the status,
+    /// the headers and the rate limit of the response.
+    ///
+    /// The request is always sent, even if the voting is cached.
+    pub async fn get_voting_with_meta(
+        &self,
+        id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<ApiResponse<Voting>, ApiError> {
+        let uri = Endpoint::voting(self.inner.api_version, id);
+
+        let response = self
+            .request::<Voting>(Method::GET, uri.as_str(), None, request_options)
+            .await?;
+
+        let response = ApiResponse::<Voting>::read(response).await?;
+        self.cache(CacheKey::Voting(id.to_string()), || {
+            CacheValue::Voting(response.data.clone())
+        });
+
+        Ok(response)
+    }
+
+    /// Retrieves a voting by its ID, if it exists.
+    ///
+    /// Returns a `Result` which is `Ok(None)` if the voting is not found instead of an
+    /// `Err` with `ApiError::NotFound`, or an `Err` with an `ApiError` if the request fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     match client.get_voting_opt("voting_id").await {
+    ///         Ok(Some(voting)) => println!("Choices: {:?}", voting.choices),
+    ///         Ok(None) => println!("Voting not found"),
+    ///         Err(err) => eprintln!("Error: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_voting_opt(&self, id: &str) -> Result<Option<Voting>, ApiError> {
+        found(self.get_voting(id).await)
+    }
+
+    /// Retrieves a voting by its ID, falling back to the last cached voting when the API is
+    /// unreachable.
+    ///
+    /// Returns `MaybeStale::Fresh` with the voting received from the API, or
+    /// `MaybeStale::Stale` with the last cached voting if the request failed with a
+    /// connection error, or a bad gateway or service unavailable response, and the
+    /// voting was retrieved before. Stale votings are kept only if the offline fallback is
+    /// enabled with `ClientBuilder::offline_fallback`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("my-api-key".to_string())
+    ///         .offline_fallback(true)
+    ///         .try_build()
+    ///         .unwrap();
+    ///
+    ///     let voting = client.get_voting_or_stale("voting_id").await.unwrap();
+    ///     if let Some(age) = voting.age() {
+    ///         println!("Showing data from {} seconds ago", age.as_secs());
+    ///     }
+    ///     println!("Choices: {:?}", voting.value().choices);
+    /// }
+    /// ```
+    pub async fn get_voting_or_stale(&self, id: &str) -> Result<MaybeStale<Voting>, ApiError> {
+        match self.get_voting(id).await {
+            Ok(voting) => Ok(MaybeStale::Fresh(voting)),
+            Err(error) => match self.stale(&CacheKey::Voting(id.to_string()), &error) {
+                Some((CacheValue::Voting(value), age)) => {
+                    Ok(MaybeStale::Stale { value, age, error })
+                }
+                _ => Err(error),
+            },
+        }
+    }
+
+    /// Retrieves the metadata of a voting, such as its title and description.
+    ///
+    /// Returns a `Result` which is `Ok` containing the `VotingMetadata` if successful,
+    /// or an `Err` with an `ApiError` if the voting is not found or the request fails.
+    ///
+    /// This method is available with the `experimental` feature. The metadata endpoint is
+    /// not part of the documented API yet, so it may change or be unavailable.
+    #[cfg(feature = "experimental")]
+    pub async fn get_voting_metadata(&self, voting_id: &str) -> Result<VotingMetadata, ApiError> {
+        self.get_voting_metadata_with_options(voting_id, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::get_voting_metadata`, but applies the given `RequestOptions` to the
+    /// request.
+    #[cfg(feature = "experimental")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "get_voting_metadata", skip_all, fields(voting_id = %voting_id))
+    )]
+    pub async fn get_voting_metadata_with_options(
+        &self,
+        voting_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<VotingMetadata, ApiError> {
+        let uri = Endpoint::voting(self.inner.api_version, voting_id).segment("metadata");
+
+        let response = self
+            .request::<VotingMetadata>(Method::GET, uri.as_str(), None, request_options)
+            .await?;
+
+        handle_api_response(response).await
+    }
+
+    /// Sets the metadata of a voting, such as its title and description.
+    ///
+    /// The metadata replaces the current metadata of the voting, so fields which are `None`
+    /// are removed from the voting.
+    ///
+    /// Returns a `Result` which is `Ok` containing the updated `VotingMetadata` if
+    /// successful, or an `Err` with an `ApiError` if the voting is not found or the request
+    /// fails.
+    ///
+    /// This method is available with the `experimental` feature. The metadata endpoint is
+    /// not part of the documented API yet, so it may change or be unavailable.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::{Client, VotingMetadata};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let metadata = VotingMetadata {
+    ///         title: Some("Lunch".to_string()),
+    ///         description: Some("Where do we go for lunch on Friday?".to_string()),
+    ///     };
+    ///     let result = client.set_voting_metadata("voting_id", metadata).await;
+    ///     // Handle result...
+    /// }
+    /// ```
+    #[cfg(feature = "experimental")]
+    pub async fn set_voting_metadata(
+        &self,
+        voting_id: &str,
+        metadata: VotingMetadata,
+    ) -> Result<VotingMetadata, ApiError> {
+        self.set_voting_metadata_with_options(voting_id, metadata, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::set_voting_metadata`, but applies the given `RequestOptions` to the
+    /// request.
+    #[cfg(feature = "experimental")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "set_voting_metadata", skip_all, fields(voting_id = %voting_id))
+    )]
+    pub async fn set_voting_metadata_with_options(
+        &self,
+        voting_id: &str,
+        metadata: VotingMetadata,
+        request_options: &RequestOptions,
+    ) -> Result<VotingMetadata, ApiError> {
+        let uri = Endpoint::voting(self.inner.api_version, voting_id).segment("metadata");
+
+        let response = self
+            .request(Method::PUT, uri.as_str(), Some(metadata), request_options)
+            .await?;
+
+        handle_api_response(response).await
+    }
+
+    /// Lists the votings created with the API key of the client.
+    ///
+    /// Votings are returned one page at a time. To fetch the next page, pass the
+    /// `next_cursor` of the returned `VotingsPage` to `ListOptions::cursor`.
+    ///
+    /// Returns a `Result` which is `Ok` containing the `VotingsPage` if successful,
+    /// or an `Err` with an `ApiError` if the request fails.
+    ///
+    /// This method is available with the `experimental` feature. The listing endpoint is not
+    /// part of the documented API yet, so it may change or be unavailable.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::{Client, ListOptions};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), ddclient_rs::ApiError> {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///
+    ///     let mut options = ListOptions::new().limit(100);
+    ///     loop {
+    ///         let page = client.list_votings(&options).await?;
+    ///         for voting in &page.votings {
+    ///             println!("{}: {:?}", voting.id, voting.choices);
+    ///         }
+    ///         match page.next_cursor {
+    ///             Some(cursor) => options = options.cursor(cursor),
+    ///             None => break,
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "experimental")]
+    pub async fn list_votings(&self, options: &ListOptions) -> Result<VotingsPage, ApiError> {
+        self.list_votings_with_options(options, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::list_votings`, but applies the given `RequestOptions` to the request.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "list_votings", skip_all)
+    )]
+    #[cfg(feature = "experimental")]
+    pub async fn list_votings_with_options(
+        &self,
+        options: &ListOptions,
+        request_options: &RequestOptions,
+    ) -> Result<VotingsPage, ApiError> {
+        let uri = options.append_query(Endpoint::votings(self.inner.api_version));
+
+        let response = self
+            .request::<Voting>(Method::GET, uri.as_str(), None, request_options)
+            .await?;
+
+        handle_api_response(response).await
+    }
+
+    /// Deletes a voting by its ID.
+    ///
+    /// Returns a `Result` which is `Ok` if the voting was deleted successfully,
+    /// or an `Err` with an `ApiError` if the voting is not found or the request fails.
+    pub async fn delete_voting(&self, id: &str) -> Result<(), ApiError> {
+        self.delete_voting_with_options(id, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::delete_voting`, but applies the given `RequestOptions` to the request.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "delete_voting", skip_all, fields(id = %id))
+    )]
+    pub async fn delete_voting_with_options(
+        &self,
+        id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<(), ApiError> {
+        let uri = Endpoint::voting(self.inner.api_version, id);
+
+        let response = self
+            .request::<OkResponse>(Method::DELETE, uri.as_str(), None, request_options)
+            .await?;
+
+        let _ = handle_api_response::<OkResponse>(response).await?;
+
+        Ok(())
+    }
+
+    /// Deletes many votings by their IDs concurrently.
+    ///
+    /// Up to `BulkOptions::concurrency` votings are deleted at the same time, and rate
+    /// limited deletions are retried as configured by the `BulkOptions`. Votings which are
+    /// not found are skipped, so the deletion can be repeated after a partial failure.
+    ///
+    /// Returns a `DeleteVotingsReport` with the IDs of the deleted, not found and failed
+    /// votings, in the order of the IDs.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::{BulkOptions, Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let ids = vec!["voting_1".to_string(), "voting_2".to_string()];
+    ///     let report = client.delete_votings(ids, &BulkOptions::new()).await;
+    ///     for (id, err) in &report.failed {
+    ///         eprintln!("{}: {}", id, err);
+    ///     }
+    /// }
+    /// ```
+    pub async fn delete_votings<I>(&self, ids: I, options: &BulkOptions) -> DeleteVotingsReport
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let results = bulk::run(
+            ids,
+            options,
+            |id| async move { self.delete_voting(&id).await },
+        )
+        .await;
+        DeleteVotingsReport::from_results(results)
+    }
+
+    /// Closes a voting, so it stops accepting ballots.
+    ///
+    /// The ballots and the results of a closed voting are kept, and the voting can be
+    /// reopened with `Client::reopen_voting`.
+    ///
+    /// Returns a `Result` which is `Ok` containing the closed `Voting` if successful,
+    /// or an `Err` with an `ApiError` if the voting is not found or the request fails.
+    ///
+    /// This method is available with the `experimental` feature. The close endpoint is not
+    /// part of the documented API yet, so it may change or be unavailable.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let result = client.close_voting("voting_id").await;
+    ///     // Handle result...
+    /// }
+    /// ```
+    #[cfg(feature = "experimental")]
+    pub async fn close_voting(&self, id: &str) -> Result<Voting, ApiError> {
+        self.close_voting_with_options(id, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::close_voting`, but applies the given `RequestOptions` to the request.
+    #[cfg(feature = "experimental")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "close_voting", skip_all, fields(id = %id))
+    )]
+    pub async fn close_voting_with_options(
+        &self,
+        id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<Voting, ApiError> {
+        let response = self
+            .request::<Voting>(
+                Method::POST,
+                Endpoint::voting(self.inner.api_version, id)
+                    .segment("close")
+                    .as_str(),
+                None,
+                request_options,
+            )
+            .await?;
+
+        handle_api_response(response).await
+    }
+
+    /// Reopens a closed voting, so it accepts ballots again.
+    ///
+    /// Returns a `Result` which is `Ok` containing the reopened `Voting` if successful,
+    /// or an `Err` with an `ApiError` if the voting is not found or the request fails.
+    ///
+    /// This method is available with the `experimental` feature. The reopen endpoint is not
+    /// part of the documented API yet, so it may change or be unavailable.
+    #[cfg(feature = "experimental")]
+    pub async fn reopen_voting(&self, id: &str) -> Result<Voting, ApiError> {
+        self.reopen_voting_with_options(id, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::reopen_voting`, but applies the given `RequestOptions` to the request.
+    #[cfg(feature = "experimental")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "reopen_voting", skip_all, fields(id = %id))
+    )]
+    pub async fn reopen_voting_with_options(
+        &self,
+        id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<Voting, ApiError> {
+        let response = self
+            .request::<Voting>(
+                Method::POST,
+                Endpoint::voting(self.inner.api_version, id)
+                    .segment("reopen")
+                    .as_str(),
+                None,
+                request_options,
+            )
+            .await?;
+
+        handle_api_response(response).await
+    }
+
+    /// Sets or updates a choice in a voting.
+    //////
+    /// This endpoint combines all possible modifications of the choices list elements.
+    /// To add a new choice, provide its value as a string and an index where it should be placed in the list. For example, index 0 will append a new choice, while index equal to the number of choices will prepend it. For any other index number between, the choice will be inserted at that position.
+    /// To remove a choice, provide the exact choice value as the string and set index to -1 value.
+    /// To move an existing choice to a new position, provide the exact choice value as the string and its new position as the index.
+    ///
+    /// Returns a `Result` with the updated list of choices if successful,
+    /// or an `Err` with an `ApiError` if the request fails. An empty choice is rejected
+    /// with `ApiError::BadRequest` before the request is sent.
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let result = client.set_choice("voting_id", "New Choice", 0).await;
+    ///     // Handle result...
+    /// }
+    /// ```
+    pub async fn set_choice(
+        &self,
+        voting_id: &str,
+        choice: &str,
+        index: i32,
+    ) -> Result<Choices, ApiError> {
+        self.set_choice_with_options(voting_id, choice, index, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::set_choice`, but applies the given `RequestOptions` to the request.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "set_choice", skip_all, fields(voting_id = %voting_id, index = index))
+    )]
+    pub async fn set_choice_with_options(
+        &self,
+        voting_id: &str,
+        choice: &str,
+        index: i32,
+        request_options: &RequestOptions,
+    ) -> Result<Choices, ApiError> {
+        let uri = Endpoint::voting(self.inner.api_version, voting_id).segment("choices");
+        validate_choice(choice).map_err(rejected)?;
+
+        let response = self
+            .request(
+                Method::POST,
+                uri.as_str(),
+                Some(SetChoiceRequest {
+                    choice: choice.to_string(),
+                    index,
+                }),
+                request_options,
+            )
+            .await?;
+
+        let resp = handle_api_response::<SetChoiceResponse>(response).await?;
+
+        Ok(resp.choices)
+    }
+
+    /// Adds a choice at the end of the choices of a voting, or moves an existing choice
+    /// to the end.
+    ///
+    /// Returns a `Result` with the updated list of choices if successful,
+    /// or an `Err` with an `ApiError` if the request fails.
+    pub async fn append_choice(&self, voting_id: &str, choice: &str) -> Result<Choices, ApiError> {
+        self.set_choice(voting_id, choice, 0).await
+    }
+
+    /// Adds a choice at the beginning of the choices of a voting, or moves an existing
+    /// choice to the beginning.
+    ///
+    /// The current choices are retrieved first, see `Client::insert_choice_at`.
+    pub async fn prepend_choice(&self, voting_id: &str, choice: &str) -> Result<Choices, ApiError> {
+        self.insert_choice_at(voting_id, choice, ChoiceIndex::new(0))
+            .await
+    }
+
+    /// Adds a choice at the given position of the choices of a voting, or moves an existing
+    /// choice to that position.
+    ///
+    /// The `set_choice` index counts positions from the end of the list, so the current
+    /// choices are retrieved first to translate the position, which takes an additional
+    /// request. If the choices are modified concurrently between the two requests, the
+    /// choice may end up at a different position.
+    ///
+    /// Returns a `Result` with the updated list of choices if successful,
+    /// or an `Err` with an `ApiError` if the request fails. A position past the end of the
+    /// choices is rejected with `ApiError::BadRequest` before the choice is set.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::{ChoiceIndex, Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let result = client
+    ///         .insert_choice_at("voting_id", "Choice 2", ChoiceIndex::new(1))
+    ///         .await;
+    ///     // Handle result...
+    /// }
+    /// ```
+    pub async fn insert_choice_at(
+        &self,
+        voting_id: &str,
+        choice: &str,
+        position: ChoiceIndex,
+    ) -> Result<Choices, ApiError> {
+        let voting = self.get_voting(voting_id).await?;
+        let index = set_choice_index(&voting.choices, choice, position)
+            .ok_or_else(|| rejected(BadRequestError::InvalidData))?;
+        self.set_choice(voting_id, choice, index).await
+    }
+
+    /// Moves an existing choice to the given position of the choices of a voting.
+    ///
+    /// Same as `Client::insert_choice_at`, which adds the choice if it does not exist.
+    pub async fn move_choice(
+        &self,
+        voting_id: &str,
+        choice: &str,
+        position: ChoiceIndex,
+    ) -> Result<Choices, ApiError> {
+        self.insert_choice_at(voting_id, choice, position).await
+    }
+
+    /// Replaces the choices of a voting with the given list.
+    ///
+    /// The current choices are retrieved first and compared with the desired ones, and
+    /// only the choices that are not already in place are set with `Client::set_choice`,
+    /// so reordering a list takes as few requests as possible. Choices that are not in the
+    /// list are removed last.
+    ///
+    /// The changes are not atomic. If a request fails, the error is returned and the
+    /// choices are left partially updated, and calling `set_choices` again with the same
+    /// list completes the change.
+    ///
+    /// Returns a `Result` with the updated list of choices if successful,
+    /// or an `Err` with an `ApiError` if any request fails. Missing, empty or repeated
+    /// choices are rejected with `ApiError::BadRequest` before any change.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let choices = vec!["Choice 3".to_string(), "Choice 1".to_string()];
+    ///     let result = client.set_choices("voting_id", choices).await;
+    ///     // Handle result...
+    /// }
+    /// ```
+    pub async fn set_choices(
+        &self,
+        voting_id: &str,
+        choices: Vec<String>,
+    ) -> Result<Choices, ApiError> {
+        validate_set_choices(&choices).map_err(rejected)?;
+
+        let mut current = self.get_voting(voting_id).await?.choices;
+        for (choice, index) in set_choices_plan(&current, &choices) {
+            current = self.set_choice(voting_id, &choice, index).await?;
+        }
+        Ok(current)
+    }
+
+    /// Renames a choice of a voting, keeping its position.
+    ///
+    /// The new choice is inserted before the old one first, and the old choice is removed
+    /// after that, so the voting never loses the choice if a request fails. If the removal
+    /// fails, the new choice is removed again to restore the original choices, and the
+    /// error of the removal is returned. Ballots ranking the old choice are not changed.
+    ///
+    /// Returns a `Result` with the updated list of choices if successful,
+    /// or an `Err` with an `ApiError` if the request fails. If the old choice does not
+    /// exist, `ApiError::NotFound` is returned, and if the new choice is empty, already
+    /// exists or is rejected by the API, `ApiError::BadRequest`, without changing the choices.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let result = client.rename_choice("voting_id", "Choice 1", "Option 1").await;
+    ///     // Handle result...
+    /// }
+    /// ```
+    pub async fn rename_choice(
+        &self,
+        voting_id: &str,
+        old: &str,
+        new: &str,
+    ) -> Result<Choices, ApiError> {
+        validate_choice(new).map_err(rejected)?;
+
+        let voting = self.get_voting(voting_id).await?;
+        let Some(index) = rename_choice_index(&voting.choices, old, new)? else {
+            return Ok(voting.choices);
+        };
+
+        self.set_choice(voting_id, new, index).await?;
+        match self.set_choice(voting_id, old, -1).await {
+            Ok(choices) => Ok(choices),
+            Err(err) => {
+                let _ = self.set_choice(voting_id, new, -1).await;
+                Err(err)
+            }
+        }
+    }
+
+    /// Removes a choice from a voting.
+    ///
+    /// Returns a `Result` with the updated list of choices if successful,
+    /// or an `Err` with an `ApiError` if the choice or the voting is not found or the
+    /// request fails.
+    pub async fn remove_choice(&self, voting_id: &str, choice: &str) -> Result<Choices, ApiError> {
+        self.set_choice(voting_id, choice, -1).await
+    }
+
+    /// Submits a vote on a specific voting.
+    ///
+    /// Votes are submitted as a ballot, which is a map of choices to their ranks.
+    /// The ranks start from 1, where 1 is the highest rank, see `Rank`.
+    /// Not all choices need to be included in the ballot.
+    ///
+    /// The ballot can be a `HashMap<String, Rank>`, or any iterator of choice and rank pairs,
+    /// such as an array of `(&str, Rank)` tuples. If a choice is repeated, its last rank is
+    /// submitted.
+    ///
+    /// Returns a `Result` which is `Ok` indicating whether the vote was a revote,
+    /// or an `Err` with an `ApiError` if the voting is not found or the request fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddclient_rs::{Client, Rank};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let ballot = [("Choice 1", Rank::HIGHEST), ("Choice 2", Rank::new(2).unwrap())];
+    ///     let result = client.vote("voting_id", "voter_id", ballot).await;
+    ///     // Handle result...
+    /// }
+    /// ```
+    pub async fn vote(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        ballot: impl IntoIterator<Item = (impl Into<String>, Rank)>,
+    ) -> Result<bool, ApiError> {
+        self.vote_with_options(voting_id, voter_id, ballot, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::vote`, but applies the given `RequestOptions` to the request.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "vote", skip_all, fields(voting_id = %voting_id, voter_id = %voter_id))
+    )]
+    pub async fn vote_with_options(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        ballot: impl IntoIterator<Item = (impl Into<String>, Rank)>,
+        request_options: &RequestOptions,
+    ) -> Result<bool, ApiError> {
+        self.vote_with_meta(voting_id, voter_id, ballot, request_options)
+            .await
+            .map(ApiResponse::into_data)
+    }
+
+    /// Same as `Client::vote_with_options`, but returns whether the vote was a revote with
+    /// the status, the headers and the rate limit of the response.
+    pub async fn vote_with_meta(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        ballot: impl IntoIterator<Item = (impl Into<String>, Rank)>,
+        request_options: &RequestOptions,
+    ) -> Result<ApiResponse<bool>, ApiError> {
+        let ballot: HashMap<String, Rank> = ballot
+            .into_iter()
+            .map(|(choice, rank)| (choice.into(), rank))
+            .collect();
+        request_options.validate_ballot(&ballot, self.inner.validate_ballots)?;
+
+        let uri = Endpoint::ballot(self.inner.api_version, voting_id, voter_id);
+
+        let response = self
+            .request(
+                Method::POST,
+                uri.as_str(),
+                Some(Ballot { ballot }),
+                request_options,
+            )
+            .await?;
+
+        let response = ApiResponse::<VoteResponse>::read(response).await?;
+
+        Ok(response.map(|response| response.revoted))
+    }
+
+    /// Submits a vote on a specific voting, with the choices given in groups of equally
+    /// ranked choices.
+    ///
+    /// The groups are ordered from the most preferred, so the choices of the first group
+    /// get rank 1, the choices of the second group rank 2, and so on. Empty groups are
+    /// skipped, and a choice repeated in a later group keeps its first rank.
+    ///
+    /// Returns a `Result` which is `Ok` indicating whether the vote was a revote,
+    /// or an `Err` with an `ApiError` if the voting is not found or the request fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let ranks = vec![
+    ///         vec!["Choice 1".to_string()],
+    ///         vec!["Choice 2".to_string(), "Choice 3".to_string()],
+    ///     ];
+    ///     let result = client.vote_ranked("voting_id", "voter_id", ranks).await;
+    ///     // Handle result...
+    /// }
+    /// ```
+    pub async fn vote_ranked(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        ranks: Vec<Vec<String>>,
+    ) -> Result<bool, ApiError> {
+        self.vote(voting_id, voter_id, ranked_ballot(ranks)).await
+    }
+
+    /// Submits a vote on a specific voting, with the choices ordered from the most
+    /// preferred.
+    ///
+    /// The choices get ranks from 1 in the given order. A repeated choice keeps its first
+    /// rank. To rank some choices equally, use `Client::vote_ranked`.
+    ///
+    /// Returns a `Result` which is `Ok` indicating whether the vote was a revote,
+    /// or an `Err` with an `ApiError` if the voting is not found or the request fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let choices = vec!["Choice 2".to_string(), "Choice 1".to_string()];
+    ///     let result = client.vote_ordered("voting_id", "voter_id", choices).await;
+    ///     // Handle result...
+    /// }
+    /// ```
+    pub async fn vote_ordered(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        choices: Vec<String>,
+    ) -> Result<bool, ApiError> {
+        self.vote(voting_id, voter_id, ordered_ballot(choices))
+            .await
+    }
+
+    /// Submits the ballots of many voters on a specific voting concurrently.
+    ///
+    /// The ballots are given as pairs of voter IDs and ballots. Up to
+    /// `BulkOptions::concurrency` votes are sent at the same time, and rate limited votes
+    /// are retried after the rate limit allows them again, as configured by the `BulkOptions`.
+    ///
+    /// Returns the voter IDs with the results of their votes, in the order of the ballots.
+    /// A result is `Ok` indicating whether the vote was a revote, or an `Err` with an
+    /// `ApiError` if the vote fails. A failed vote does not stop the other votes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::{BulkOptions, Client, Rank};
+    /// use std::collections::HashMap;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let ballots = vec![
+    ///         ("voter_1".to_string(), HashMap::from([("Choice 1".to_string(), Rank::HIGHEST)])),
+    ///         ("voter_2".to_string(), HashMap::from([("Choice 2".to_string(), Rank::HIGHEST)])),
+    ///     ];
+    ///     let options = BulkOptions::new().concurrency(4);
+    ///     for (voter_id, result) in client.vote_many("voting_id", ballots, &options).await {
+    ///         if let Err(err) = result {
+    ///             eprintln!("{}: {}", voter_id, err);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn vote_many<I>(
+        &self,
+        voting_id: &str,
+        ballots: I,
+        options: &BulkOptions,
+    ) -> Vec<(String, Result<bool, ApiError>)>
+    where
+        I: IntoIterator<Item = (String, HashMap<String, Rank>)>,
+    {
+        bulk::run(ballots, options, |(voter_id, ballot)| async move {
+            self.vote(voting_id, &voter_id, ballot).await
+        })
+        .await
+        .into_iter()
+        .map(|((voter_id, _), result)| (voter_id, result))
+        .collect()
+    }
+
+    /// Removes a voter's ballot from a specific voting.
+    pub async fn unvote(&self, voting_id: &str, voter_id: &str) -> Result<(), ApiError> {
+        self.unvote_with_options(voting_id, voter_id, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::unvote`, but applies the given `RequestOptions` to the request.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "unvote", skip_all, fields(voting_id = %voting_id, voter_id = %voter_id))
+    )]
+    pub async fn unvote_with_options(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<(), ApiError> {
+        let uri = Endpoint::ballot(self.inner.api_version, voting_id, voter_id);
+
+        let response = self
+            .request::<OkResponse>(Method::DELETE, uri.as_str(), None, request_options)
+            .await?;
+
+        let _ = handle_api_response::<OkResponse>(response).await?;
+
+        Ok(())
+    }
+
+    /// Retrieves a ballot for a specific voting and voter.
+    /// The ballot is returned as a map of choices to their ranks.
+    /// The ranks are integers starting from 1, where 1 is the highest rank.
+    pub async fn get_ballot(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+    ) -> Result<HashMap<String, Rank>, ApiError> {
+        self.get_ballot_with_options(voting_id, voter_id, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::get_ballot`, but applies the given `RequestOptions` to the request.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "get_ballot", skip_all, fields(voting_id = %voting_id, voter_id = %voter_id))
+    )]
+    pub async fn get_ballot_with_options(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<HashMap<String, Rank>, ApiError> {
+        let key = CacheKey::Ballot(voting_id.to_string(), voter_id.to_string());
+        if let Some(CacheValue::Ballot(ballot)) = self.cached(&key) {
+            return Ok(ballot);
+        }
+
+        self.get_ballot_with_meta(voting_id, voter_id, request_options)
+            .await
+            .map(ApiResponse::into_data)
+    }
+
+    /// Same as `Client::get_ballot_with_options`, but returns the ballot with the status,
+    /// the headers and the rate limit of the response.
+    ///
+    /// The request is always sent, even if the ballot is cached.
+    pub async fn get_ballot_with_meta(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<ApiResponse<HashMap<String, Rank>>, ApiError> {
+        let uri = Endpoint::ballot(self.inner.api_version, voting_id, voter_id);
+
+        let response = self
+            .request::<Ballot>(Method::GET, uri.as_str(), None, request_options)
+            .await?;
+
+        let response = ApiResponse::<Ballot>::read(response).await?;
+        self.cache(
+            CacheKey::Ballot(voting_id.to_string(), voter_id.to_string()),
+            || CacheValue::Ballot(response.data.ballot.clone()),
+        );
+
+        Ok(response.map(|response| response.ballot))
+    }
+
+    /// Retrieves a ballot for a specific voting and voter, if it exists.
+    ///
+    /// Returns `Ok(None)` instead of `ApiError::NotFound` if the ballot or the voting does
+    /// not exist.
+    pub async fn get_ballot_opt(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+    ) -> Result<Option<HashMap<String, Rank>>, ApiError> {
+        found(self.get_ballot(voting_id, voter_id).await)
+    }
+
+    /// Checks whether a voter has submitted a ballot to a specific voting.
+    ///
+    /// Returns `Ok(false)` instead of `ApiError::NotFound` if the ballot does not exist.
+    /// The API responds with the same status if the voting does not exist, so a missing
+    /// voting also returns `Ok(false)`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let voted = client.has_ballot("voting_id", "voter_id").await;
+    ///     // Handle result...
+    /// }
+    /// ```
+    pub async fn has_ballot(&self, voting_id: &str, voter_id: &str) -> Result<bool, ApiError> {
+        let ballot = self.get_ballot_opt(voting_id, voter_id).await?;
+        Ok(ballot.is_some())
+    }
+
+    /// Lists the ballots submitted to a specific voting.
+    ///
+    /// Ballots are returned one page at a time. To fetch the next page, pass the
+    /// `next_cursor` of the returned `BallotsPage` to `ListOptions::cursor`.
+    /// To iterate over all ballots without handling pagination, use `Client::ballots_stream`.
+    ///
+    /// This method is available with the `experimental` feature. The ballots listing endpoint
+    /// is not part of the documented API yet, so it may change or be unavailable.
+    #[cfg(feature = "experimental")]
+    pub async fn list_ballots(
+        &self,
+        voting_id: &str,
+        options: &ListOptions,
+    ) -> Result<BallotsPage, ApiError> {
+        self.list_ballots_with_options(voting_id, options, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::list_ballots`, but applies the given `RequestOptions` to the request.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "list_ballots", skip_all, fields(voting_id = %voting_id))
+    )]
+    #[cfg(feature = "experimental")]
+    pub async fn list_ballots_with_options(
+        &self,
+        voting_id: &str,
+        options: &ListOptions,
+        request_options: &RequestOptions,
+    ) -> Result<BallotsPage, ApiError> {
+        let uri = options
+            .append_query(Endpoint::voting(self.inner.api_version, voting_id).segment("ballots"));
+
+        let response = self
+            .request::<Ballot>(Method::GET, uri.as_str(), None, request_options)
+            .await?;
+
+        handle_api_response(response).await
+    }
+
+    /// Returns a stream over all ballots submitted to a specific voting.
+    ///
+    /// The stream yields `(voter_id, ballot)` pairs and transparently follows pagination,
+    /// fetching the next page only after all ballots of the previous page were consumed,
+    /// so ballots can be processed incrementally without buffering them all in memory.
+    /// The stream ends after the first error.
+    ///
+    /// This method is available with the `experimental` feature, as it pages through the
+    /// ballots with `Client::list_ballots`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    /// use futures_util::TryStreamExt;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), ddclient_rs::ApiError> {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///
+    ///     let mut ballots = std::pin::pin!(client.ballots_stream("voting_id"));
+    ///     while let Some((voter_id, ballot)) = ballots.try_next().await? {
+    ///         println!("{}: {:?}", voter_id, ballot);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "experimental")]
+    pub fn ballots_stream<'a>(
+        &'a self,
+        voting_id: &'a str,
+    ) -> impl Stream<Item = Result<(String, HashMap<String, Rank>), ApiError>> + 'a {
+        self.ballots_stream_with_options(voting_id, RequestOptions::none())
+    }
+
+    /// Same as `Client::ballots_stream`, but applies the given `RequestOptions` to the request.
+    #[cfg(feature = "experimental")]
+    pub fn ballots_stream_with_options<'a>(
+        &'a self,
+        voting_id: &'a str,
+        request_options: &'a RequestOptions,
+    ) -> impl Stream<Item = Result<(String, HashMap<String, Rank>), ApiError>> + 'a {
+        stream::try_unfold(Some(ListOptions::new()), move |options| async move {
+            let Some(options) = options else {
+                return Ok::<_, ApiError>(None);
+            };
+
+            let page = self
+                .list_ballots_with_options(voting_id, &options, request_options)
+                .await?;
+            let next = page.next_cursor.map(|cursor| options.cursor(cursor));
+
+            Ok(Some((page.ballots, next)))
+        })
+        .map_ok(|ballots| {
+            stream::iter(
+                ballots
+                    .into_iter()
+                    .map(|b| Ok::<_, ApiError>((b.voter_id, b.ballot))),
+            )
+        })
+        .try_flatten()
+    }
+
+    /// Watches the results of a specific voting by polling them at the given interval.
+    ///
+    /// The returned stream yields the current results first, and then the results only when
+    /// they are different from the previously yielded results. When a request is rate
+    /// limited, polling pauses until the rate limit allows requests again, and other errors
+    /// are yielded without ending the stream, so the caller decides whether to continue.
+    /// The stream never ends on its own.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    /// use futures_util::StreamExt;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///
+    ///     let results = client.watch_results("voting_id", Duration::from_secs(5));
+    ///     futures_util::pin_mut!(results);
+    ///     while let Some(results) = results.next().await {
+    ///         match results {
+    ///             Ok(results) => println!("Leader: {}", results.results[0].choice),
+    ///             Err(err) => eprintln!("Error: {}", err),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn watch_results<'a>(
+        &'a self,
+        voting_id: &'a str,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<VotingResults, ApiError>> + 'a {
+        stream::unfold(
+            (None::<VotingResults>, Duration::ZERO),
+            move |(mut last, mut delay)| async move {
+                loop {
+                    if !delay.is_zero() {
+                        tokio::time::sleep(delay).await;
+                    }
+                    delay = interval;
+
+                    match self.get_voting_results(voting_id).await {
+                        Ok(results) if last.as_ref() == Some(&results) => {}
+                        Ok(results) => {
+                            last = Some(results.clone());
+                            return Some((Ok(results), (last, delay)));
+                        }
+                        Err(err) if err.is_rate_limited() => {
+                            delay = rate_limit_delay(&err).max(interval);
+                        }
+                        Err(err) => return Some((Err(err), (last, delay))),
+                    }
+                }
+            },
+        )
+    }
+
+    /// Subscribes to the changes of a voting, such as submitted ballots and updated results.
+    ///
+    /// Sends a GET request to the `v1/votings/{id}/events` endpoint of the API, and returns a
+    /// stream of the events received as Server-Sent Events. Errors of the request, or of
+    /// reading the response, are yielded as the last item of the stream, and the stream ends
+    /// when the API closes the connection, so the caller decides whether to subscribe again.
+    ///
+    /// The timeout of the client applies to the whole subscription. Use a client without a
+    /// timeout, or a `RequestOptions` timeout, for long-lived subscriptions.
+    ///
+    /// This method is available with the `sse` feature. The events endpoint is not part of
+    /// the documented API yet, so the subscription fails with an error of the API until the
+    /// API provides it, and the events may change.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::{Client, VotingEvent};
+    /// use futures_util::StreamExt;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///
+    ///     let events = client.subscribe_events("voting_id");
+    ///     futures_util::pin_mut!(events);
+    ///     while let Some(event) = events.next().await {
+    ///         match event {
+    ///             Ok(VotingEvent::BallotSubmitted { voter_id }) => println!("{} voted", voter_id),
+    ///             Ok(event) => println!("{:?}", event),
+    ///             Err(err) => eprintln!("Error: {}", err),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "sse")]
+    pub fn subscribe_events<'a>(
+        &'a self,
+        voting_id: &'a str,
+    ) -> impl Stream<Item = Result<VotingEvent, ApiError>> + 'a {
+        self.subscribe_events_with_options(voting_id, RequestOptions::none())
+    }
+
+    /// Same as `Client::subscribe_events`, but applies the given `RequestOptions` to the request.
+    #[cfg(feature = "sse")]
+    pub fn subscribe_events_with_options<'a>(
+        &'a self,
+        voting_id: &'a str,
+        request_options: &'a RequestOptions,
+    ) -> impl Stream<Item = Result<VotingEvent, ApiError>> + 'a {
+        enum State {
+            Connecting,
+            Open(Box<(Response, SseParser, VecDeque<VotingEvent>)>),
+            Closed,
+        }
+
+        stream::unfold(State::Connecting, move |state| async move {
+            let (mut response, mut parser, mut events) = match state {
+                State::Connecting => match self.open_events(voting_id, request_options).await {
+                    Ok(response) => (response, SseParser::default(), VecDeque::new()),
+                    Err(err) => return Some((Err(err), State::Closed)),
+                },
+                State::Open(open) => *open,
+                State::Closed => return None,
+            };
+
+            loop {
+                if let Some(event) = events.pop_front() {
+                    let open = Box::new((response, parser, events));
+                    return Some((Ok(event), State::Open(open)));
+                }
+                match response.chunk().await {
+                    Ok(Some(chunk)) => events.extend(
+                        parser
+                            .feed(&chunk)
+                            .into_iter()
+                            .map(VotingEvent::from_message),
+                    ),
+                    Ok(None) => return None,
+                    Err(err) => {
+                        let err = ApiError::Client(ClientError::HttpRequestError(err));
+                        return Some((Err(err), State::Closed));
+                    }
+                }
+            }
+        })
+    }
+
+    #[cfg(feature = "sse")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "subscribe_events", skip_all, fields(voting_id = %voting_id))
+    )]
+    async fn open_events(
+        &self,
+        voting_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<Response, ApiError> {
+        let uri = Endpoint::voting(self.inner.api_version, voting_id).segment("events");
+
+        let mut options = request_options.clone();
+        if !options.headers().contains_key(header::ACCEPT) {
+            options = options.header(header::ACCEPT, HeaderValue::from_static(EVENT_STREAM));
+        }
+
+        let response = self
+            .request::<()>(Method::GET, uri.as_str(), None, &options)
+            .await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response),
+            _ => Err(response_error(response).await),
+        }
+    }
+
+    /// Retrieves the results of a specific voting.
+    /// The results are returned as a list of choices with their wins, percentage, and index.
+    /// It does not include the duels information.
+    pub async fn get_voting_results(&self, voting_id: &str) -> Result<VotingResults, ApiError> {
+        self.get_voting_results_with_options(voting_id, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::get_voting_results`, but applies the given `RequestOptions` to the request.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "get_voting_results", skip_all, fields(voting_id = %voting_id))
+    )]
+    pub async fn get_voting_results_with_options(
+        &self,
+        voting_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<VotingResults, ApiError> {
+        let key = CacheKey::Results(voting_id.to_string());
+        if let Some(CacheValue::Results(results)) = self.cached(&key) {
+            return Ok(results);
+        }
+
+        self.get_voting_results_with_meta(voting_id, request_options)
+            .await
+            .map(ApiResponse::into_data)
+    }
+
+    /// Same as `Client::get_voting_results_with_options`, but returns the results with the
+    /// status, the headers and the rate limit of the response.
+    ///
+    /// The request is always sent, even if the results are cached.
+    pub async fn get_voting_results_with_meta(
+        &self,
+        voting_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<ApiResponse<VotingResults>, ApiError> {
+        let uri = Endpoint::voting(self.inner.api_version, voting_id).segment("results");
+
+        let response = self
+            .request::<VotingResults>(Method::GET, uri.as_str(), None, request_options)
+            .await?;
+
+        let response = ApiResponse::<VotingResults>::read(response).await?;
+        self.cache(CacheKey::Results(voting_id.to_string()), || {
+            CacheValue::Results(response.data.clone())
+        });
+
+        Ok(response)
+    }
+
+    /// Retrieves the results of a specific voting, falling back to the last cached results
+    /// when the API is unreachable.
+    ///
+    /// See `Client::get_voting_or_stale`.
+    pub async fn get_voting_results_or_stale(
+        &self,
+        voting_id: &str,
+    ) -> Result<MaybeStale<VotingResults>, ApiError> {
+        match self.get_voting_results(voting_id).await {
+            Ok(results) => Ok(MaybeStale::Fresh(results)),
+            Err(error) => match self.stale(&CacheKey::Results(voting_id.to_string()), &error) {
+                Some((CacheValue::Results(value), age)) => {
+                    Ok(MaybeStale::Stale { value, age, error })
+                }
+                _ => Err(error),
+            },
+        }
+    }
+
+    /// Retrieves the results of a specific voting, including the duels information.
+    /// The results are returned as a list of choices with their wins, percentage, and index.
+    /// The results also include the duels information between choices, requested from the
+    /// `results/duels` endpoint. The `duels` field of the returned `VotingResults` is always
+    /// `Some`, and contains an empty list if the voting has no duels.
+    pub async fn get_voting_results_duels(
+        &self,
+        voting_id: &str,
+    ) -> Result<VotingResults, ApiError> {
+        self.get_voting_results_duels_with_options(voting_id, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::get_voting_results_duels`, but applies the given `RequestOptions` to the request.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "get_voting_results_duels", skip_all, fields(voting_id = %voting_id))
+    )]
+    pub async fn get_voting_results_duels_with_options(
+        &self,
+        voting_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<VotingResults, ApiError> {
+        let uri = Endpoint::voting(self.inner.api_version, voting_id).segment("results/duels");
+
+        let response = self
+            .request::<VotingResults>(Method::GET, uri.as_str(), None, request_options)
+            .await?;
+
+        let mut results: VotingResults = handle_api_response(response).await?;
+        results.duels.get_or_insert_with(Vec::new);
+
+        Ok(results)
+    }
+
+    /// Retrieves the pairwise preferences of a specific voting, the number of voters
+    /// preferring each choice over every other choice.
+    ///
+    /// The matrix is created from the duels information of the voting results.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     if let Ok(matrix) = client.get_pairwise_matrix("voting_id").await {
+    ///         println!("{:?}", matrix.margin("Choice 1", "Choice 2"));
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_pairwise_matrix(&self, voting_id: &str) -> Result<PairwiseMatrix, ApiError> {
+        self.get_pairwise_matrix_with_options(voting_id, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::get_pairwise_matrix`, but applies the given `RequestOptions` to the
+    /// request.
+    pub async fn get_pairwise_matrix_with_options(
+        &self,
+        voting_id: &str,
+        request_options: &RequestOptions,
+    ) -> Result<PairwiseMatrix, ApiError> {
+        let results = self
+            .get_voting_results_duels_with_options(voting_id, request_options)
+            .await?;
+
+        Ok(PairwiseMatrix::from_results(&results))
+    }
+
+    /// Creates a human-readable summary of a voting.
+    ///
+    /// Fetches the voting and its results, including the duels information, and renders the
+    /// choices, ranked standings, notable duels and tie information in the given format. The
+    /// API does not report the turnout, so it is only rendered by summaries created with
+    /// `VotingSummary::with_stats`.
+    ///
+    /// Returns a `Result` with the rendered summary if successful,
+    /// or an `Err` with an `ApiError` if the voting is not found or any of the requests fail.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddclient_rs::{Client, ReportFormat};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+    ///     let summary = client.summarize_voting("voting_id", ReportFormat::Markdown).await;
+    ///     // Post the summary...
+    /// }
+    /// ```
+    pub async fn summarize_voting(
+        &self,
+        voting_id: &str,
+        format: ReportFormat,
+    ) -> Result<String, ApiError> {
+        self.summarize_voting_with_options(voting_id, format, RequestOptions::none())
+            .await
+    }
+
+    /// Same as `Client::summarize_voting`, but applies the given `RequestOptions` to the request.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "summarize_voting", skip_all, fields(voting_id = %voting_id, format = ?format))
+    )]
+    pub async fn summarize_voting_with_options(
+        &self,
+        voting_id: &str,
+        format: ReportFormat,
+        request_options: &RequestOptions,
+    ) -> Result<String, ApiError> {
+        let (voting, results) = tokio::try_join!(
+            self.get_voting_with_options(voting_id, request_options),
+            self.get_voting_results_duels_with_options(voting_id, request_options)
+        )?;
+
+        Ok(VotingSummary::new(voting, results).render(format))
+    }
+}
+
+/// A builder for creating a voting with optional parameters, returned by
+/// `Client::create_voting_builder`.
+///
+/// Only the choices are required. The title and the description are sent only if they are
+/// set, and parameters which are not supported by this builder yet can be sent with
+/// `CreateVotingBuilder::option`.
+pub struct CreateVotingBuilder<'a> {
+    client: &'a Client,
+    request: VotingRequest,
+    request_options: RequestOptions,
+}
+
+impl CreateVotingBuilder<'_> {
+    /// Sets the choices of the voting, replacing the choices added before.
+    pub fn choices(mut self, choices: Vec<String>) -> Self {
+        self.request.choices = choices;
+        self
+    }
+
+    /// Adds a choice after the choices added before.
+    pub fn choice(mut self, choice: String) -> Self {
+        self.request.choices.push(choice);
+        self
+    }
+
+    /// Sets the title of the voting.
+    pub fn title(mut self, title: String) -> Self {
+        self.request.title = Some(title);
+        self
+    }
+
+    /// Sets the description of the voting.
+    pub fn description(mut self, description: String) -> Self {
+        self.request.description = Some(description);
+        self
+    }
+
+    /// Sets a parameter of the new voting by its name in the API payload.
+    ///
+    /// This allows sending parameters of new server-side features, such as duels or the
+    /// visibility of the voting, before they are supported by this builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the parameter in the JSON payload.
+    /// * `value` - The JSON value of the parameter.
+    pub fn option(mut self, name: String, value: serde_json::Value) -> Self {
+        self.request.options.insert(name, value);
+        self
+    }
+
+    /// Sets the `RequestOptions` applied to the request.
+    pub fn request_options(mut self, request_options: RequestOptions) -> Self {
+        self.request_options = request_options;
+        self
+    }
+
+    /// Creates the voting.
+    ///
+    /// Returns a `Result` which is `Ok` containing the created `Voting` if successful,
+    /// or an `Err` with an `ApiError` if the choices are rejected or the request fails.
+    pub async fn send(self) -> Result<Voting, ApiError> {
+        self.client
+            .post_voting(self.request, &self.request_options)
+            .await
+    }
+}
+
+/// A builder for creating an instance of `Client`.
+///
+/// This builder allows for configuring optional parameters for `Client`,
+/// such as a custom API URL or a custom Reqwest client.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{Client, ClientBuilder};
+///
+/// let client = Client::builder("my-api-key".to_string())
+///     .api_url("https://custom-api.directdecisions.com".to_string())
+///     .try_build()
+///     .unwrap();
+/// ```
+pub struct ClientBuilder {
+    token: String,
+    token_provider: Option<Arc<dyn TokenProvider>>,
+    api_url: Option<String>,
+    api_version: ApiVersion,
+    reqwest_client: Option<reqwest::Client>,
+    transport: Option<Arc<dyn Transport>>,
+    retry_policy: Option<RetryPolicy>,
+    idempotency_keys: bool,
+    validate_ballots: bool,
+    strict_responses: bool,
+    throttle_threshold: Option<u32>,
+    pacer: Option<RatePacer>,
+    max_concurrent_requests: Option<usize>,
+    #[cfg(feature = "rate-limit")]
+    max_requests_per_second: Option<u32>,
+    hedge_delay: Option<Duration>,
+    cache: Option<(Duration, usize)>,
+    offline_fallback: bool,
+    timeout: Option<Duration>,
+    http: HttpConfig,
+    default_headers: HeaderMap,
+    middleware: Vec<Arc<dyn Middleware>>,
+    metrics: Option<Arc<dyn MetricsObserver>>,
+}
+
+/// Formats the configuration of the builder, with the API token redacted.
+impl fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let token = match self.token_provider {
+            Some(_) => "TokenProvider",
+            None => REDACTED,
+        };
+        f.debug_struct("ClientBuilder")
+            .field("token", &format_args!("{}", token))
+            .field("api_url", &self.api_url)
+            .field("api_version", &self.api_version)
+            .field("retry_policy", &self.retry_policy)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
 
 impl ClientBuilder {
     fn new(token: String) -> Self {
         ClientBuilder {
             token,
+            token_provider: None,
             api_url: None,
+            api_version: ApiVersion::default(),
             reqwest_client: None,
+            transport: None,
+            retry_policy: None,
+            idempotency_keys: false,
+            validate_ballots: false,
+            strict_responses: false,
+            throttle_threshold: None,
+            pacer: None,
+            max_concurrent_requests: None,
+            #[cfg(feature = "rate-limit")]
+            max_requests_per_second: None,
+            hedge_delay: None,
+            cache: None,
+            offline_fallback: false,
+            timeout: None,
+            http: HttpConfig::default(),
+            default_headers: HeaderMap::new(),
+            middleware: Vec::new(),
+            metrics: None,
         }
     }
 
@@ -450,6 +2530,31 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the version of the API used by the `Client`, `ApiVersion::V1` by default.
+    ///
+    /// The version is the path prefix of all endpoints, relative to the API URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The `ApiVersion` of the API.
+    pub fn api_version(mut self, version: ApiVersion) -> Self {
+        self.api_version = version;
+        self
+    }
+
+    /// Sets a `TokenProvider` that is consulted for the API token before every request.
+    ///
+    /// The provider replaces the token passed to `Client::builder`, which is then ignored
+    /// and may be empty. Use it when API keys are rotated, or fetched from a secrets manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - The `TokenProvider` returning the API token.
+    pub fn token_provider(mut self, provider: impl TokenProvider + 'static) -> Self {
+        self.token_provider = Some(Arc::new(provider));
+        self
+    }
+
     /// Sets a custom Reqwest client for the `Client`.
     ///
     /// If not set, a default Reqwest client is used.
@@ -462,6 +2567,428 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets a custom `Transport` that sends the requests instead of the Reqwest client,
+    /// for example a `MockTransport` in tests.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport` - The `Transport` used to send the requests.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Sets the total timeout of each request, from sending the request until the response
+    /// body is received.
+    ///
+    /// The timeout is applied to every attempt of a request, and takes precedence over the
+    /// timeout of a custom Reqwest client. If not set, requests do not time out unless the
+    /// custom Reqwest client has a timeout configured.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The maximum duration of a single request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for establishing a connection to the API.
+    ///
+    /// The connect timeout is applied to the internal Reqwest client and has no effect if a
+    /// custom Reqwest client is set with `reqwest_client`, configure it on that client instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The maximum duration of the connection phase.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Routes all requests through the proxy at the given URL.
+    ///
+    /// By default, the proxies configured with the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY`
+    /// environment variables are used. The proxy is applied to the internal Reqwest client
+    /// and has no effect if a custom Reqwest client is set with `reqwest_client`.
+    ///
+    /// An invalid proxy URL fails `try_build` with `BuildError::InvalidProxy`.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the proxy, such as `http://proxy.internal:3128`.
+    pub fn proxy(mut self, url: String) -> Self {
+        self.http.proxy = Some(url);
+        self
+    }
+
+    /// Sets the credentials sent to the proxy set with `proxy`, using basic authentication.
+    ///
+    /// # Arguments
+    ///
+    /// * `username` - The proxy username.
+    /// * `password` - The proxy password.
+    pub fn proxy_auth(mut self, username: String, password: String) -> Self {
+        self.http.proxy_auth = Some((username, password));
+        self
+    }
+
+    /// Disables the proxies configured with environment variables.
+    ///
+    /// A proxy set with `proxy` is still used.
+    pub fn no_proxy(mut self) -> Self {
+        self.http.no_proxy = true;
+        self
+    }
+
+    /// Sets the HTTP protocol versions used by the client.
+    ///
+    /// By default, HTTP/2 is used if the API offers it, which fails behind some proxies
+    /// that do not support HTTP/2. Use `HttpVersion::Http1Only` to disable HTTP/2 in that case.
+    /// The version is applied to the internal Reqwest client and has no effect if a custom
+    /// Reqwest client is set with `reqwest_client`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddclient_rs::{Client, HttpVersion};
+    ///
+    /// let client = Client::builder("my-api-key".to_string())
+    ///     .http_version(HttpVersion::Http1Only)
+    ///     .try_build()
+    ///     .unwrap();
+    /// ```
+    pub fn http_version(mut self, version: HttpVersion) -> Self {
+        self.http.http_version = version;
+        self
+    }
+
+    /// Enables or disables the adaptive flow control of HTTP/2 connections, which adjusts the
+    /// window sizes to the bandwidth of the connection.
+    ///
+    /// Like `http_version`, it has no effect if a custom Reqwest client is set.
+    pub fn http2_adaptive_window(mut self, enabled: bool) -> Self {
+        self.http.http2_adaptive_window = Some(enabled);
+        self
+    }
+
+    /// Sets the interval of the keep-alive pings sent on HTTP/2 connections, which detect
+    /// connections dropped by proxies or load balancers. By default, no pings are sent.
+    ///
+    /// Like `http_version`, it has no effect if a custom Reqwest client is set.
+    pub fn http2_keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.http.http2_keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Sets how long to wait for the response to a keep-alive ping before the connection is
+    /// closed. It has no effect unless pings are enabled with `http2_keep_alive_interval`.
+    pub fn http2_keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.http.http2_keep_alive_timeout = Some(timeout);
+        self
+    }
+
+    /// Adds a trusted root certificate, in addition to the system trust store.
+    ///
+    /// Use it when the API traffic is routed through an internal TLS-intercepting gateway.
+    /// The certificate is applied to the internal Reqwest client and has no effect if a
+    /// custom Reqwest client is set with `reqwest_client`.
+    ///
+    /// This method is available with the `native-tls` or `rustls` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `certificate` - The root certificate, for example from `reqwest::Certificate::from_pem`.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub fn add_root_certificate(mut self, certificate: reqwest::Certificate) -> Self {
+        self.http.root_certificates.push(certificate);
+        self
+    }
+
+    /// Sets the client certificate presented to the server, for mutual TLS.
+    ///
+    /// The identity is applied to the internal Reqwest client and has no effect if a custom
+    /// Reqwest client is set with `reqwest_client`.
+    ///
+    /// This method is available with the `native-tls` or `rustls` feature. The identity
+    /// must be created with a constructor supported by the enabled TLS backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `identity` - The client certificate and private key.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub fn identity(mut self, identity: reqwest::Identity) -> Self {
+        self.http.identity = Some(identity);
+        self
+    }
+
+    /// Adds a header that is sent with every request.
+    ///
+    /// Default headers replace the headers of the same name set by the client, and are
+    /// replaced by the headers set with `RequestOptions::header`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the header.
+    /// * `value` - The value of the header.
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.default_headers.append(name, value);
+        self
+    }
+
+    /// Enables automatic retries of requests that failed with a transient error.
+    ///
+    /// If not set, requests are not retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The `RetryPolicy` with the maximum number of attempts and backoff delays.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Enables automatic idempotency keys for mutating requests.
+    ///
+    /// When enabled, every `POST` and `DELETE` request gets a random `Idempotency-Key`
+    /// header, unless a key is set with `RequestOptions::idempotency_key`. The key is
+    /// generated once per call and reused by its retries, so a retried `create_voting` or
+    /// `vote` request is not applied twice.
+    ///
+    /// If not set, idempotency keys are only sent when set on the `RequestOptions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether idempotency keys are generated.
+    pub fn idempotency_keys(mut self, enabled: bool) -> Self {
+        self.idempotency_keys = enabled;
+        self
+    }
+
+    /// Enables client-side validation of ballots before they are sent.
+    ///
+    /// When enabled, `Client::vote` rejects empty ballots with `ApiError::Validation`,
+    /// without sending a request. Ballots ranking
+    /// choices that are not in the voting are rejected too, if the choices are provided
+    /// with `RequestOptions::known_choices`.
+    ///
+    /// If not set, ballots are validated only when the known choices are provided.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether ballots are validated.
+    pub fn validate_ballots(mut self, enabled: bool) -> Self {
+        self.validate_ballots = enabled;
+        self
+    }
+
+    /// Enables the strict deserialization of responses.
+    ///
+    /// When enabled, responses with fields that are not in the response models, or without
+    /// fields of the models, fail with `ClientError::UnexpectedResponse`, even if the
+    /// fields are optional. Use it to catch changes of the API contract, for example in
+    /// tests against a staging API.
+    ///
+    /// If not set, unknown fields are ignored and missing optional fields get their
+    /// defaults, so the client keeps working when the API adds fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether responses are deserialized strictly.
+    pub fn strict_responses(mut self, enabled: bool) -> Self {
+        self.strict_responses = enabled;
+        self
+    }
+
+    /// Enables proactive throttling based on the rate limit information.
+    ///
+    /// Before each request, the client inspects the last received rate limit. When the
+    /// number of remaining requests drops to `threshold` or below, requests are delayed
+    /// and spread evenly until the rate limit resets, and when no requests remain, they wait
+    /// for the reset. This keeps concurrent workloads under the limit instead of failing
+    /// with `ApiError::RateLimited`.
+    ///
+    /// If not set, requests are never delayed.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The number of remaining requests at which throttling starts.
+    pub fn throttle(mut self, threshold: u32) -> Self {
+        self.throttle_threshold = Some(threshold);
+        self
+    }
+
+    /// Paces the requests with a `RatePacer`, which is acquired before every request and
+    /// updated with the rate limit of every response.
+    ///
+    /// When no requests remain, requests wait for the rate limit to reset. Share clones of
+    /// the pacer between clients using the same API key to pace them together.
+    ///
+    /// # Arguments
+    ///
+    /// * `pacer` - The `RatePacer` of the client.
+    pub fn rate_pacer(mut self, pacer: RatePacer) -> Self {
+        self.pacer = Some(pacer);
+        self
+    }
+
+    /// Limits the number of requests in flight at the same time.
+    ///
+    /// Requests over the limit wait until an earlier request receives its response, in the
+    /// order they were made. The limit is shared by the clones of the client, so bulk jobs
+    /// sharing a client cannot open more connections to the API than the limit. A request
+    /// holds its slot during its retries, and releases it before its response body is read.
+    ///
+    /// If not set, the number of requests is not limited.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of requests in flight. A limit of `0` is treated as `1`.
+    pub fn max_concurrent_requests(mut self, limit: usize) -> Self {
+        self.max_concurrent_requests = Some(limit);
+        self
+    }
+
+    /// Limits the rate of requests sent by the client, independently of the rate limit
+    /// reported by the API.
+    ///
+    /// Up to `requests` requests are sent at once, and the following requests are delayed
+    /// and spaced evenly, so that no more than `requests` requests are sent per second on
+    /// average. The limit is shared by the clones of the client, and applies once to every
+    /// request, but not to its retries.
+    ///
+    /// If not set, requests are only delayed by `throttle`.
+    ///
+    /// # Arguments
+    ///
+    /// * `requests` - The maximum number of requests per second. A rate of `0` is treated
+    ///   as `1`.
+    #[cfg(feature = "rate-limit")]
+    pub fn max_requests_per_second(mut self, requests: u32) -> Self {
+        self.max_requests_per_second = Some(requests);
+        self
+    }
+
+    /// Enables hedging of the read requests, such as `Client::get_voting` and
+    /// `Client::get_voting_results`, to reduce their tail latency.
+    ///
+    /// When no response to a `GET` request is received within `delay`, a second copy of the
+    /// request is sent, and the response received first is used. Mutating requests are never
+    /// hedged. Hedging applies to every attempt of a request, including its retries, and
+    /// both copies count against the rate limit of the API.
+    ///
+    /// If not set, every request is sent once.
+    ///
+    /// # Arguments
+    ///
+    /// * `delay` - How long to wait for a response before sending the hedged request.
+    pub fn hedge(mut self, delay: Duration) -> Self {
+        self.hedge_delay = Some(delay);
+        self
+    }
+
+    /// Enables an in-memory cache of the votings, ballots and results retrieved with
+    /// `Client::get_voting`, `Client::get_ballot` and `Client::get_voting_results`.
+    ///
+    /// Cached responses are returned without sending a request until they expire. Every
+    /// request changing a voting, such as `Client::vote` or `Client::set_choice`, removes
+    /// the cached responses of the voting, and `Client::invalidate_cache` removes them
+    /// explicitly, for example when a voting is changed by another client. The cache is
+    /// shared by the clones of the client.
+    ///
+    /// If not set, responses are not cached.
+    ///
+    /// # Arguments
+    ///
+    /// * `ttl` - How long a response is cached.
+    /// * `capacity` - The maximum number of cached responses. When the cache is full, the
+    ///   oldest response is removed.
+    pub fn cache(mut self, ttl: Duration, capacity: usize) -> Self {
+        self.cache = Some((ttl, capacity));
+        self
+    }
+
+    /// Enables the offline fallback of `Client::get_voting_or_stale` and
+    /// `Client::get_voting_results_or_stale`.
+    ///
+    /// The cache keeps the votings and results after they expire, until they are evicted
+    /// by newer responses, and the `*_or_stale` methods return them as
+    /// `MaybeStale::Stale` when the API is unreachable. If the cache is not enabled with
+    /// `ClientBuilder::cache`, the last 1024 responses are kept for the offline fallback
+    /// only, and every read is sent to the API.
+    ///
+    /// If not set, the `*_or_stale` methods return the error when the API is unreachable.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether stale responses are served when the API is unreachable.
+    pub fn offline_fallback(mut self, enabled: bool) -> Self {
+        self.offline_fallback = enabled;
+        self
+    }
+
+    /// Registers a `Middleware` that can mutate every outgoing request and observe every
+    /// received response.
+    ///
+    /// Middleware runs in the order of registration.
+    ///
+    /// # Arguments
+    ///
+    /// * `middleware` - The `Middleware` to register.
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Registers a closure that is called with every request before it is sent.
+    ///
+    /// This is a shorthand for registering a `Middleware` that only implements
+    /// `Middleware::on_request`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddclient_rs::Client;
+    /// use reqwest::header::HeaderValue;
+    ///
+    /// let client = Client::builder("my-api-key".to_string())
+    ///     .on_request(|request| {
+    ///         request
+    ///             .headers_mut()
+    ///             .insert("X-Tenant-Id", HeaderValue::from_static("tenant-1"));
+    ///     })
+    ///     .try_build()
+    ///     .unwrap();
+    /// ```
+    pub fn on_request<F>(self, f: F) -> Self
+    where
+        F: Fn(&mut Request) + Send + Sync + 'static,
+    {
+        self.middleware(OnRequest(f))
+    }
+
+    /// Registers a closure that is called with every received response.
+    ///
+    /// This is a shorthand for registering a `Middleware` that only implements
+    /// `Middleware::on_response`.
+    pub fn on_response<F>(self, f: F) -> Self
+    where
+        F: Fn(&Response) + Send + Sync + 'static,
+    {
+        self.middleware(OnResponse(f))
+    }
+
+    /// Registers a `MetricsObserver` that is notified when requests are started, completed,
+    /// retried or rate limited.
+    ///
+    /// # Arguments
+    ///
+    /// * `observer` - The `MetricsObserver` to notify.
+    pub fn metrics_observer(mut self, observer: impl MetricsObserver + 'static) -> Self {
+        self.metrics = Some(Arc::new(observer));
+        self
+    }
+
     /// Builds and returns a new `Client` instance.
     ///
     /// This method consumes the builder, applies URL validation and formatting,
@@ -470,7 +2997,8 @@ impl ClientBuilder {
     ///
     /// # Panics
     ///
-    /// Panics if the provided API URL is invalid.
+    /// Panics if the provided API URL or token is invalid, or if the internal Reqwest client
+    /// cannot be created.
     ///
     /// # Returns
     ///
@@ -481,30 +3009,155 @@ impl ClientBuilder {
     /// ```
     /// use ddclient_rs::Client;
     ///
+    /// #[allow(deprecated)]
     /// let client = Client::builder("my-api-key".to_string())
     ///     .api_url("https://custom-api.directdecisions.com".to_string())
     ///     .build();
     /// ```
+    #[deprecated(
+        since = "0.1.4",
+        note = "use `try_build`, which returns an error instead of panicking"
+    )]
     pub fn build(self) -> Client {
-        let mut api_url = match self.api_url {
-            Some(url) => {
-                let _ = reqwest::Url::parse(&url).expect("Invalid API URL");
-                url
-            }
-            None => DEFAULT_BASE_URL.to_string(),
+        self.try_build().expect("Invalid client configuration")
+    }
+
+    /// Builds and returns a new `Client` instance.
+    ///
+    /// This method consumes the builder, applies URL validation and formatting,
+    /// and uses the provided configurations to create a `Client`.
+    /// If certain configurations are not provided, default values are used.
+    ///
+    /// Returns a `Result` which is `Ok` containing the `Client` if the configuration is valid,
+    /// or an `Err` with a `BuildError` if the API URL or token is invalid, or if the internal
+    /// Reqwest client cannot be created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddclient_rs::{BuildError, Client};
+    ///
+    /// let client = Client::builder("my-api-key".to_string())
+    ///     .api_url("https://custom-api.directdecisions.com".to_string())
+    ///     .try_build()
+    ///     .unwrap();
+    ///
+    /// let result = Client::builder("my-api-key".to_string())
+    ///     .api_url("not a url".to_string())
+    ///     .try_build();
+    /// assert!(matches!(result, Err(BuildError::InvalidUrl(_))));
+    /// ```
+    pub fn try_build(self) -> Result<Client, BuildError> {
+        let api_url = validate_api_url(self.api_url)?;
+        let token = match self.token_provider {
+            Some(provider) => Token::Provider(provider),
+            None => Token::Static(validate_token(self.token)?),
         };
 
-        if !api_url.ends_with('/') {
-            api_url.push('/');
-        }
+        let client = match self.reqwest_client {
+            Some(client) => client,
+            None => self.http.build()?,
+        };
+        let transport = self
+            .transport
+            .unwrap_or_else(|| Arc::new(client.clone()) as Arc<dyn Transport>);
+        let cache = match (self.cache, self.offline_fallback) {
+            (Some((ttl, capacity)), keep_stale) => Some(Cache::new(ttl, capacity, keep_stale)),
+            (None, true) => Some(Cache::new(Duration::ZERO, DEFAULT_OFFLINE_CAPACITY, true)),
+            (None, false) => None,
+        };
+
+        Ok(Client {
+            inner: Arc::new(ClientInner {
+                token,
+                client,
+                transport,
+                api_url,
+                api_version: self.api_version,
+                rate: Mutex::new(None),
+                endpoint_rates: EndpointRates::default(),
+                rate_updates: watch::Sender::new(None),
+                last_request_id: Mutex::new(None),
+                last_error_context: LastErrorContext::default(),
+                retry_policy: self.retry_policy,
+                idempotency_keys: self.idempotency_keys,
+                validate_ballots: self.validate_ballots,
+                strict_responses: self.strict_responses,
+                throttle: self.throttle_threshold.map(Throttle::new),
+                pacer: self.pacer,
+                limiter: self.max_concurrent_requests.map(Limiter::new),
+                #[cfg(feature = "rate-limit")]
+                rate_limiter: self.max_requests_per_second.map(RateLimiter::new),
+                hedge_delay: self.hedge_delay,
+                cache,
+                timeout: self.timeout,
+                default_headers: self.default_headers,
+                middleware: self.middleware,
+                metrics: self.metrics,
+            }),
+        })
+    }
+}
 
-        let client = self.reqwest_client.unwrap_or_default();
+/// Reports whether the result of a request attempt is a transient error worth retrying.
+fn is_transient_result(result: &Result<Response, ClientError>, replayable: bool) -> bool {
+    match result {
+        Ok(response) => is_transient(Ok(response.status()), replayable),
+        Err(ClientError::HttpRequestError(err)) => is_transient(Err(err), replayable),
+        Err(_) => false,
+    }
+}
+
+/// Records the outcome of a request on the current `request` span.
+#[cfg(feature = "tracing")]
+fn record_response(response: &Result<Response, ApiError>, latency: Duration, rate: Option<Rate>) {
+    let span = tracing::Span::current();
+    span.record("latency_ms", latency.as_millis() as u64);
+    if let Some(rate) = rate {
+        span.record("rate_remaining", rate.remaining);
+    }
+    match response {
+        Ok(response) => {
+            span.record("status", response.status().as_u16());
+        }
+        Err(err) => tracing::warn!(error = %err, "request failed"),
+    }
+}
 
-        Client {
-            token: self.token,
-            client,
-            api_url,
-            rate: Arc::new(Mutex::new(None)),
+/// Validates the API URL of a client configuration.
+///
+/// Returns the API URL, or the default one if not set, with a trailing slash.
+pub(crate) fn validate_api_url(api_url: Option<String>) -> Result<String, BuildError> {
+    let mut api_url = match api_url {
+        Some(url) => {
+            reqwest::Url::parse(&url)
+                .map_err(|err| BuildError::InvalidUrl(format!("{}: {}", url, err)))?;
+            url
         }
+        None => DEFAULT_BASE_URL.to_string(),
+    };
+
+    if !api_url.ends_with('/') {
+        api_url.push('/');
+    }
+
+    Ok(api_url)
+}
+
+/// Validates that the API token can be sent in the `Authorization` header, and returns the
+/// header value, which is reused by every request.
+pub(crate) fn validate_token(token: String) -> Result<HeaderValue, BuildError> {
+    if token.is_empty() {
+        return Err(BuildError::InvalidToken);
     }
+
+    authorization(&token).ok_or(BuildError::InvalidToken)
+}
+
+/// Creates the sensitive `Authorization` header value of the API token, or returns `None` if
+/// the token contains characters which are not allowed in a header.
+fn authorization(token: &str) -> Option<HeaderValue> {
+    let mut value = HeaderValue::from_str(&format!("Bearer {}", token)).ok()?;
+    value.set_sensitive(true);
+    Some(value)
 }
@@ -0,0 +1,172 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::{ApiError, BadRequestError};
+use miette::{Diagnostic, LabeledSpan, NamedSource, SourceCode};
+use std::fmt;
+
+/// An `ApiError` paired with the request body that caused it.
+///
+/// When rendered with a `miette` report handler, the request body is shown as a source snippet
+/// with the offending fields highlighted, in addition to the error code and help text of the
+/// wrapped error.
+///
+/// This type is available with the `diagnostics` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddclient_rs::{Client, RequestDiagnostic};
+///
+/// #[tokio::main]
+/// async fn main() -> miette::Result<()> {
+///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+///     let choices = vec!["Einstein".to_string(), "Newton".to_string()];
+///
+///     if let Err(err) = client.create_voting(choices.clone()).await {
+///         let body = serde_json::json!({ "choices": choices }).to_string();
+///         return Err(RequestDiagnostic::new(err, body).into());
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RequestDiagnostic {
+    error: ApiError,
+    body: NamedSource<String>,
+    labels: Vec<LabeledSpan>,
+}
+
+impl RequestDiagnostic {
+    /// Wraps the error together with the JSON request body that was sent to the API.
+    pub fn new(error: ApiError, body: impl Into<String>) -> Self {
+        let body = body.into();
+        let labels = labels_for(&error, &body);
+
+        Self {
+            error,
+            body: NamedSource::new("request body", body).with_language("json"),
+            labels,
+        }
+    }
+
+    /// Returns the wrapped error.
+    pub fn error(&self) -> &ApiError {
+        &self.error
+    }
+
+    /// Consumes the diagnostic and returns the wrapped error.
+    pub fn into_error(self) -> ApiError {
+        self.error
+    }
+}
+
+impl fmt::Display for RequestDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for RequestDiagnostic {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+impl Diagnostic for RequestDiagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.error.code()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.error.help()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.error.related()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(&self.body)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        if self.labels.is_empty() {
+            return None;
+        }
+        Some(Box::new(self.labels.iter().cloned()))
+    }
+}
+
+fn labels_for(error: &ApiError, body: &str) -> Vec<LabeledSpan> {
+    let ApiError::BadRequest(errors) = error else {
+        return Vec::new();
+    };
+
+    errors
+        .iter()
+        .filter_map(|err| {
+            let field = match err {
+                BadRequestError::MissingChoices
+                | BadRequestError::ChoiceTooLong
+                | BadRequestError::TooManyChoices => "choices",
+                BadRequestError::ChoiceRequired => "choice",
+                BadRequestError::InvalidData | BadRequestError::BallotRequired => "ballot",
+                BadRequestError::VoterIDTooLong
+                | BadRequestError::InvalidVoterID
+                | BadRequestError::Unknown(_) => return None,
+            };
+            let key = format!("\"{}\"", field);
+            let offset = body.find(&key)?;
+            Some(LabeledSpan::at(offset..offset + key.len(), err.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_diagnostic_test() {
+        let body = r#"{"choices":["Einstein","Newton"]}"#;
+        let diagnostic = RequestDiagnostic::new(
+            ApiError::BadRequest(vec![BadRequestError::TooManyChoices]),
+            body,
+        );
+
+        assert_eq!(
+            diagnostic.code().unwrap().to_string(),
+            "ddclient::bad_request"
+        );
+        assert!(diagnostic.source_code().is_some());
+
+        let labels: Vec<LabeledSpan> = diagnostic.labels().unwrap().collect();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].offset(), 1);
+        assert_eq!(labels[0].len(), "\"choices\"".len());
+        assert_eq!(labels[0].label(), Some("Too many choices"));
+
+        let related: Vec<String> = diagnostic
+            .related()
+            .unwrap()
+            .map(|d| d.code().unwrap().to_string())
+            .collect();
+        assert_eq!(related, vec!["ddclient::too_many_choices"]);
+    }
+
+    #[test]
+    fn request_diagnostic_without_labels_test() {
+        let diagnostic = RequestDiagnostic::new(ApiError::NotFound, "{}");
+
+        assert_eq!(
+            diagnostic.code().unwrap().to_string(),
+            "ddclient::not_found"
+        );
+        assert!(diagnostic.labels().is_none());
+        assert_eq!(diagnostic.to_string(), "Not Found");
+    }
+}
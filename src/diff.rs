@@ -0,0 +1,197 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::schulze::ranking;
+use crate::{Percentage, VotingResults};
+
+/// Describes what changed between two snapshots of the results of a voting.
+///
+/// Created with `diff`. Choices are matched by their value, so the diff remains correct
+/// when the choices of the voting are reordered between the snapshots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultsDiff {
+    /// The changes of the choices in the newer results, in their order in the newer results.
+    pub changes: Vec<ChoiceChange>,
+    /// The choices which are only in the older results.
+    pub removed: Vec<String>,
+    /// The groups of tied choices in the newer results, which were not tied in the same way
+    /// in the older results.
+    pub new_ties: Vec<Vec<String>>,
+}
+
+impl ResultsDiff {
+    /// Reports whether the ranking, the percentages or the ties changed.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+            && self.new_ties.is_empty()
+            && self.changes.iter().all(|change| !change.is_changed())
+    }
+
+    /// Returns the changes of the choices whose position or percentage changed.
+    pub fn changed(&self) -> impl Iterator<Item = &ChoiceChange> {
+        self.changes.iter().filter(|change| change.is_changed())
+    }
+}
+
+/// Describes the change of a single choice between two snapshots of voting results.
+///
+/// Positions start from 1 for the winners, and tied choices share a position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChoiceChange {
+    /// The choice value.
+    pub choice: String,
+    /// The position of the choice in the older results, or `None` if the choice is new.
+    pub old_position: Option<usize>,
+    /// The position of the choice in the newer results.
+    pub new_position: usize,
+    /// The difference between the newer and the older percentage of the choice. New choices
+    /// are compared to zero.
+    pub percentage_delta: Percentage,
+}
+
+impl ChoiceChange {
+    /// Returns by how many positions the choice moved up in the ranking, which is negative
+    /// if it moved down, or `None` if the choice is new.
+    pub fn position_change(&self) -> Option<isize> {
+        self.old_position
+            .map(|old| old as isize - self.new_position as isize)
+    }
+
+    /// Reports whether the choice is new, or its position or percentage changed.
+    pub fn is_changed(&self) -> bool {
+        self.position_change() != Some(0) || self.percentage_delta != Percentage::default()
+    }
+}
+
+/// Compares two snapshots of the results of a voting, such as the results before and after
+/// a new ballot.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{diff, schulze, Rank};
+/// use std::collections::HashMap;
+///
+/// let choices = vec!["Einstein".to_string(), "Newton".to_string()];
+/// let mut ballots = vec![HashMap::from([("Einstein".to_string(), Rank::HIGHEST)])];
+/// let before = schulze::compute(&choices, &ballots);
+///
+/// ballots.push(HashMap::from([("Newton".to_string(), Rank::HIGHEST)]));
+/// let after = schulze::compute(&choices, &ballots);
+///
+/// let changes = diff(&before, &after);
+/// assert_eq!(changes.new_ties, vec![vec!["Einstein", "Newton"]]);
+/// assert_eq!(changes.changes[1].position_change(), Some(1));
+/// ```
+pub fn diff(old: &VotingResults, new: &VotingResults) -> ResultsDiff {
+    let old_ranking = ranking(old);
+    let new_ranking = ranking(new);
+
+    let position = |ranking: &[Vec<&str>], choice: &str| {
+        ranking
+            .iter()
+            .position(|group| group.contains(&choice))
+            .map(|position| position + 1)
+    };
+
+    let changes = new
+        .results
+        .iter()
+        .map(|result| {
+            let old_result = old.results.iter().find(|r| r.choice == result.choice);
+            ChoiceChange {
+                choice: result.choice.clone(),
+                old_position: position(&old_ranking, &result.choice),
+                new_position: position(&new_ranking, &result.choice).unwrap_or_default(),
+                percentage_delta: result.percentage
+                    - old_result.map_or(Percentage::default(), |r| r.percentage),
+            }
+        })
+        .collect();
+
+    let removed = old
+        .results
+        .iter()
+        .filter(|result| new.results.iter().all(|r| r.choice != result.choice))
+        .map(|result| result.choice.clone())
+        .collect();
+
+    let new_ties = new_ranking
+        .iter()
+        .filter(|group| group.len() > 1 && !old_ranking.iter().any(|old| same_group(old, group)))
+        .map(|group| group.iter().map(|choice| choice.to_string()).collect())
+        .collect();
+
+    ResultsDiff {
+        changes,
+        removed,
+        new_ties,
+    }
+}
+
+fn same_group(a: &[&str], b: &[&str]) -> bool {
+    a.len() == b.len() && a.iter().all(|choice| b.contains(choice))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schulze::compute;
+    use crate::Rank;
+    use std::collections::HashMap;
+
+    fn ballot(choices: &[&str]) -> HashMap<String, Rank> {
+        choices
+            .iter()
+            .enumerate()
+            .map(|(i, choice)| (choice.to_string(), Rank::new(i as u32 + 1).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn diff_test() {
+        let choices: Vec<String> = ["A", "B", "C"].iter().map(|c| c.to_string()).collect();
+        let mut ballots = vec![ballot(&["A", "B", "C"]), ballot(&["A", "C", "B"])];
+        let before = compute(&choices, &ballots);
+
+        assert!(diff(&before, &before).is_empty());
+
+        ballots.push(ballot(&["B", "C", "A"]));
+        ballots.push(ballot(&["B", "C", "A"]));
+        let after = compute(&choices, &ballots);
+
+        let changes = diff(&before, &after);
+        assert!(!changes.is_empty());
+        // B now beats C, while A no longer beats B and C
+        assert_eq!(changes.new_ties, vec![vec!["A", "C"]]);
+        assert!(changes.removed.is_empty());
+
+        let b = changes.changes.iter().find(|c| c.choice == "B").unwrap();
+        assert_eq!(b.old_position, Some(2));
+        assert_eq!(b.new_position, 1);
+        assert_eq!(b.position_change(), Some(1));
+        assert_eq!(b.percentage_delta, Percentage::from(50u8));
+
+        let a = changes.changes.iter().find(|c| c.choice == "A").unwrap();
+        assert_eq!(a.position_change(), Some(-1));
+        assert_eq!(changes.changed().count(), 2);
+    }
+
+    #[test]
+    fn diff_choices_test() {
+        let old_choices: Vec<String> = ["A", "B"].iter().map(|c| c.to_string()).collect();
+        let new_choices: Vec<String> = ["C", "B"].iter().map(|c| c.to_string()).collect();
+
+        let before = compute(&old_choices, &[ballot(&["A"])]);
+        let after = compute(&new_choices, &[]);
+
+        let changes = diff(&before, &after);
+        assert_eq!(changes.removed, vec!["A"]);
+        assert_eq!(changes.new_ties, vec![vec!["C", "B"]]);
+        let c = changes.changes.iter().find(|c| c.choice == "C").unwrap();
+        assert_eq!(c.old_position, None);
+        assert!(c.is_changed());
+    }
+}
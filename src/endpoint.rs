@@ -0,0 +1,126 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::ApiVersion;
+#[cfg(feature = "experimental")]
+use std::fmt::Write;
+
+const VOTINGS: &str = "votings";
+
+/// The initial capacity of the path buffer, enough for the paths of the votings and
+/// ballots endpoints with typical IDs, so they are built without reallocations.
+const CAPACITY: usize = 96;
+
+/// Builds the path of an API endpoint, relative to the API URL, in a single buffer.
+///
+/// Path parameters added with `Endpoint::param` are percent-encoded as path segments and
+/// query values added with `Endpoint::query` as URL components, while the fixed segments
+/// added with `Endpoint::segment` are added as they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Endpoint {
+    path: String,
+    has_query: bool,
+}
+
+impl Endpoint {
+    /// Returns the path of the votings collection, `{version}/votings`.
+    pub(crate) fn votings(version: ApiVersion) -> Self {
+        let mut path = String::with_capacity(CAPACITY);
+        path.push_str(version.as_str());
+        path.push('/');
+        path.push_str(VOTINGS);
+        Self {
+            path,
+            has_query: false,
+        }
+    }
+
+    /// Returns the path of a voting, `{version}/votings/{voting_id}`.
+    pub(crate) fn voting(version: ApiVersion, voting_id: &str) -> Self {
+        Self::votings(version).param(voting_id)
+    }
+
+    /// Returns the path of the ballot of a voter,
+    /// `{version}/votings/{voting_id}/ballots/{voter_id}`.
+    pub(crate) fn ballot(version: ApiVersion, voting_id: &str, voter_id: &str) -> Self {
+        Self::voting(version, voting_id)
+            .segment("ballots")
+            .param(voter_id)
+    }
+
+    /// Appends a fixed path segment, which is not escaped.
+    pub(crate) fn segment(mut self, segment: &str) -> Self {
+        debug_assert!(!self.has_query);
+        self.path.push('/');
+        self.path.push_str(segment);
+        self
+    }
+
+    /// Appends a path parameter as an escaped path segment.
+    pub(crate) fn param(mut self, value: &str) -> Self {
+        debug_assert!(!self.has_query);
+        self.path.push('/');
+        url_escape::encode_path_to_string(value, &mut self.path);
+        self
+    }
+
+    /// Appends an escaped query parameter.
+    #[cfg(feature = "experimental")]
+    pub(crate) fn query(mut self, name: &str, value: &str) -> Self {
+        self.push_query_name(name);
+        url_escape::encode_component_to_string(value, &mut self.path);
+        self
+    }
+
+    /// Appends a numeric query parameter.
+    #[cfg(feature = "experimental")]
+    pub(crate) fn query_number(mut self, name: &str, value: u32) -> Self {
+        self.push_query_name(name);
+        let _ = write!(self.path, "{}", value);
+        self
+    }
+
+    /// Returns the path.
+    pub(crate) fn as_str(&self) -> &str {
+        &self.path
+    }
+
+    #[cfg(feature = "experimental")]
+    fn push_query_name(&mut self, name: &str) {
+        self.path.push(if self.has_query { '&' } else { '?' });
+        self.has_query = true;
+        self.path.push_str(name);
+        self.path.push('=');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoint_test() {
+        assert_eq!(Endpoint::votings(ApiVersion::V1).as_str(), "v1/votings");
+        assert_eq!(
+            Endpoint::voting(ApiVersion::V1, "a b#c")
+                .segment("results/duels")
+                .as_str(),
+            "v1/votings/a%20b%23c/results/duels"
+        );
+        assert_eq!(
+            Endpoint::ballot(ApiVersion::V1, "40f80454800b2bd7c172", "einstein?").as_str(),
+            "v1/votings/40f80454800b2bd7c172/ballots/einstein%3F"
+        );
+        #[cfg(feature = "experimental")]
+        assert_eq!(
+            Endpoint::voting(ApiVersion::V1, "id")
+                .segment("ballots")
+                .query_number("limit", 10)
+                .query("cursor", "a&b=c")
+                .as_str(),
+            "v1/votings/id/ballots?limit=10&cursor=a%26b%3Dc"
+        );
+    }
+}
@@ -3,7 +3,12 @@
 // Use of this source code is governed by a BSD-style
 // license that can be found in the LICENSE file.
 
-use serde::Deserialize;
+use crate::retry::is_transient;
+use crate::{ApiVersion, Rate, ValidationError};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use thiserror::Error;
 
 /// Represents an error returned by the API.
@@ -13,34 +18,390 @@ use thiserror::Error;
 ///
 /// Client errors represent errors that occur on the client side.
 ///
+/// The method, path and status of the failed request, and the request ID sent by the API
+/// in the `X-Request-Id` header, are recorded on the client that sent the request and
+/// returned by `Client::last_error_context`.
+///
+/// New variants may be added in minor releases, so matches on `ApiError` need a wildcard
+/// arm. The `is_retryable`, `is_client_error`, `is_rate_limited` and `is_not_found`
+/// methods classify errors without matching on the variants.
+///
 #[derive(Debug, Error)]
+#[cfg_attr(feature = "diagnostics", derive(miette::Diagnostic))]
+#[non_exhaustive]
 pub enum ApiError {
     #[error("Bad Request: {0:?}")]
-    BadRequest(Vec<BadRequestError>),
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::bad_request),
+            help("the API rejected the request payload, see the related errors for details")
+        )
+    )]
+    BadRequest(#[cfg_attr(feature = "diagnostics", related)] Vec<BadRequestError>),
 
     #[error("Unauthorized")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::unauthorized),
+            help("check that the API key passed to the client is valid")
+        )
+    )]
     Unauthorized,
 
     #[error("Not Found")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::not_found),
+            help("the voting or ballot does not exist or has been deleted")
+        )
+    )]
     NotFound,
 
     #[error("Forbidden")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::forbidden),
+            help("the API key is not allowed to access this resource")
+        )
+    )]
     Forbidden,
 
     #[error("Internal Server Error: {0}")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::internal_server_error),
+            help("the API failed to process the request, try again later")
+        )
+    )]
     InternalServerError(String),
 
     #[error("Method Not Allowed")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::method_not_allowed),
+            help("the endpoint does not support this HTTP method, check the API URL")
+        )
+    )]
     MethodNotAllowed,
 
+    /// The rate limit is exhausted. The `rate` holds the rate limit information of the
+    /// rejected request, and `retry_after` the duration from the `Retry-After` header to
+    /// wait before sending the request again.
     #[error("Too many requests")]
-    TooManyRequests,
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::rate_limited),
+            help("the rate limit is exhausted, retry after the duration in retry_after")
+        )
+    )]
+    RateLimited {
+        rate: Option<Rate>,
+        retry_after: Option<Duration>,
+    },
+
+    /// The request was cancelled with the cancellation token of its `RequestOptions`.
+    #[error("Request cancelled")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::cancelled),
+            help("the request was cancelled before a response was received")
+        )
+    )]
+    Cancelled,
+
+    /// The deadline set in the `RequestOptions` of the request passed before a response
+    /// was received.
+    #[error("Deadline exceeded")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::deadline_exceeded),
+            help("the request did not complete before its deadline, try a later deadline")
+        )
+    )]
+    DeadlineExceeded,
 
     #[error("Other Error: {0}")]
+    #[cfg_attr(feature = "diagnostics", diagnostic(code(ddclient::other)))]
     Other(String),
 
     #[error("Client Error: {0}")]
+    #[cfg_attr(feature = "diagnostics", diagnostic(transparent))]
     Client(#[from] ClientError),
+
+    #[error("Validation Error: {0}")]
+    #[cfg_attr(feature = "diagnostics", diagnostic(transparent))]
+    Validation(#[from] ValidationError),
+}
+
+impl ApiError {
+    /// Reports whether the request may succeed if it is sent again later.
+    ///
+    /// Rate limited requests, unavailable or unreachable API gateways, and connection
+    /// errors and timeouts are retryable. These are the errors retried by a `RetryPolicy`,
+    /// with the addition of `ApiError::RateLimited`, which should be retried only after
+    /// the rate limit resets. A `RetryPolicy` retries timeouts and bad gateway responses only
+    /// for idempotent requests, and so should callers, as a `POST` request that failed with
+    /// them may have been applied by the API.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ApiError::RateLimited { .. } => true,
+            ApiError::Client(ClientError::BadGateway | ClientError::ServiceUnavailable) => true,
+            ApiError::Client(ClientError::HttpRequestError(err)) => is_transient(Err(err), true),
+            _ => false,
+        }
+    }
+
+    /// Reports whether the API rejected the request itself, with a `4xx` status other than
+    /// `429 Too Many Requests`, or the request was rejected by the client-side validation.
+    /// Sending the same request again fails with the same error.
+    ///
+    /// `ApiError::Other` errors are not classified, as their status is only available in
+    /// the `ErrorContext` returned by `Client::last_error_context`.
+    pub fn is_client_error(&self) -> bool {
+        matches!(
+            self,
+            ApiError::Validation(_)
+                | ApiError::BadRequest(_)
+                | ApiError::Unauthorized
+                | ApiError::NotFound
+                | ApiError::Forbidden
+                | ApiError::MethodNotAllowed
+        )
+    }
+
+    /// Reports whether the request was rejected because the rate limit is exhausted.
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, ApiError::RateLimited { .. })
+    }
+
+    /// Reports whether the request was interrupted by its cancellation token or its
+    /// deadline, see `RequestOptions::cancellation_token` and `RequestOptions::deadline`.
+    pub fn is_interrupted(&self) -> bool {
+        matches!(self, ApiError::Cancelled | ApiError::DeadlineExceeded)
+    }
+
+    /// Reports whether the requested voting or ballot does not exist.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, ApiError::NotFound)
+    }
+
+    /// Returns the HTTP status code of the response that caused the error, or `None` if no
+    /// response was received, or the error does not identify the status, as for
+    /// `ApiError::Other`.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ApiError::BadRequest(_) => Some(StatusCode::BAD_REQUEST),
+            ApiError::Unauthorized => Some(StatusCode::UNAUTHORIZED),
+            ApiError::NotFound => Some(StatusCode::NOT_FOUND),
+            ApiError::Forbidden => Some(StatusCode::FORBIDDEN),
+            ApiError::InternalServerError(_) => Some(StatusCode::INTERNAL_SERVER_ERROR),
+            ApiError::MethodNotAllowed => Some(StatusCode::METHOD_NOT_ALLOWED),
+            ApiError::RateLimited { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
+            ApiError::Client(ClientError::BadGateway) => Some(StatusCode::BAD_GATEWAY),
+            ApiError::Client(ClientError::ServiceUnavailable) => {
+                Some(StatusCode::SERVICE_UNAVAILABLE)
+            }
+            ApiError::Client(ClientError::HttpRequestError(err)) => err.status(),
+            _ => None,
+        }
+    }
+
+    /// Returns the validation errors of a `ApiError::BadRequest`, or an empty slice for
+    /// other errors.
+    pub fn bad_request_errors(&self) -> &[BadRequestError] {
+        match self {
+            ApiError::BadRequest(errors) => errors,
+            _ => &[],
+        }
+    }
+
+    /// Returns the duration to wait before retrying a `ApiError::RateLimited` request, if
+    /// the API sent it.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ApiError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Returns the snake case name of the variant, used as the `kind` of the serialized
+    /// error.
+    fn kind(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Unauthorized => "unauthorized",
+            ApiError::NotFound => "not_found",
+            ApiError::Forbidden => "forbidden",
+            ApiError::InternalServerError(_) => "internal_server_error",
+            ApiError::MethodNotAllowed => "method_not_allowed",
+            ApiError::RateLimited { .. } => "rate_limited",
+            ApiError::Cancelled => "cancelled",
+            ApiError::DeadlineExceeded => "deadline_exceeded",
+            ApiError::Other(_) => "other",
+            ApiError::Client(_) => "client",
+            ApiError::Validation(_) => "validation",
+        }
+    }
+}
+
+/// Serializes the error as a structured object, so it can be returned in JSON responses or
+/// structured logs without losing its details.
+///
+/// The object has the snake case `kind` of the variant, such as `bad_request` or
+/// `rate_limited`, the HTTP `status` if known, the `message` of the error, and the
+/// `details` of the variant: the `errors` of a bad request, the `body` of a server error,
+/// and the `rate` and `retry_after` seconds of a rate limited request.
+///
+/// ```
+/// use ddclient_rs::{ApiError, BadRequestError};
+///
+/// let err = ApiError::BadRequest(vec![BadRequestError::MissingChoices]);
+/// assert_eq!(
+///     serde_json::to_string(&err).unwrap(),
+///     r#"{"kind":"bad_request","status":400,"message":"Bad Request: [MissingChoices]","details":{"errors":["MissingChoices"]}}"#
+/// );
+/// ```
+impl Serialize for ApiError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut details = ErrorDetails::default();
+        match self {
+            ApiError::BadRequest(errors) => details.errors = errors,
+            ApiError::InternalServerError(body) | ApiError::Other(body) => {
+                details.body = Some(body);
+            }
+            ApiError::RateLimited { rate, retry_after } => {
+                details.rate = rate.as_ref();
+                details.retry_after = retry_after.map(|retry_after| retry_after.as_secs());
+            }
+            _ => {}
+        }
+
+        SerializedError {
+            kind: self.kind(),
+            status: self.status().map(|status| status.as_u16()),
+            message: self.to_string(),
+            details,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(Serialize)]
+struct SerializedError<'a> {
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    message: String,
+    details: ErrorDetails<'a>,
+}
+
+#[derive(Serialize, Default)]
+struct ErrorDetails<'a> {
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    errors: &'a [BadRequestError],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rate: Option<&'a Rate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_after: Option<u64>,
+}
+
+/// A validation error of a single payload field, reported by the API with a bad request
+/// response.
+///
+/// The `field` is the path of the rejected field in the request payload, for example
+/// `choices[1]` or `ballot.Kant`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// The path of the rejected field in the request payload.
+    pub field: String,
+    /// The machine readable error code, such as `choice_too_long`.
+    #[serde(default)]
+    pub code: String,
+    /// The human readable error message.
+    #[serde(default)]
+    pub message: String,
+}
+
+/// Describes the last request of a client that failed with an `ApiError`, returned by
+/// `Client::last_error_context`.
+///
+/// The fields are `None` if the information is not available, for example the status of
+/// a request that failed before a response was received.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// The HTTP method of the request.
+    pub method: Option<Method>,
+    /// The path of the request, relative to the API URL.
+    pub path: Option<String>,
+    /// The status code of the response.
+    pub status: Option<StatusCode>,
+    /// The ID of the voting the request refers to.
+    pub voting_id: Option<String>,
+    /// The ID of the voter the request refers to.
+    pub voter_id: Option<String>,
+    /// The request ID sent by the API.
+    pub request_id: Option<String>,
+    /// The payload fields rejected with a bad request response, when the API reports them.
+    pub fields: Vec<FieldError>,
+}
+
+impl ErrorContext {
+    /// Creates the context of a request with the given method and path, extracting the
+    /// voting and voter IDs from the path.
+    pub(crate) fn new(method: Method, path: &str) -> Self {
+        let path = path.split('?').next().unwrap_or_default();
+        let segments: Vec<&str> = path.split('/').collect();
+        let decode = |segment: &str| url_escape::decode(segment).into_owned();
+
+        let segments = match segments.split_first() {
+            Some((version, rest)) if ApiVersion::from_prefix(version).is_some() => rest,
+            _ => &[],
+        };
+
+        let voting_id = match segments {
+            ["votings", id, ..] => Some(decode(id)),
+            _ => None,
+        };
+        let voter_id = match segments {
+            ["votings", _, "ballots", voter_id] => Some(decode(voter_id)),
+            _ => None,
+        };
+
+        Self {
+            method: Some(method),
+            path: Some(path.to_string()),
+            voting_id,
+            voter_id,
+            ..Self::default()
+        }
+    }
+}
+
+/// Holds the `ErrorContext` of the last failed request of a client. It is shared with the
+/// response extensions, so the context of an error response is recorded when its body is
+/// read.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LastErrorContext(Arc<Mutex<Option<ErrorContext>>>);
+
+impl LastErrorContext {
+    pub(crate) fn get(&self) -> Option<ErrorContext> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub(crate) fn set(&self, context: ErrorContext) {
+        *self.0.lock().unwrap() = Some(context);
+    }
 }
 
 /// Represents a client error.
@@ -49,35 +410,228 @@ pub enum ApiError {
 ///
 /// It also includes an HTTP request error variant that wraps the reqwest::Error type.
 #[derive(Debug, Error)]
+#[cfg_attr(feature = "diagnostics", derive(miette::Diagnostic))]
+#[non_exhaustive]
 pub enum ClientError {
     #[error("Bad Gateway")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::bad_gateway),
+            help("the API is temporarily unreachable, try again later")
+        )
+    )]
     BadGateway,
     #[error("HTTP Request Error: {0}")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::http_request),
+            help("check the network connection and the configured API URL")
+        )
+    )]
     HttpRequestError(#[from] reqwest::Error),
 
     #[error("Service Unavailable")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::service_unavailable),
+            help("the API is temporarily unavailable, try again later")
+        )
+    )]
     ServiceUnavailable,
+
+    #[error("Token Provider Error: {0}")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::token_provider),
+            help("the configured TokenProvider failed to return an API token")
+        )
+    )]
+    TokenProvider(Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("Transport Error: {0}")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::transport),
+            help("the configured Transport failed to send the request")
+        )
+    )]
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+
+    /// The response body does not match the response model, returned by clients built
+    /// with `ClientBuilder::strict_responses`.
+    #[error("Unexpected Response: {0}")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::unexpected_response),
+            help("the API response differs from the models of this client version")
+        )
+    )]
+    UnexpectedResponse(String),
+}
+
+/// Represents an error returned by `ClientBuilder::try_build`.
+///
+/// This enum represents an invalid client configuration, such as a malformed API URL or
+/// an API token that cannot be sent in an HTTP header.
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "diagnostics", derive(miette::Diagnostic))]
+pub enum BuildError {
+    #[error("Invalid API URL: {0}")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(code(ddclient::invalid_url), help("use an absolute http or https URL"))
+    )]
+    InvalidUrl(String),
+
+    #[error("Invalid proxy URL: {0}")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::invalid_proxy),
+            help("use an absolute proxy URL, such as http://proxy.internal:3128")
+        )
+    )]
+    InvalidProxy(String),
+
+    #[error("Invalid API token")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::invalid_token),
+            help("use a non-empty token with visible ASCII characters only")
+        )
+    )]
+    InvalidToken,
+
+    #[error("HTTP client error: {0}")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::http_client),
+            help("check the TLS and proxy configuration of the environment")
+        )
+    )]
+    HttpClient(reqwest::Error),
 }
 
 /// Represents a bad request error.
-#[derive(Error, Debug, Deserialize, PartialEq)]
+///
+/// New variants may be added in minor releases, as the API introduces new validation
+/// errors, so matches on `BadRequestError` need a wildcard arm.
+///
+/// Errors are serialized as the name of their variant, such as `MissingChoices`, and
+/// unknown errors as their message, so they deserialize back into the same error.
+#[derive(Error, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "diagnostics", derive(miette::Diagnostic))]
+#[non_exhaustive]
 pub enum BadRequestError {
     #[error("Invalid data")]
+    #[serde(alias = "Invalid data")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::invalid_data),
+            help("your ballot referenced a choice not present in the voting, or the payload is malformed")
+        )
+    )]
     InvalidData,
     #[error("Missing choices")]
+    #[serde(alias = "Missing choices")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::missing_choices),
+            help("a voting must be created with at least one choice")
+        )
+    )]
     MissingChoices,
     #[error("Choice too long")]
+    #[serde(alias = "Choice too long")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(code(ddclient::choice_too_long), help("shorten the choice text"))
+    )]
     ChoiceTooLong,
     #[error("Too many choices")]
+    #[serde(alias = "Too many choices")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::too_many_choices),
+            help("reduce the number of choices in the voting")
+        )
+    )]
     TooManyChoices,
     #[error("Choice required")]
+    #[serde(alias = "Choice required")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::choice_required),
+            help("provide a non-empty choice value")
+        )
+    )]
     ChoiceRequired,
     #[error("Ballot required")]
+    #[serde(alias = "Ballot required")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::ballot_required),
+            help("the ballot must rank at least one choice")
+        )
+    )]
     BallotRequired,
     #[error("Voter ID too long")]
+    #[serde(alias = "Voter ID too long")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(code(ddclient::voter_id_too_long), help("use a shorter voter ID"))
+    )]
     VoterIDTooLong,
     #[error("Invalid voter ID")]
+    #[serde(alias = "Invalid voter ID")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::invalid_voter_id),
+            help("voter IDs must not be empty or contain unsupported characters")
+        )
+    )]
     InvalidVoterID,
+    /// An error message that is not known to this client, preserved as sent by the API.
+    #[error("{0}")]
+    #[serde(untagged)]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::unknown_bad_request),
+            help("the API rejected the request, check the error message")
+        )
+    )]
+    Unknown(String),
+}
+
+impl BadRequestError {
+    /// Reports whether the error message is not known to this client.
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, BadRequestError::Unknown(_))
+    }
+}
+
+impl From<String> for BadRequestError {
+    /// Parses an error message sent by the API, returning `BadRequestError::Unknown` for
+    /// messages that are not known to this client.
+    fn from(message: String) -> Self {
+        serde_json::from_value(serde_json::Value::String(message.clone()))
+            .unwrap_or(BadRequestError::Unknown(message))
+    }
 }
 
 #[cfg(test)]
@@ -91,13 +645,7 @@ mod tests {
         fn eq(&self, other: &Self) -> bool {
             match (self, other) {
                 (ApiError::BadRequest(errors_self), ApiError::BadRequest(errors_other)) => {
-                    for err in errors_self {
-                        if !errors_other.contains(err) {
-                            return false;
-                        }
-                    }
-
-                    true
+                    errors_self == errors_other
                 }
                 (ApiError::Unauthorized, ApiError::Unauthorized) => true,
                 (ApiError::NotFound, ApiError::NotFound) => true,
@@ -107,7 +655,7 @@ mod tests {
                     ApiError::InternalServerError(msg_other),
                 ) => msg_self == msg_other,
                 (ApiError::MethodNotAllowed, ApiError::MethodNotAllowed) => true,
-                (ApiError::TooManyRequests, ApiError::TooManyRequests) => true,
+                (ApiError::RateLimited { .. }, ApiError::RateLimited { .. }) => true,
                 (ApiError::Other(msg_self), ApiError::Other(msg_other)) => msg_self == msg_other,
                 (ApiError::Client(err_self), ApiError::Client(err_other)) => {
                     match (err_self, err_other) {
@@ -195,7 +743,27 @@ mod tests {
                     BadRequestError::MissingChoices,
                 ]),
             ),
-            (StatusCode::TOO_MANY_REQUESTS, "", ApiError::TooManyRequests),
+            (
+                StatusCode::BAD_REQUEST,
+                r#"{"code":400,"message":"Bad Request","errors":["Invalid data","Voting closed"]}"#,
+                ApiError::BadRequest(vec![
+                    BadRequestError::InvalidData,
+                    BadRequestError::Unknown("Voting closed".to_string()),
+                ]),
+            ),
+            (
+                StatusCode::BAD_REQUEST,
+                r#"{"code":400,"message":"Bad Request","errors":[{"field":"choices[1]","code":"choice_too_long","message":"Choice too long"}]}"#,
+                ApiError::BadRequest(vec![BadRequestError::ChoiceTooLong]),
+            ),
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                "",
+                ApiError::RateLimited {
+                    rate: None,
+                    retry_after: None,
+                },
+            ),
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Internal Server Error",
@@ -221,9 +789,151 @@ mod tests {
             let result = handle_api_response::<()>(mock_response).await;
 
             match result {
-                Ok(_) => assert!(false, "Expected error but got Ok"),
+                Ok(_) => panic!("Expected error but got Ok"),
                 Err(err) => assert_eq!(err, expected_error),
             }
         }
     }
+
+    #[tokio::test]
+    async fn error_context_test() {
+        let last = LastErrorContext::default();
+        let mut response = Response::from(
+            Builder::new()
+                .status(StatusCode::BAD_REQUEST)
+                .header("X-Correlation-Id", "corr-42")
+                .body(r#"{"code":400,"message":"Bad Request","errors":[{"field":"choices[1]","code":"choice_too_long","message":"Choice too long"}]}"#)
+                .unwrap(),
+        );
+        response
+            .extensions_mut()
+            .insert(ErrorContext::new(Method::POST, "v1/votings/abc/choices"));
+        response.extensions_mut().insert(last.clone());
+
+        let err = handle_api_response::<()>(response).await.unwrap_err();
+        assert_eq!(
+            err,
+            ApiError::BadRequest(vec![BadRequestError::ChoiceTooLong])
+        );
+
+        let context = last.get().unwrap();
+        assert_eq!(context.method, Some(Method::POST));
+        assert_eq!(context.path.as_deref(), Some("v1/votings/abc/choices"));
+        assert_eq!(context.status, Some(StatusCode::BAD_REQUEST));
+        assert_eq!(context.voting_id.as_deref(), Some("abc"));
+        assert_eq!(context.request_id.as_deref(), Some("corr-42"));
+        assert_eq!(
+            context.fields,
+            vec![FieldError {
+                field: "choices[1]".to_string(),
+                code: "choice_too_long".to_string(),
+                message: "Choice too long".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn classification_test() {
+        let not_found = ApiError::NotFound;
+        assert!(not_found.is_not_found());
+        assert!(not_found.is_client_error());
+        assert!(!not_found.is_retryable());
+
+        let rate_limited = ApiError::RateLimited {
+            rate: None,
+            retry_after: None,
+        };
+        assert!(rate_limited.is_rate_limited());
+        assert!(rate_limited.is_retryable());
+        assert!(!rate_limited.is_client_error());
+
+        let unavailable = ApiError::Client(ClientError::ServiceUnavailable);
+        assert!(unavailable.is_retryable());
+        assert!(!unavailable.is_client_error());
+
+        let server_error = ApiError::InternalServerError(String::new());
+        assert!(!server_error.is_retryable());
+        assert!(!server_error.is_client_error());
+
+        let other = ApiError::Other(String::new());
+        assert!(!other.is_client_error());
+        assert!(!other.is_not_found());
+        assert!(!other.is_interrupted());
+
+        let cancelled = ApiError::Cancelled;
+        assert!(cancelled.is_interrupted());
+        assert!(!cancelled.is_retryable());
+        assert!(!cancelled.is_client_error());
+    }
+
+    #[test]
+    fn accessors_test() {
+        let bad_request =
+            ApiError::BadRequest(vec![BadRequestError::Unknown("Voting closed".to_string())]);
+        assert_eq!(bad_request.status(), Some(StatusCode::BAD_REQUEST));
+        assert!(bad_request.bad_request_errors()[0].is_unknown());
+        assert_eq!(bad_request.retry_after(), None);
+
+        let rate_limited = ApiError::RateLimited {
+            rate: None,
+            retry_after: Some(Duration::from_secs(30)),
+        };
+        assert_eq!(rate_limited.retry_after(), Some(Duration::from_secs(30)));
+        assert_eq!(rate_limited.status(), Some(StatusCode::TOO_MANY_REQUESTS));
+        assert!(rate_limited.bad_request_errors().is_empty());
+
+        let unavailable = ApiError::Client(ClientError::ServiceUnavailable);
+        assert_eq!(unavailable.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(ApiError::Other(String::new()).status(), None);
+    }
+
+    #[test]
+    fn serialize_test() {
+        let bad_request = ApiError::BadRequest(vec![
+            BadRequestError::InvalidData,
+            BadRequestError::Unknown("Voting closed".to_string()),
+        ]);
+        assert_eq!(
+            serde_json::to_value(&bad_request).unwrap(),
+            serde_json::json!({
+                "kind": "bad_request",
+                "status": 400,
+                "message": bad_request.to_string(),
+                "details": {
+                    "errors": ["InvalidData", "Voting closed"],
+                },
+            })
+        );
+
+        // the serialized errors deserialize back into the same errors
+        let errors: Vec<BadRequestError> =
+            serde_json::from_value(serde_json::to_value(bad_request.bad_request_errors()).unwrap())
+                .unwrap();
+        assert_eq!(errors, bad_request.bad_request_errors());
+
+        let rate_limited = ApiError::RateLimited {
+            rate: None,
+            retry_after: Some(Duration::from_secs(30)),
+        };
+        assert_eq!(
+            serde_json::to_value(&rate_limited).unwrap(),
+            serde_json::json!({
+                "kind": "rate_limited",
+                "status": 429,
+                "message": "Too many requests",
+                "details": {"retry_after": 30},
+            })
+        );
+
+        let unavailable = ApiError::Client(ClientError::ServiceUnavailable);
+        assert_eq!(
+            serde_json::to_value(&unavailable).unwrap(),
+            serde_json::json!({
+                "kind": "client",
+                "status": 503,
+                "message": "Client Error: Service Unavailable",
+                "details": {},
+            })
+        );
+    }
 }
@@ -0,0 +1,194 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::{Choices, VotingResults};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+pub(crate) const EVENT_STREAM: &str = "text/event-stream";
+
+/// Represents a change of a voting, received from the event stream of the voting.
+///
+/// Events are received with `Client::subscribe_events`. Events with a name which is not
+/// known to this version of the client, or with data which cannot be decoded, are returned
+/// as `VotingEvent::Unknown`, so new kinds of events don't end the subscription.
+///
+/// This type is available with the `sse` feature. The event stream of a voting is not part
+/// of the documented API yet, so no events are received until the API provides it.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum VotingEvent {
+    /// A voter submitted or changed a ballot.
+    BallotSubmitted { voter_id: String },
+    /// The ballot of a voter was removed.
+    BallotRemoved { voter_id: String },
+    /// The choices of the voting changed.
+    ChoicesChanged { choices: Choices },
+    /// The results of the voting changed.
+    ResultsUpdated { results: VotingResults },
+    /// An event which is not known to this version of the client.
+    Unknown { event: String, data: String },
+}
+
+#[derive(Deserialize)]
+struct VoterEvent {
+    voter_id: String,
+}
+
+#[derive(Deserialize)]
+struct ChoicesEvent {
+    choices: Choices,
+}
+
+impl VotingEvent {
+    pub(crate) fn from_message(message: SseMessage) -> Self {
+        let event = match message.event.as_str() {
+            "ballot_submitted" => {
+                decode(&message.data).map(|e: VoterEvent| VotingEvent::BallotSubmitted {
+                    voter_id: e.voter_id,
+                })
+            }
+            "ballot_removed" => {
+                decode(&message.data).map(|e: VoterEvent| VotingEvent::BallotRemoved {
+                    voter_id: e.voter_id,
+                })
+            }
+            "choices_changed" => decode(&message.data)
+                .map(|e: ChoicesEvent| VotingEvent::ChoicesChanged { choices: e.choices }),
+            "results_updated" => {
+                decode(&message.data).map(|results| VotingEvent::ResultsUpdated { results })
+            }
+            _ => None,
+        };
+        event.unwrap_or(VotingEvent::Unknown {
+            event: message.event,
+            data: message.data,
+        })
+    }
+}
+
+fn decode<T: DeserializeOwned>(data: &str) -> Option<T> {
+    serde_json::from_str(data).ok()
+}
+
+/// A message of a Server-Sent Events stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SseMessage {
+    pub(crate) event: String,
+    pub(crate) data: String,
+}
+
+/// Parses a Server-Sent Events stream, which may be split into chunks at any byte.
+#[derive(Debug, Default)]
+pub(crate) struct SseParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Option<String>,
+}
+
+impl SseParser {
+    /// Parses the next chunk of the stream, and returns the messages completed by it.
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> Vec<SseMessage> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut messages = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if let Some(data) = self.data.take() {
+                    messages.push(SseMessage {
+                        event: self.event.take().unwrap_or_else(|| "message".to_string()),
+                        data,
+                    });
+                }
+                self.event = None;
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => self.event = Some(value.to_string()),
+                "data" => match &mut self.data {
+                    Some(data) => {
+                        data.push('\n');
+                        data.push_str(value);
+                    }
+                    None => self.data = Some(value.to_string()),
+                },
+                _ => {}
+            }
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(event: &str, data: &str) -> SseMessage {
+        SseMessage {
+            event: event.to_string(),
+            data: data.to_string(),
+        }
+    }
+
+    #[test]
+    fn sse_parser_test() {
+        let mut parser = SseParser::default();
+
+        assert_eq!(parser.feed(b": keep-alive\n\nevent: ballot_subm"), vec![]);
+        assert_eq!(
+            parser.feed(b"itted\r\ndata: {\"voter_id\":\"einstein\"}\r\n\r\ndata: a\n"),
+            vec![message("ballot_submitted", "{\"voter_id\":\"einstein\"}")]
+        );
+        assert_eq!(
+            parser.feed(b"data:b\n\nevent: empty\n\n"),
+            vec![message("message", "a\nb")]
+        );
+
+        // a multi-byte character split between chunks
+        let text = "data: Schrödinger\n\n".as_bytes();
+        assert_eq!(parser.feed(&text[..13]), vec![]);
+        assert_eq!(
+            parser.feed(&text[13..]),
+            vec![message("message", "Schrödinger")]
+        );
+    }
+
+    #[test]
+    fn voting_event_test() {
+        assert_eq!(
+            VotingEvent::from_message(message("ballot_removed", r#"{"voter_id":"bohr"}"#)),
+            VotingEvent::BallotRemoved {
+                voter_id: "bohr".to_string()
+            }
+        );
+        assert_eq!(
+            VotingEvent::from_message(message("choices_changed", r#"{"choices":["Kant"]}"#)),
+            VotingEvent::ChoicesChanged {
+                choices: Choices::new(vec!["Kant".to_string()])
+            }
+        );
+        assert!(matches!(
+            VotingEvent::from_message(message("results_updated", r#"{"tie":false,"results":[]}"#)),
+            VotingEvent::ResultsUpdated { .. }
+        ));
+        assert_eq!(
+            VotingEvent::from_message(message("ballot_submitted", "not json")),
+            VotingEvent::Unknown {
+                event: "ballot_submitted".to_string(),
+                data: "not json".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::{ClientError, Transport};
+use reqwest::{Method, Request, Response};
+use std::time::Duration;
+
+/// Sends the request, and if no response is received within `delay`, sends a second, hedged
+/// copy of it and returns the response received first.
+///
+/// Only `GET` requests are hedged, as sending a mutating request twice could apply it twice.
+/// Other requests, and requests whose body cannot be cloned, are sent once.
+pub(crate) async fn execute(
+    transport: &dyn Transport,
+    request: Request,
+    delay: Duration,
+) -> Result<Response, ClientError> {
+    let hedged = match request.method() {
+        &Method::GET => request.try_clone(),
+        _ => None,
+    };
+    let Some(hedged) = hedged else {
+        return transport.execute(request).await;
+    };
+
+    let first = transport.execute(request);
+    tokio::pin!(first);
+    tokio::select! {
+        response = &mut first => return response,
+        _ = tokio::time::sleep(delay) => {}
+    }
+
+    let second = transport.execute(hedged);
+    tokio::select! {
+        response = first => response,
+        response = second => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockResponse;
+    use async_trait::async_trait;
+    use reqwest::StatusCode;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Responds to the first request after a second, and to the following ones immediately.
+    #[derive(Default)]
+    struct SlowFirstTransport {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for SlowFirstTransport {
+        async fn execute(&self, _request: Request) -> Result<Response, ClientError> {
+            let status = match self.requests.fetch_add(1, Ordering::SeqCst) {
+                0 => {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    StatusCode::OK
+                }
+                _ => StatusCode::ACCEPTED,
+            };
+            Ok(MockResponse::new(status).into_response())
+        }
+    }
+
+    fn request(method: Method) -> Request {
+        Request::new(method, "http://localhost/v1/votings/1".parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn hedge_get_test() {
+        let transport = SlowFirstTransport::default();
+
+        let response = execute(&transport, request(Method::GET), Duration::from_millis(10))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn no_hedge_for_fast_response_test() {
+        let transport = SlowFirstTransport::default();
+        transport.requests.store(1, Ordering::SeqCst);
+
+        let response = execute(&transport, request(Method::GET), Duration::from_millis(10))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn no_hedge_for_mutating_request_test() {
+        for method in [Method::POST, Method::DELETE] {
+            let transport = SlowFirstTransport::default();
+
+            let response = execute(&transport, request(method), Duration::from_millis(10))
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(transport.requests.load(Ordering::SeqCst), 1);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::BuildError;
+use std::time::Duration;
+
+/// The HTTP protocol versions used by the client, set with `ClientBuilder::http_version`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HttpVersion {
+    /// Uses HTTP/2 if the server offers it during the TLS handshake, and HTTP/1.1 otherwise.
+    #[default]
+    Auto,
+    /// Uses only HTTP/1.1, for proxies and gateways which do not support HTTP/2.
+    Http1Only,
+    /// Uses only HTTP/2, without negotiating it first.
+    Http2Only,
+}
+
+/// Settings of the internal Reqwest client, shared by the async and blocking builders.
+///
+/// The settings have no effect if a custom Reqwest client is set on the builder.
+#[derive(Debug, Default)]
+pub(crate) struct HttpConfig {
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) proxy: Option<String>,
+    pub(crate) proxy_auth: Option<(String, String)>,
+    pub(crate) no_proxy: bool,
+    pub(crate) http_version: HttpVersion,
+    pub(crate) http2_adaptive_window: Option<bool>,
+    pub(crate) http2_keep_alive_interval: Option<Duration>,
+    pub(crate) http2_keep_alive_timeout: Option<Duration>,
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub(crate) root_certificates: Vec<reqwest::Certificate>,
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub(crate) identity: Option<reqwest::Identity>,
+}
+
+impl HttpConfig {
+    pub(crate) fn build(&self) -> Result<reqwest::Client, BuildError> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if self.no_proxy {
+            builder = builder.no_proxy();
+        }
+        if let Some(proxy) = self.proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        match self.http_version {
+            HttpVersion::Auto => {}
+            HttpVersion::Http1Only => builder = builder.http1_only(),
+            HttpVersion::Http2Only => builder = builder.http2_prior_knowledge(),
+        }
+        if let Some(enabled) = self.http2_adaptive_window {
+            builder = builder.http2_adaptive_window(enabled);
+        }
+        if let Some(interval) = self.http2_keep_alive_interval {
+            builder = builder.http2_keep_alive_interval(interval);
+        }
+        if let Some(timeout) = self.http2_keep_alive_timeout {
+            builder = builder.http2_keep_alive_timeout(timeout);
+        }
+        #[cfg(any(feature = "native-tls", feature = "rustls"))]
+        {
+            for certificate in &self.root_certificates {
+                builder = builder.add_root_certificate(certificate.clone());
+            }
+            if let Some(identity) = &self.identity {
+                builder = builder.identity(identity.clone());
+            }
+        }
+        builder.build().map_err(BuildError::HttpClient)
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn build_blocking(&self) -> Result<reqwest::blocking::Client, BuildError> {
+        let mut builder = reqwest::blocking::Client::builder();
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if self.no_proxy {
+            builder = builder.no_proxy();
+        }
+        if let Some(proxy) = self.proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        match self.http_version {
+            HttpVersion::Auto => {}
+            HttpVersion::Http1Only => builder = builder.http1_only(),
+            HttpVersion::Http2Only => builder = builder.http2_prior_knowledge(),
+        }
+        if let Some(enabled) = self.http2_adaptive_window {
+            builder = builder.http2_adaptive_window(enabled);
+        }
+        #[cfg(any(feature = "native-tls", feature = "rustls"))]
+        {
+            for certificate in &self.root_certificates {
+                builder = builder.add_root_certificate(certificate.clone());
+            }
+            if let Some(identity) = &self.identity {
+                builder = builder.identity(identity.clone());
+            }
+        }
+        builder.build().map_err(BuildError::HttpClient)
+    }
+
+    fn proxy(&self) -> Result<Option<reqwest::Proxy>, BuildError> {
+        let Some(url) = &self.proxy else {
+            return Ok(None);
+        };
+
+        let mut proxy = reqwest::Proxy::all(url)
+            .map_err(|err| BuildError::InvalidProxy(format!("{}: {}", url, err)))?;
+        if let Some((username, password)) = &self.proxy_auth {
+            proxy = proxy.basic_auth(username, password);
+        }
+        Ok(Some(proxy))
+    }
+}
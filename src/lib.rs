@@ -12,6 +12,8 @@
 //! retrieving voting results, and more.
 //!
 //! The api specification can be found at https://api.directdecisions.com/v1.
+//! The version of the API is set with `ClientBuilder::api_version`, and the response models
+//! of each version are in the module of the same name, such as `ddclient_rs::v1`.
 //!
 //! ## Features
 //!
@@ -20,6 +22,10 @@
 //! - Modify voting choices.
 //! - Fetch voting results and analyze outcomes.
 //! - Handle rate limits and errors gracefully.
+//! - Compute results offline with the Schulze method in the `ddclient_rs::schulze` module.
+//! - Generate synthetic ballots for load tests and demos in the `ddclient_rs::simulation`
+//!   module.
+//! - Queue votes while offline and submit them later with `VoteQueue`.
 //!
 //! ## Usage
 //!
@@ -37,7 +43,7 @@
 //!
 //! #[tokio::main]
 //! async fn main() {
-//!     let client = Client::builder("your-api-key".to_string()).build();
+//!     let client = Client::try_new("your-api-key".to_string()).unwrap();
 //!
 //!     // Example: Creating a new voting
 //!     let voting = client.create_voting(vec!["Einstein".to_string(), "Newton".to_string()]).await.unwrap();
@@ -46,6 +52,44 @@
 //! }
 //! ```
 //!
+//! Application code can depend on the `DirectDecisionsApi` trait, which is implemented by the
+//! `Client`, to replace the client with a fake or a `mockall` mock in unit tests.
+//!
+//! ## Optional Features
+//!
+//! - `native-tls` - Enabled by default, uses the platform native TLS implementation.
+//! - `rustls` - Uses `rustls` as the TLS implementation. Together with
+//!   `default-features = false`, the crate can be built without OpenSSL.
+//! - `blocking` - Provides a blocking client in the `ddclient_rs::blocking` module, for
+//!   applications that don't use an async runtime.
+//! - `cli` - Builds the `ddclient` command line client, with commands to create, show and
+//!   delete votings, change their choices, submit and show ballots, and show results.
+//! - `decimal` - Deserializes result percentages into `rust_decimal::Decimal` instead of `f32`.
+//! - `diagnostics` - Implements `miette::Diagnostic` for the error types, with error codes,
+//!   help texts and request body snippets through `RequestDiagnostic`.
+//! - `experimental` - Provides methods for endpoints which are not part of the documented
+//!   API yet, such as `Client::list_votings` and `Client::ballots_stream`, and the
+//!   speculative voting metadata: `Voting::title`, `Voting::description` and
+//!   `Client::get_voting_metadata`, and the status of a voting: `Voting::status` and
+//!   `Client::close_voting`. These endpoints may change or be unavailable, and the methods
+//!   may change without a major version bump.
+//! - `sse` - Provides `Client::subscribe_events`, a stream of the changes of a voting,
+//!   consumed as Server-Sent Events. The events endpoint is not part of the documented API
+//!   yet, so the subscriptions fail until the API provides it.
+//! - `tracing` - Instruments the requests of the `Client` with `tracing` spans, recording
+//!   the HTTP method, path, response status, latency and remaining rate limit.
+//! - `rate-limit` - Provides `ClientBuilder::max_requests_per_second`, which limits the rate
+//!   of requests sent by the client, independently of the rate limit reported by the API.
+//! - `prometheus` - Provides `PrometheusMetrics`, a `MetricsObserver` that records request
+//!   counts, latencies and the remaining rate limit as Prometheus metrics.
+//! - `test-util` - Provides the `ddclient_rs::testing` module with `MockClient`, an in-memory
+//!   implementation of `DirectDecisionsApi`, `FakeServer`, a local HTTP server emulating
+//!   the API, and `Cassette`, a transport recording and replaying responses, for offline
+//!   tests.
+//! - `tower` - Implements `tower::Service` for the transport of the `Client` with
+//!   `Client::service`, and provides `ServiceTransport`, which sends the requests of a
+//!   `Client` through a `tower::Service`, so tower layers can be composed around the calls.
+//!
 //! ## Error Handling
 //!
 //! The client uses custom error types defined in the `ddclient_rs::errors`, the APIError enum.
@@ -58,27 +102,124 @@
 //!
 //! Contributions are welcome! Please refer to the repository's `CONTRIBUTING.md` file for contribution guidelines.
 //!
+mod api;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod bulk;
+mod cache;
+mod choices;
 mod client;
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
+mod diff;
+mod endpoint;
 mod errors;
+#[cfg(feature = "sse")]
+mod events;
+mod hedge;
+mod http_config;
+mod limiter;
+mod logging;
+mod metrics;
+mod middleware;
+mod options;
+mod pacer;
+mod pairwise;
+mod percentage;
+mod pool;
+#[cfg(feature = "prometheus")]
+mod prometheus_metrics;
+mod queue;
+mod rank;
 mod rate;
+#[cfg(feature = "rate-limit")]
+mod rate_limiter;
+mod report;
+mod response;
+mod retry;
+pub mod schulze;
+#[cfg(feature = "tower")]
+mod service;
+pub mod simulation;
+mod strict;
+mod table;
+#[cfg(feature = "test-util")]
+pub mod testing;
+mod throttle;
+mod token;
+mod transport;
+mod validation;
+mod version;
 
+pub use api::DirectDecisionsApi;
+pub use bulk::{BulkOptions, DeleteVotingsReport};
+pub use cache::MaybeStale;
+pub use choices::{ChoiceIndex, Choices};
 pub use client::*;
+#[cfg(feature = "diagnostics")]
+pub use diagnostics::RequestDiagnostic;
+pub use diff::{diff, ChoiceChange, ResultsDiff};
 pub use errors::*;
-pub use rate::Rate;
+#[cfg(feature = "sse")]
+pub use events::VotingEvent;
+pub use http_config::HttpVersion;
+pub use logging::{LogRecord, RequestLogger};
+pub use metrics::MetricsObserver;
+pub use middleware::Middleware;
+pub use options::RequestOptions;
+pub use pacer::RatePacer;
+pub use pairwise::PairwiseMatrix;
+pub use percentage::{format_percentage, Percentage};
+pub use pool::ClientPool;
+#[cfg(feature = "prometheus")]
+pub use prometheus_metrics::PrometheusMetrics;
+pub use queue::{FlushReport, QueuedOperation, VoteQueue};
+pub use rank::{Rank, RankOutOfRange};
+pub use rate::{EndpointFamily, Rate};
+pub use report::{ReportFormat, Standing, VotingSummary};
+use reqwest::header::HeaderMap;
 use reqwest::{Response, StatusCode};
+pub use response::ApiResponse;
+pub use retry::RetryPolicy;
+#[cfg(feature = "tower")]
+pub use service::{ServiceTransport, TransportService};
+pub use table::{render_table, TableFormat};
+pub use token::TokenProvider;
+pub use tokio_util::sync::CancellationToken;
+pub use transport::{MockResponse, MockTransport, RecordedRequest, Transport};
+pub use validation::{validate_ballot, ValidationError};
+pub use version::ApiVersion;
 
+#[cfg(feature = "experimental")]
+use endpoint::Endpoint;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use strict::StrictResponses;
+
+/// The response models of the version 1 API, used with `ApiVersion::V1`.
+///
+/// The models of the default version are also exported from the crate root.
+pub mod v1 {
+    pub use crate::{
+        ChoiceStrength, Duels, VoterBallot, Voting, VotingResult, VotingResults, VotingStats,
+    };
+
+    #[cfg(feature = "experimental")]
+    pub use crate::{BallotsPage, VotingMetadata, VotingStatus, VotingsPage};
+}
 
 const CONTENT_TYPE: &str = "application/json; charset=utf-8";
 const USER_AGENT: &str = "ddclient-rs/0.1.0";
 const DEFAULT_BASE_URL: &str = "https://api.directdecisions.com";
+const HEADER_REQUEST_ID: &str = "X-Request-Id";
+const HEADER_CORRELATION_ID: &str = "X-Correlation-Id";
 
 /// Represents the results of a voting process.
 ///
 /// This struct contains the overall results of a voting, including details on whether the
 /// voting resulted in a tie and the individual results for each choice.
 /// It can also contain additional information about how choices compare to each other in duels
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct VotingResults {
     pub tie: bool,
     pub results: Vec<VotingResult>,
@@ -86,80 +227,357 @@ pub struct VotingResults {
 }
 
 /// Represents the duel information for 2 choices, as part of the voting results.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct Duels {
     pub left: ChoiceStrength,
     pub right: ChoiceStrength,
 }
 
 /// Represents the strength of a choice compared to another choice in a duel.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct ChoiceStrength {
-    pub index: isize,
+    pub index: ChoiceIndex,
     pub choice: String,
     pub strength: isize,
 }
 
 /// Represents the single result for a specific choice.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+///
+/// Contains all fields returned by the v1 results endpoint, including the Schulze method
+/// strength data.
+///
+/// `VotingResult` and `VotingResults` implement `PartialEq`, but not `Eq` and `Hash`, as the
+/// percentage is a floating point number unless the `decimal` feature is enabled.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct VotingResult {
+    /// The choice value.
     pub choice: String,
-    pub index: i32,
+    /// The position of the choice in the voting choices list.
+    pub index: ChoiceIndex,
+    /// The number of other choices this choice beats in the Schulze method.
     pub wins: i32,
-    pub percentage: f32,
+    /// The percentage of other choices this choice beats.
+    #[cfg_attr(feature = "decimal", serde(with = "rust_decimal::serde::float"))]
+    pub percentage: Percentage,
+    /// The sum of the strongest path strengths of this choice against all other choices.
     pub strength: usize,
+    /// The sum of the differences between the strongest path strengths of this choice and
+    /// the other choices, for the choices it beats.
     pub advantage: usize,
 }
 
+impl VotingResult {
+    /// Returns the percentage formatted with the given number of decimal places.
+    ///
+    /// See `format_percentage` for the rounding rules.
+    pub fn formatted_percentage(&self, decimals: u32) -> String {
+        format_percentage(self.percentage, decimals)
+    }
+}
+
 /// Represents a voting.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct Voting {
     pub id: String,
-    pub choices: Vec<String>,
+    pub choices: Choices,
+    /// The title of the voting, if it was set.
+    ///
+    /// This field is available with the `experimental` feature, as voting metadata is not
+    /// part of the documented API yet.
+    #[cfg(feature = "experimental")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The description of the voting, if it was set.
+    ///
+    /// This field is available with the `experimental` feature, as voting metadata is not
+    /// part of the documented API yet.
+    #[cfg(feature = "experimental")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Whether the voting accepts ballots, or `None` if the API does not report it.
+    ///
+    /// This field is available with the `experimental` feature, as the status of a voting
+    /// is not part of the documented API yet.
+    #[cfg(feature = "experimental")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<VotingStatus>,
+}
+
+/// Represents whether a voting accepts ballots.
+///
+/// Votings are open when they are created, and can be closed with `Client::close_voting`
+/// to stop accepting ballots while keeping the results, and reopened with
+/// `Client::reopen_voting`.
+///
+/// This type is available with the `experimental` feature. The status of a voting and the
+/// endpoints which change it are not part of the documented API yet, so they may change or
+/// be unavailable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+#[cfg(feature = "experimental")]
+pub enum VotingStatus {
+    /// The voting accepts ballots.
+    Open,
+    /// The voting does not accept ballots.
+    Closed,
+    /// A status which is not known to this version of the client.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Voting {
+    /// Reports whether the API reported the voting as closed, so it does not accept
+    /// ballots.
+    ///
+    /// This method is available with the `experimental` feature.
+    #[cfg(feature = "experimental")]
+    pub fn is_closed(&self) -> bool {
+        self.status == Some(VotingStatus::Closed)
+    }
+
+    /// Returns the choice at the given index, or `None` if the index is out of bounds.
+    pub fn choice(&self, index: ChoiceIndex) -> Option<&str> {
+        index.choice(&self.choices)
+    }
+
+    /// Returns the index of the given choice, or `None` if the voting does not contain it.
+    pub fn index_of(&self, choice: &str) -> Option<ChoiceIndex> {
+        self.choices
+            .iter()
+            .position(|c| c == choice)
+            .map(ChoiceIndex::new)
+    }
+}
+
+/// Represents the turnout statistics of a voting.
+///
+/// The API does not report the turnout, so applications which track it can add it to a
+/// summary with `VotingSummary::with_stats`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct VotingStats {
+    /// The number of ballots currently submitted to the voting.
+    pub ballots: u64,
+    /// The number of times voters replaced their previously submitted ballot.
+    pub revotes: u64,
+}
+
+/// Represents a single page of votings returned by the votings listing endpoint.
+///
+/// This type is available with the `experimental` feature.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg(feature = "experimental")]
+pub struct VotingsPage {
+    /// The votings on this page.
+    pub votings: Vec<Voting>,
+    /// The cursor to pass to `ListOptions::cursor` to fetch the next page,
+    /// or `None` if this is the last page.
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+    /// The total number of votings, if reported by the API.
+    #[serde(default)]
+    pub total: Option<u64>,
+}
+
+#[cfg(feature = "experimental")]
+impl VotingsPage {
+    /// Returns `true` if there are more pages after this one.
+    pub fn has_more(&self) -> bool {
+        self.next_cursor.is_some()
+    }
+}
+
+/// Represents a ballot submitted by a specific voter.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VoterBallot {
+    /// The ID of the voter who submitted the ballot.
+    pub voter_id: String,
+    /// The ballot as a map of choices to their ranks.
+    pub ballot: HashMap<String, Rank>,
+}
+
+/// Represents the descriptive metadata of a voting, such as what the voting is about.
+///
+/// Fields which are `None` are not set on the voting.
+///
+/// This type is available with the `experimental` feature. Voting metadata is not part of
+/// the documented API yet, so the type is speculative and may change.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg(feature = "experimental")]
+pub struct VotingMetadata {
+    /// The title of the voting.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// The description of the voting.
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Represents a single page of ballots returned by the ballots listing endpoint.
+///
+/// This type is available with the `experimental` feature.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg(feature = "experimental")]
+pub struct BallotsPage {
+    /// The ballots on this page.
+    pub ballots: Vec<VoterBallot>,
+    /// The cursor to pass to `ListOptions::cursor` to fetch the next page,
+    /// or `None` if this is the last page.
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+}
+
+/// Pagination parameters for listing endpoints.
+///
+/// This type is available with the `experimental` feature.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::ListOptions;
+///
+/// let options = ListOptions::new().limit(50);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg(feature = "experimental")]
+pub struct ListOptions {
+    limit: Option<u32>,
+    cursor: Option<String>,
+}
+
+#[cfg(feature = "experimental")]
+impl ListOptions {
+    /// Creates new `ListOptions` which request the first page with the default page size.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of items returned in a single page.
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Sets the cursor of the page to fetch, as returned by the previous page.
+    pub fn cursor(mut self, cursor: String) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    fn append_query(&self, mut endpoint: Endpoint) -> Endpoint {
+        if let Some(limit) = self.limit {
+            endpoint = endpoint.query_number("limit", limit);
+        }
+        if let Some(cursor) = &self.cursor {
+            endpoint = endpoint.query("cursor", cursor);
+        }
+        endpoint
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct ApiErrorResponse {
     code: i32,
     message: String,
-    errors: Vec<String>,
+    errors: Vec<ApiErrorEntry>,
 }
 
-async fn handle_api_response<T: serde::de::DeserializeOwned>(
+/// An entry of the `errors` array of an API error response, either an error message, or a
+/// structured validation error of a payload field.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum ApiErrorEntry {
+    Message(String),
+    Field(FieldError),
+}
+
+async fn handle_api_response<T: serde::de::DeserializeOwned + Serialize>(
     response: Response,
 ) -> Result<T, ApiError> {
     match response.status() {
+        StatusCode::OK if response.extensions().get::<StrictResponses>().is_some() => {
+            let body = response
+                .bytes()
+                .await
+                .map_err(ClientError::HttpRequestError)?;
+            Ok(strict::from_slice(&body)?)
+        }
         StatusCode::OK => response
             .json()
             .await
             .map_err(|err| ApiError::Client(ClientError::HttpRequestError(err))),
-        StatusCode::NOT_FOUND => Err(ApiError::NotFound),
-        StatusCode::UNAUTHORIZED => Err(ApiError::Unauthorized),
-        StatusCode::FORBIDDEN => Err(ApiError::Forbidden),
-        StatusCode::TOO_MANY_REQUESTS => Err(ApiError::TooManyRequests),
-        StatusCode::METHOD_NOT_ALLOWED => Err(ApiError::MethodNotAllowed),
-        StatusCode::BAD_REQUEST => match response.json::<ApiErrorResponse>().await {
-            Ok(error_resp) => {
-                let bad_request_errors = error_resp
-                    .errors
-                    .into_iter()
-                    .filter_map(|err| {
-                        serde_json::from_str::<BadRequestError>(&format!("\"{}\"", err)).ok()
-                    })
-                    .collect();
-                Err(ApiError::BadRequest(bad_request_errors))
-            }
-            Err(_) => Err(ApiError::BadRequest(vec![])),
+        _ => Err(response_error(response).await),
+    }
+}
+
+/// Maps an unsuccessful response to an `ApiError`, reading the response body.
+async fn response_error(mut response: Response) -> ApiError {
+    let status = response.status();
+    let headers = response.headers().clone();
+    let extensions = std::mem::take(response.extensions_mut());
+    let body = response.text().await.unwrap_or_default();
+    api_error(status, &headers, &extensions, body)
+}
+
+/// Returns the request ID sent by the API in the `X-Request-Id` or `X-Correlation-Id`
+/// response header.
+fn request_id(headers: &HeaderMap) -> Option<String> {
+    [HEADER_REQUEST_ID, HEADER_CORRELATION_ID]
+        .iter()
+        .find_map(|name| headers.get(*name)?.to_str().ok())
+        .map(String::from)
+}
+
+/// Maps an unsuccessful response status, its headers and body to an `ApiError`.
+///
+/// The `ErrorContext` of the request, stored in the response extensions when the request
+/// was sent, is completed with the response status, request ID and field errors, and
+/// recorded on the client that sent the request.
+fn api_error(
+    status: StatusCode,
+    headers: &HeaderMap,
+    extensions: &http::Extensions,
+    body: String,
+) -> ApiError {
+    let mut context = extensions
+        .get::<ErrorContext>()
+        .cloned()
+        .unwrap_or_default();
+    context.status = Some(status);
+    context.request_id = request_id(headers);
+
+    let error = match status {
+        StatusCode::NOT_FOUND => ApiError::NotFound,
+        StatusCode::UNAUTHORIZED => ApiError::Unauthorized,
+        StatusCode::FORBIDDEN => ApiError::Forbidden,
+        StatusCode::TOO_MANY_REQUESTS => ApiError::RateLimited {
+            rate: Rate::from_headers(headers),
+            retry_after: rate::retry_after(headers),
         },
-        StatusCode::SERVICE_UNAVAILABLE => Err(ApiError::Client(ClientError::ServiceUnavailable)),
-        StatusCode::BAD_GATEWAY => Err(ApiError::Client(ClientError::BadGateway)),
-        StatusCode::INTERNAL_SERVER_ERROR => {
-            let error_message = response.text().await.unwrap_or_default();
-            Err(ApiError::InternalServerError(error_message))
-        }
-        _ => {
-            let error_message = response.text().await.unwrap_or_default();
-            Err(ApiError::Other(error_message))
+        StatusCode::METHOD_NOT_ALLOWED => ApiError::MethodNotAllowed,
+        StatusCode::BAD_REQUEST => {
+            let mut errors = Vec::new();
+            if let Ok(error_resp) = serde_json::from_str::<ApiErrorResponse>(&body) {
+                for entry in error_resp.errors {
+                    match entry {
+                        ApiErrorEntry::Message(message) => errors.push(message.into()),
+                        ApiErrorEntry::Field(field) => {
+                            errors.push(field.message.clone().into());
+                            context.fields.push(field);
+                        }
+                    }
+                }
+            }
+            ApiError::BadRequest(errors)
         }
+        StatusCode::SERVICE_UNAVAILABLE => ApiError::Client(ClientError::ServiceUnavailable),
+        StatusCode::BAD_GATEWAY => ApiError::Client(ClientError::BadGateway),
+        StatusCode::INTERNAL_SERVER_ERROR => ApiError::InternalServerError(body),
+        _ => ApiError::Other(body),
+    };
+
+    if let Some(last_error_context) = extensions.get::<LastErrorContext>() {
+        last_error_context.set(context);
     }
+    error
 }
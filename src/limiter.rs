@@ -0,0 +1,132 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+#[cfg(feature = "blocking")]
+use std::sync::{Condvar, Mutex};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Limits the number of requests of a client in flight at the same time.
+///
+/// A request holds a permit from before it is sent until its response is received,
+/// including its retries, and requests over the limit wait for a permit in the order
+/// they were made.
+#[derive(Debug)]
+pub(crate) struct Limiter {
+    semaphore: Semaphore,
+}
+
+impl Limiter {
+    /// Creates a limiter allowing `limit` requests at the same time. A limit of `0` is
+    /// treated as `1`.
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            semaphore: Semaphore::new(limit.clamp(1, Semaphore::MAX_PERMITS)),
+        }
+    }
+
+    /// Waits until a request can be sent.
+    pub(crate) async fn acquire(&self) -> SemaphorePermit<'_> {
+        self.semaphore
+            .acquire()
+            .await
+            .expect("the semaphore is never closed")
+    }
+}
+
+/// Limits the number of requests of a blocking client in flight at the same time.
+///
+/// See `Limiter`.
+#[cfg(feature = "blocking")]
+#[derive(Debug)]
+pub(crate) struct BlockingLimiter {
+    available: Mutex<usize>,
+    released: Condvar,
+}
+
+#[cfg(feature = "blocking")]
+impl BlockingLimiter {
+    /// Creates a limiter allowing `limit` requests at the same time. A limit of `0` is
+    /// treated as `1`.
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            available: Mutex::new(limit.max(1)),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Blocks until a request can be sent.
+    pub(crate) fn acquire(&self) -> BlockingPermit<'_> {
+        let mut available = self
+            .released
+            .wait_while(self.available.lock().unwrap(), |available| *available == 0)
+            .unwrap();
+        *available -= 1;
+        BlockingPermit { limiter: self }
+    }
+}
+
+/// Allows a request of a blocking client to be in flight, until it is dropped.
+#[cfg(feature = "blocking")]
+#[derive(Debug)]
+pub(crate) struct BlockingPermit<'a> {
+    limiter: &'a BlockingLimiter,
+}
+
+#[cfg(feature = "blocking")]
+impl Drop for BlockingPermit<'_> {
+    fn drop(&mut self) {
+        *self.limiter.available.lock().unwrap() += 1;
+        self.limiter.released.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn limiter_test() {
+        let limiter = Limiter::new(2);
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+
+        let requests = (0..6).map(|_| async {
+            let _permit = limiter.acquire().await;
+            let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            max_in_flight.fetch_max(current, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+        });
+        futures_util::future::join_all(requests).await;
+
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+        assert_eq!(Limiter::new(0).semaphore.available_permits(), 1);
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn blocking_limiter_test() {
+        let limiter = BlockingLimiter::new(2);
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+
+        std::thread::scope(|scope| {
+            for _ in 0..6 {
+                scope.spawn(|| {
+                    let _permit = limiter.acquire();
+                    let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(current, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(5));
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+        assert_eq!(*limiter.available.lock().unwrap(), 2);
+    }
+}
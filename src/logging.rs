@@ -0,0 +1,251 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::{Middleware, REDACTED};
+use reqwest::header::{
+    HeaderMap, HeaderName, AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, SET_COOKIE,
+};
+use reqwest::{Method, Request, Response, StatusCode};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+type Sink = dyn Fn(&LogRecord) + Send + Sync;
+
+/// Middleware logging every request and response of a client, for diagnosing production
+/// incidents.
+///
+/// Every request is logged with its method, path, headers and body size, and every response
+/// with its status, path, headers and body size. The values of the `Authorization`,
+/// `Proxy-Authorization`, `Cookie` and `Set-Cookie` headers, and of the headers added with
+/// `RequestLogger::redact_header`, are replaced with `[REDACTED]`, so the API key never
+/// appears in the logs.
+///
+/// The records are logged with `tracing::debug!` when the `tracing` feature is enabled, and
+/// written to the standard error otherwise, unless a sink is set with `RequestLogger::sink`.
+/// Clones of a `RequestLogger` share the enabled state, so logging can be switched on and
+/// off at runtime with `RequestLogger::set_enabled`, without rebuilding the client.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{Client, RequestLogger};
+///
+/// let logger = RequestLogger::new();
+/// let client = Client::builder("my-api-key".to_string())
+///     .middleware(logger.clone())
+///     .try_build()
+///     .unwrap();
+///
+/// // Later, for example from an admin endpoint.
+/// logger.set_enabled(false);
+/// ```
+#[derive(Clone)]
+pub struct RequestLogger {
+    enabled: Arc<AtomicBool>,
+    redacted: Vec<HeaderName>,
+    sink: Arc<Sink>,
+}
+
+impl RequestLogger {
+    /// Creates a new enabled `RequestLogger` with the default sink.
+    pub fn new() -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(true)),
+            redacted: vec![AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE, SET_COOKIE],
+            sink: Arc::new(log),
+        }
+    }
+
+    /// Sets the function called with every record instead of the default sink, for
+    /// example to forward the records to the logger of the application.
+    pub fn sink(mut self, sink: impl Fn(&LogRecord) + Send + Sync + 'static) -> Self {
+        self.sink = Arc::new(sink);
+        self
+    }
+
+    /// Adds a header whose value is redacted in the records, in addition to the
+    /// authentication and cookie headers.
+    pub fn redact_header(mut self, name: HeaderName) -> Self {
+        self.redacted.push(name);
+        self
+    }
+
+    /// Enables or disables logging, for this logger and all its clones.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Reports whether logging is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Returns the headers as name and value pairs, with the sensitive values redacted.
+    fn sanitize(&self, headers: &HeaderMap) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if self.redacted.contains(name) {
+                    REDACTED.to_string()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                (name.to_string(), value)
+            })
+            .collect()
+    }
+}
+
+impl Default for RequestLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for RequestLogger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestLogger")
+            .field("enabled", &self.is_enabled())
+            .field("redacted", &self.redacted)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Middleware for RequestLogger {
+    fn on_request(&self, request: &mut Request) {
+        if !self.is_enabled() {
+            return;
+        }
+        (self.sink)(&LogRecord {
+            method: Some(request.method().clone()),
+            path: request.url().path().to_string(),
+            status: None,
+            headers: self.sanitize(request.headers()),
+            body_size: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|bytes| bytes.len() as u64),
+        });
+    }
+
+    fn on_response(&self, response: &Response) {
+        if !self.is_enabled() {
+            return;
+        }
+        (self.sink)(&LogRecord {
+            method: None,
+            path: response.url().path().to_string(),
+            status: Some(response.status()),
+            headers: self.sanitize(response.headers()),
+            body_size: response.content_length(),
+        });
+    }
+}
+
+/// A request or response logged by a `RequestLogger`.
+///
+/// Records of requests have a `method` and no `status`, and records of responses have a
+/// `status` and no `method`. The `Display` implementation formats the record as a single
+/// line, such as `GET /v1/votings/abc body=0 headers=[...]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LogRecord {
+    /// The method of a logged request.
+    pub method: Option<Method>,
+    /// The path of the URL.
+    pub path: String,
+    /// The status of a logged response.
+    pub status: Option<StatusCode>,
+    /// The headers, with the sensitive values redacted.
+    pub headers: Vec<(String, String)>,
+    /// The size of the body in bytes, if it is known.
+    pub body_size: Option<u64>,
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.method, self.status) {
+            (Some(method), _) => write!(f, "{} {}", method, self.path)?,
+            (None, Some(status)) => write!(f, "{} {}", status.as_u16(), self.path)?,
+            (None, None) => f.write_str(&self.path)?,
+        }
+        if let Some(body_size) = self.body_size {
+            write!(f, " body={}", body_size)?;
+        }
+        let headers: Vec<String> = self
+            .headers
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect();
+        write!(f, " headers=[{}]", headers.join(", "))
+    }
+}
+
+/// The default sink of a `RequestLogger`.
+fn log(record: &LogRecord) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        method = record.method.as_ref().map(Method::as_str),
+        path = %record.path,
+        status = record.status.map(|status| status.as_u16()),
+        body_size = record.body_size,
+        headers = ?record.headers,
+        "http"
+    );
+    #[cfg(not(feature = "tracing"))]
+    eprintln!("ddclient: {}", record);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use std::sync::Mutex;
+
+    #[test]
+    fn logger_test() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let logger = RequestLogger::new()
+            .redact_header(HeaderName::from_static("x-api-key"))
+            .sink({
+                let records = records.clone();
+                move |record| records.lock().unwrap().push(record.to_string())
+            });
+
+        let mut request = Request::new(
+            Method::POST,
+            "http://localhost/v1/votings?x=1".parse().unwrap(),
+        );
+        let headers = request.headers_mut();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        headers.insert("x-api-key", HeaderValue::from_static("secret"));
+        headers.insert("accept", HeaderValue::from_static("application/json"));
+        *request.body_mut() = Some("{\"choices\":[]}".into());
+        logger.on_request(&mut request);
+
+        let response = http::Response::builder()
+            .status(StatusCode::CREATED)
+            .header("content-length", "2")
+            .body("{}")
+            .unwrap();
+        logger.on_response(&Response::from(response));
+
+        // disabled clones log nothing
+        logger.clone().set_enabled(false);
+        assert!(!logger.is_enabled());
+        logger.on_request(&mut request);
+
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec![
+                "POST /v1/votings body=14 headers=[authorization: [REDACTED], \
+                 x-api-key: [REDACTED], accept: application/json]"
+                    .to_string(),
+                "201 / body=2 headers=[content-length: 2]".to_string(),
+            ]
+        );
+    }
+}
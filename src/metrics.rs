@@ -0,0 +1,113 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::Rate;
+use reqwest::{Method, StatusCode};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Receives events about the requests sent by the `Client`, for plugging the client into
+/// a metrics stack.
+///
+/// All methods have empty default implementations, so observers only implement the events
+/// they are interested in. The `path` is relative to the API URL and includes the voting
+/// and voter IDs, so it should not be used as a metric label as is.
+///
+/// An observer is registered with `ClientBuilder::metrics_observer`.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{Client, MetricsObserver};
+/// use reqwest::{Method, StatusCode};
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::time::Duration;
+///
+/// #[derive(Default)]
+/// struct ErrorCounter(AtomicU64);
+///
+/// impl MetricsObserver for ErrorCounter {
+///     fn request_completed(
+///         &self,
+///         _method: &Method,
+///         _path: &str,
+///         status: Option<StatusCode>,
+///         _latency: Duration,
+///     ) {
+///         if status.map_or(true, |status| !status.is_success()) {
+///             self.0.fetch_add(1, Ordering::Relaxed);
+///         }
+///     }
+/// }
+///
+/// let client = Client::builder("my-api-key".to_string())
+///     .metrics_observer(ErrorCounter::default())
+///     .try_build()
+///     .unwrap();
+/// ```
+pub trait MetricsObserver: Send + Sync {
+    /// Called when a request is started, before any throttling delay.
+    fn request_started(&self, method: &Method, path: &str) {
+        let _ = (method, path);
+    }
+
+    /// Called when a request is completed, after all retries.
+    ///
+    /// The `status` is `None` if no response was received. The `latency` includes the
+    /// throttling delay and all retries.
+    fn request_completed(
+        &self,
+        method: &Method,
+        path: &str,
+        status: Option<StatusCode>,
+        latency: Duration,
+    ) {
+        let _ = (method, path, status, latency);
+    }
+
+    /// Called when a request is retried after a transient error, with the number of the
+    /// attempt that failed, starting from 1.
+    fn request_retried(&self, method: &Method, path: &str, attempt: u32) {
+        let _ = (method, path, attempt);
+    }
+
+    /// Called when a request is rejected with `429 Too Many Requests`.
+    fn rate_limited(&self, method: &Method, path: &str) {
+        let _ = (method, path);
+    }
+
+    /// Called when a response carried rate limit information.
+    fn rate_updated(&self, rate: &Rate) {
+        let _ = rate;
+    }
+}
+
+impl<T: MetricsObserver + ?Sized> MetricsObserver for Arc<T> {
+    fn request_started(&self, method: &Method, path: &str) {
+        (**self).request_started(method, path)
+    }
+
+    fn request_completed(
+        &self,
+        method: &Method,
+        path: &str,
+        status: Option<StatusCode>,
+        latency: Duration,
+    ) {
+        (**self).request_completed(method, path, status, latency)
+    }
+
+    fn request_retried(&self, method: &Method, path: &str, attempt: u32) {
+        (**self).request_retried(method, path, attempt)
+    }
+
+    fn rate_limited(&self, method: &Method, path: &str) {
+        (**self).rate_limited(method, path)
+    }
+
+    fn rate_updated(&self, rate: &Rate) {
+        (**self).rate_updated(rate)
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use reqwest::{Request, Response};
+
+/// A hook into the requests sent by the `Client`.
+///
+/// Middleware can mutate every outgoing request before it is sent, for example to add custom
+/// authentication headers, and observe every received response, for example for audit logging.
+/// Both methods are called once per attempt, so a request that is retried is seen again.
+///
+/// Middleware is registered with `ClientBuilder::middleware`, and runs in the order of
+/// registration. For simple cases, `ClientBuilder::on_request` and `ClientBuilder::on_response`
+/// register closures instead.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{Client, Middleware};
+/// use reqwest::header::HeaderValue;
+/// use reqwest::{Request, Response};
+///
+/// struct AuditLog;
+///
+/// impl Middleware for AuditLog {
+///     fn on_request(&self, request: &mut Request) {
+///         request
+///             .headers_mut()
+///             .insert("X-Audit-User", HeaderValue::from_static("batch-job"));
+///     }
+///
+///     fn on_response(&self, response: &Response) {
+///         println!("{} {}", response.url(), response.status());
+///     }
+/// }
+///
+/// let client = Client::builder("my-api-key".to_string())
+///     .middleware(AuditLog)
+///     .try_build()
+///     .unwrap();
+/// ```
+pub trait Middleware: Send + Sync {
+    /// Called with every request before it is sent.
+    fn on_request(&self, request: &mut Request) {
+        let _ = request;
+    }
+
+    /// Called with every received response, before its body is read.
+    fn on_response(&self, response: &Response) {
+        let _ = response;
+    }
+}
+
+/// Middleware that calls a closure with every request.
+pub(crate) struct OnRequest<F>(pub(crate) F);
+
+impl<F> Middleware for OnRequest<F>
+where
+    F: Fn(&mut Request) + Send + Sync,
+{
+    fn on_request(&self, request: &mut Request) {
+        (self.0)(request)
+    }
+}
+
+/// Middleware that calls a closure with every response.
+pub(crate) struct OnResponse<F>(pub(crate) F);
+
+impl<F> Middleware for OnResponse<F>
+where
+    F: Fn(&Response) + Send + Sync,
+{
+    fn on_response(&self, response: &Response) {
+        (self.0)(response)
+    }
+}
@@ -0,0 +1,297 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::{validate_ballot, ApiError, Rank, ValidationError};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio_util::sync::CancellationToken;
+
+pub(crate) const HEADER_IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+
+/// Options applied to a single request.
+///
+/// Request options are accepted by the `*_with_options` variants of the `Client` methods,
+/// so a single client instance can serve requests with different requirements, such as
+/// latency-sensitive calls with a short timeout and long running batch jobs.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddclient_rs::{Client, RequestOptions};
+/// use std::time::Duration;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+///     let options = RequestOptions::new()
+///         .timeout(Duration::from_millis(500))
+///         .idempotency_key("create-poll-42".to_string());
+///
+///     let result = client
+///         .create_voting_with_options(["Option 1", "Option 2"], &options)
+///         .await;
+///     // Handle result...
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    timeout: Option<Duration>,
+    headers: HeaderMap,
+    idempotency_key: Option<String>,
+    known_choices: Option<Vec<String>>,
+    deadline: Option<Instant>,
+    cancellation_token: Option<CancellationToken>,
+}
+
+impl RequestOptions {
+    /// Creates new empty `RequestOptions`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the total timeout of the request, overriding the timeout configured on the client.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Adds an extra header to the request.
+    ///
+    /// Extra headers replace the headers of the same name set by the client.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Sets the `Idempotency-Key` header of the request.
+    ///
+    /// The key overrides the key generated by a client with automatic idempotency keys
+    /// enabled, see `ClientBuilder::idempotency_keys`.
+    pub fn idempotency_key(mut self, key: String) -> Self {
+        self.idempotency_key = Some(key);
+        self
+    }
+
+    /// Sets the choices of the voting, known to the application, for the client-side ballot
+    /// validation of `Client::vote_with_options`.
+    ///
+    /// The ballot is validated before it is sent, and ballots ranking other choices are
+    /// rejected with `ApiError::Validation`, even if ballot validation is not enabled with
+    /// `ClientBuilder::validate_ballots`.
+    pub fn known_choices(mut self, choices: Vec<String>) -> Self {
+        self.known_choices = Some(choices);
+        self
+    }
+
+    /// Sets the instant by which the request must complete.
+    ///
+    /// Unlike `RequestOptions::timeout`, which limits every attempt of the request, the
+    /// deadline covers the whole request, including its retries and the backoff delays
+    /// between them. When the deadline passes before a response is received, the request
+    /// is aborted and fails with `ApiError::DeadlineExceeded`.
+    ///
+    /// The deadline applies to the async `Client` only.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets a token which aborts the request when it is cancelled.
+    ///
+    /// When the token is cancelled before a response is received, including during the
+    /// retries of the request and the backoff delays between them, the request is aborted
+    /// and fails with `ApiError::Cancelled`. A single token can be shared by many requests,
+    /// for example to abort all requests of a task when it is shut down.
+    ///
+    /// The cancellation token applies to the async `Client` only.
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Returns the timeout override, if set.
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the extra headers.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the idempotency key, if set.
+    pub fn get_idempotency_key(&self) -> Option<&str> {
+        self.idempotency_key.as_deref()
+    }
+
+    /// Returns the known choices of the voting, if set.
+    pub fn get_known_choices(&self) -> Option<&[String]> {
+        self.known_choices.as_deref()
+    }
+
+    /// Returns the deadline of the request, if set.
+    pub fn get_deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns the cancellation token of the request, if set.
+    pub fn get_cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation_token.as_ref()
+    }
+
+    /// Runs the future until it completes, or fails with `ApiError::Cancelled` or
+    /// `ApiError::DeadlineExceeded` if the request is interrupted first, dropping the
+    /// future.
+    pub(crate) async fn interruptible<F: Future>(&self, future: F) -> Result<F::Output, ApiError> {
+        let cancelled = async {
+            match &self.cancellation_token {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+        let deadline_passed = async {
+            match self.deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            biased;
+            _ = cancelled => Err(ApiError::Cancelled),
+            _ = deadline_passed => Err(ApiError::DeadlineExceeded),
+            output = future => Ok(output),
+        }
+    }
+
+    /// Validates the ballot if `validate` is set or the known choices are set.
+    pub(crate) fn validate_ballot(
+        &self,
+        ballot: &HashMap<String, Rank>,
+        validate: bool,
+    ) -> Result<(), ValidationError> {
+        if validate || self.known_choices.is_some() {
+            validate_ballot(ballot, self.get_known_choices())?;
+        }
+        Ok(())
+    }
+
+    fn resolve_idempotency_key(&self, generate_key: bool) -> Option<String> {
+        match &self.idempotency_key {
+            Some(key) => Some(key.clone()),
+            None if generate_key && !self.headers.contains_key(HEADER_IDEMPOTENCY_KEY) => {
+                Some(generate_idempotency_key())
+            }
+            None => None,
+        }
+    }
+
+    /// Reports whether the request is sent with an idempotency key.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn has_idempotency_key(&self, generate_key: bool) -> bool {
+        generate_key
+            || self.idempotency_key.is_some()
+            || self.headers.contains_key(HEADER_IDEMPOTENCY_KEY)
+    }
+
+    pub(crate) fn none() -> &'static RequestOptions {
+        static NONE: OnceLock<RequestOptions> = OnceLock::new();
+        NONE.get_or_init(RequestOptions::default)
+    }
+
+    /// Applies the options to the request. If `generate_key` is set and the options do not
+    /// contain an idempotency key, a new random key is set.
+    pub(crate) fn apply(
+        &self,
+        mut request: reqwest::RequestBuilder,
+        generate_key: bool,
+    ) -> reqwest::RequestBuilder {
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        if !self.headers.is_empty() {
+            request = request.headers(self.headers.clone());
+        }
+        if let Some(key) = self.resolve_idempotency_key(generate_key) {
+            request = request.header(HEADER_IDEMPOTENCY_KEY, key);
+        }
+        request
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn apply_blocking(
+        &self,
+        mut request: reqwest::blocking::RequestBuilder,
+        generate_key: bool,
+    ) -> reqwest::blocking::RequestBuilder {
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        if !self.headers.is_empty() {
+            request = request.headers(self.headers.clone());
+        }
+        if let Some(key) = self.resolve_idempotency_key(generate_key) {
+            request = request.header(HEADER_IDEMPOTENCY_KEY, key);
+        }
+        request
+    }
+}
+
+/// Reports whether requests with the method get an automatically generated idempotency key.
+pub(crate) fn is_mutating(method: &reqwest::Method) -> bool {
+    matches!(*method, reqwest::Method::POST | reqwest::Method::DELETE)
+}
+
+/// Generates a random 128-bit idempotency key, formatted as 32 hexadecimal digits.
+pub(crate) fn generate_idempotency_key() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+
+    // every RandomState is seeded with different random keys
+    let state = RandomState::new();
+    let high = state.hash_one((count, now, 0u8));
+    let low = state.hash_one((count, now, 1u8));
+
+    format!("{:016x}{:016x}", high, low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idempotency_key_test() {
+        let options = RequestOptions::new();
+        assert_eq!(options.resolve_idempotency_key(false), None);
+
+        let first = options.resolve_idempotency_key(true).unwrap();
+        let second = options.resolve_idempotency_key(true).unwrap();
+        assert_eq!(first.len(), 32);
+        assert_ne!(first, second);
+
+        let options = RequestOptions::new().idempotency_key("key".to_string());
+        assert_eq!(
+            options.resolve_idempotency_key(true).as_deref(),
+            Some("key")
+        );
+
+        let options = RequestOptions::new().header(
+            HeaderName::from_static("idempotency-key"),
+            HeaderValue::from_static("key"),
+        );
+        assert_eq!(options.resolve_idempotency_key(true), None);
+    }
+}
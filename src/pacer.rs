@@ -0,0 +1,134 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::Rate;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Paces requests by the rate limit of the API, like a semaphore with a permit for each
+/// remaining request.
+///
+/// Every `RatePacer::acquire` takes one of the remaining requests of the last known `Rate`,
+/// and suspends when none remain until the rate limit resets. Until a rate is known, the
+/// requests are not paced.
+///
+/// Set on a client with `ClientBuilder::rate_pacer`, the pacer is acquired before every
+/// request and updated from every response. Clones of a `RatePacer` share the remaining
+/// requests, so a pacer can be shared by several clients using the same API key, or used
+/// by applications to schedule their own work.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddclient_rs::{Client, RatePacer};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let pacer = RatePacer::new();
+///     let client = Client::builder("my-api-key".to_string())
+///         .rate_pacer(pacer.clone())
+///         .try_build()
+///         .unwrap();
+///
+///     for id in ["a", "b", "c"] {
+///         // Requests wait here for the reset when the rate limit is exhausted.
+///         let _ = client.get_voting(id).await;
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RatePacer {
+    rate: Arc<Mutex<Option<Rate>>>,
+}
+
+impl RatePacer {
+    /// Creates a new `RatePacer` without a known rate limit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the known rate limit, usually with the rate of the latest response.
+    pub fn update(&self, rate: &Rate) {
+        *self.rate.lock().unwrap() = Some(rate.clone());
+    }
+
+    /// Returns the number of requests which can be acquired before the rate limit resets,
+    /// or `None` if the rate limit is not known.
+    pub fn remaining(&self) -> Option<u32> {
+        self.rate
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|rate| rate.remaining)
+    }
+
+    /// Waits until a request can be sent, and takes one of the remaining requests.
+    pub async fn acquire(&self) {
+        while let Some(wait) = self.try_acquire() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Takes one of the remaining requests, or returns how long to wait for the reset.
+    fn try_acquire(&self) -> Option<Duration> {
+        let mut rate = self.rate.lock().unwrap();
+        let rate = rate.as_mut().filter(|rate| rate.limit > 0)?;
+        if rate.remaining == 0 {
+            let wait = rate.reset_in();
+            if !wait.is_zero() {
+                return Some(wait);
+            }
+            rate.remaining = rate.limit;
+        }
+        rate.remaining -= 1;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn rate(remaining: u32, reset_in: u64) -> Rate {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        Rate {
+            limit: 10,
+            remaining,
+            reset: now + reset_in,
+            retry: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn acquire_test() {
+        let pacer = RatePacer::new();
+
+        // the requests are not paced without a rate
+        pacer.acquire().await;
+        assert_eq!(pacer.remaining(), None);
+
+        pacer.update(&rate(2, 60));
+        pacer.acquire().await;
+        pacer.clone().acquire().await;
+        assert_eq!(pacer.remaining(), Some(0));
+
+        let wait = tokio::time::timeout(Duration::from_millis(50), pacer.acquire()).await;
+        assert!(wait.is_err());
+    }
+
+    #[tokio::test]
+    async fn reset_test() {
+        let pacer = RatePacer::new();
+
+        // the remaining requests are restored once the rate limit reset
+        pacer.update(&rate(0, 0));
+        pacer.acquire().await;
+        assert_eq!(pacer.remaining(), Some(9));
+    }
+}
@@ -0,0 +1,397 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::{ChoiceIndex, Rank, VotingResults};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Represents the pairwise preferences of a voting.
+///
+/// For every ordered pair of choices `(a, b)`, the matrix contains the number of voters who
+/// prefer choice `a` over choice `b`. The matrix can be created from the duels of the voting
+/// results with `PairwiseMatrix::from_results`, or computed from ballots with
+/// `PairwiseMatrix::from_ballots`.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{PairwiseMatrix, Rank};
+/// use std::collections::HashMap;
+///
+/// let choices = vec!["Einstein".to_string(), "Newton".to_string()];
+/// let ballots = [
+///     HashMap::from([("Einstein".to_string(), Rank::HIGHEST)]),
+///     HashMap::from([("Newton".to_string(), Rank::HIGHEST)]),
+///     HashMap::from([("Newton".to_string(), Rank::HIGHEST)]),
+/// ];
+///
+/// let matrix = PairwiseMatrix::from_ballots(&choices, &ballots);
+/// assert_eq!(matrix.get("Newton", "Einstein"), Some(2));
+/// assert_eq!(matrix.get("Einstein", "Newton"), Some(1));
+/// assert_eq!(matrix.margin("Newton", "Einstein"), Some(1));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairwiseMatrix {
+    choices: Vec<String>,
+    preferences: Vec<Vec<isize>>,
+}
+
+impl PairwiseMatrix {
+    /// Creates the matrix from the duels of voting results, as returned by
+    /// `Client::get_voting_results_duels`.
+    ///
+    /// Choices are ordered by their index. Pairs of choices without a duel in the results,
+    /// for example when the results were requested without duels, have no preferences.
+    pub fn from_results(results: &VotingResults) -> Self {
+        let n = results
+            .results
+            .iter()
+            .map(|result| result.index.get() + 1)
+            .max()
+            .unwrap_or(0);
+
+        let mut choices = vec![String::new(); n];
+        for result in &results.results {
+            choices[result.index.get()] = result.choice.clone();
+        }
+
+        let mut preferences = vec![vec![0; n]; n];
+        for duel in results.duels.iter().flatten() {
+            let (l, r) = (duel.left.index.get(), duel.right.index.get());
+            if l < n && r < n {
+                preferences[l][r] = duel.left.strength;
+                preferences[r][l] = duel.right.strength;
+            }
+        }
+
+        Self {
+            choices,
+            preferences,
+        }
+    }
+
+    /// Computes the matrix from ballots, without requesting the results from the API.
+    ///
+    /// Choices that are not ranked on a ballot are considered less preferred than all ranked
+    /// choices, and choices that are not in `choices` are ignored.
+    pub fn from_ballots(choices: &[String], ballots: &[HashMap<String, Rank>]) -> Self {
+        let ballots: Vec<&HashMap<String, Rank>> = ballots.iter().collect();
+        let preferences = preferences(choices, &ballots)
+            .into_iter()
+            .map(|row| row.into_iter().map(|count| count as isize).collect())
+            .collect();
+
+        Self {
+            choices: choices.to_vec(),
+            preferences,
+        }
+    }
+
+    /// Returns the choices of the matrix, ordered by their index.
+    pub fn choices(&self) -> &[String] {
+        &self.choices
+    }
+
+    /// Returns the number of voters preferring choice `a` over choice `b`, or `None` if any
+    /// of the choices is not in the matrix.
+    pub fn get(&self, a: &str, b: &str) -> Option<isize> {
+        let a = self.index_of(a)?;
+        let b = self.index_of(b)?;
+        self.get_index(a, b)
+    }
+
+    /// Returns the number of voters preferring the choice at index `a` over the choice at
+    /// index `b`, or `None` if any of the indexes is out of bounds.
+    pub fn get_index(&self, a: ChoiceIndex, b: ChoiceIndex) -> Option<isize> {
+        self.preferences.get(a.get())?.get(b.get()).copied()
+    }
+
+    /// Returns by how many voters choice `a` is preferred over choice `b`, which is negative
+    /// if choice `b` is preferred, or `None` if any of the choices is not in the matrix.
+    pub fn margin(&self, a: &str, b: &str) -> Option<isize> {
+        Some(self.get(a, b)? - self.get(b, a)?)
+    }
+
+    /// Returns the Condorcet winner, the choice which is preferred over every other choice
+    /// by more voters than the other choice, or `None` if there is no such choice.
+    ///
+    /// A voting has at most one Condorcet winner, and it is always the winner of the
+    /// Schulze method. A voting with a single choice has that choice as the Condorcet winner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddclient_rs::{PairwiseMatrix, Rank};
+    /// use std::collections::HashMap;
+    ///
+    /// let choices = vec!["Einstein".to_string(), "Newton".to_string(), "Bohr".to_string()];
+    /// let ballots = [
+    ///     HashMap::from([("Newton".to_string(), Rank::HIGHEST)]),
+    ///     HashMap::from([("Newton".to_string(), Rank::HIGHEST)]),
+    ///     HashMap::from([("Bohr".to_string(), Rank::HIGHEST)]),
+    /// ];
+    ///
+    /// let matrix = PairwiseMatrix::from_ballots(&choices, &ballots);
+    /// assert_eq!(matrix.condorcet_winner(), Some("Newton"));
+    /// ```
+    pub fn condorcet_winner(&self) -> Option<&str> {
+        let index = self.condorcet_winner_index()?;
+        Some(&self.choices[index.get()])
+    }
+
+    /// Returns the index of the Condorcet winner, or `None` if there is no Condorcet winner.
+    ///
+    /// See `PairwiseMatrix::condorcet_winner`.
+    pub fn condorcet_winner_index(&self) -> Option<ChoiceIndex> {
+        let n = self.choices.len();
+        (0..n)
+            .find(|&i| (0..n).all(|j| i == j || self.preferences[i][j] > self.preferences[j][i]))
+            .map(ChoiceIndex::new)
+    }
+
+    /// Renders the matrix as a directed graph in the Graphviz DOT language, to visualize
+    /// the beat-path structure of the voting.
+    ///
+    /// Every choice is a node, and there is an edge from choice `a` to choice `b` if more
+    /// voters prefer `a` over `b` than `b` over `a`. The edge is labeled and weighted with
+    /// the number of voters preferring `a` over `b`. Tied pairs with preferences are
+    /// connected by a dashed edge without a direction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddclient_rs::{PairwiseMatrix, Rank};
+    /// use std::collections::HashMap;
+    ///
+    /// let choices = vec!["Einstein".to_string(), "Newton".to_string()];
+    /// let ballots = [HashMap::from([("Newton".to_string(), Rank::HIGHEST)])];
+    ///
+    /// let dot = PairwiseMatrix::from_ballots(&choices, &ballots).to_dot();
+    /// assert!(dot.contains("\"Newton\" -> \"Einstein\" [label=1, weight=1];"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph duels {\n");
+        for choice in &self.choices {
+            let _ = writeln!(out, "    {};", dot_id(choice));
+        }
+
+        let n = self.choices.len();
+        for i in 0..n {
+            for j in 0..n {
+                let (a, b) = (self.preferences[i][j], self.preferences[j][i]);
+                let (from, to) = (dot_id(&self.choices[i]), dot_id(&self.choices[j]));
+                if a > b {
+                    let _ = writeln!(out, "    {} -> {} [label={}, weight={}];", from, to, a, a);
+                } else if a == b && a > 0 && i < j {
+                    let _ = writeln!(
+                        out,
+                        "    {} -> {} [label={}, dir=none, style=dashed];",
+                        from, to, a
+                    );
+                }
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn index_of(&self, choice: &str) -> Option<ChoiceIndex> {
+        self.choices
+            .iter()
+            .position(|c| c == choice)
+            .map(ChoiceIndex::new)
+    }
+}
+
+/// Quotes a choice as a DOT identifier.
+fn dot_id(choice: &str) -> String {
+    format!("\"{}\"", choice.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Computes the pairwise preferences of ballots, where `preferences[i][j]` is the number of
+/// voters preferring choice `i` over choice `j`.
+///
+/// Choices that are not ranked on a ballot are considered less preferred than all ranked
+/// choices.
+pub(crate) fn preferences(
+    choices: &[String],
+    ballots: &[&HashMap<String, Rank>],
+) -> Vec<Vec<usize>> {
+    let n = choices.len();
+
+    let mut preferences = vec![vec![0usize; n]; n];
+    for ballot in ballots {
+        let ranks: Vec<u32> = choices
+            .iter()
+            .map(|choice| ballot.get(choice).map_or(u32::MAX, |rank| rank.get()))
+            .collect();
+        for i in 0..n {
+            for j in 0..n {
+                if ranks[i] < ranks[j] {
+                    preferences[i][j] += 1;
+                }
+            }
+        }
+    }
+    preferences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChoiceStrength, Duels, VotingResult};
+
+    fn result(choice: &str, index: usize) -> VotingResult {
+        VotingResult {
+            choice: choice.to_string(),
+            index: ChoiceIndex::new(index),
+            wins: 0,
+            percentage: Default::default(),
+            strength: 0,
+            advantage: 0,
+        }
+    }
+
+    fn strength(choice: &str, index: usize, strength: isize) -> ChoiceStrength {
+        ChoiceStrength {
+            index: ChoiceIndex::new(index),
+            choice: choice.to_string(),
+            strength,
+        }
+    }
+
+    #[test]
+    fn from_results_test() {
+        let results = VotingResults {
+            tie: false,
+            results: vec![result("Kant", 1), result("Spinoza", 0), result("Hume", 2)],
+            duels: Some(vec![
+                Duels {
+                    left: strength("Kant", 1, 3),
+                    right: strength("Spinoza", 0, 1),
+                },
+                Duels {
+                    left: strength("Kant", 1, 2),
+                    right: strength("Hume", 2, 2),
+                },
+            ]),
+        };
+
+        let matrix = PairwiseMatrix::from_results(&results);
+
+        assert_eq!(matrix.choices(), ["Spinoza", "Kant", "Hume"]);
+        assert_eq!(matrix.get("Kant", "Spinoza"), Some(3));
+        assert_eq!(matrix.get("Spinoza", "Kant"), Some(1));
+        assert_eq!(matrix.margin("Kant", "Hume"), Some(0));
+        // no duel between Spinoza and Hume
+        assert_eq!(matrix.get("Spinoza", "Hume"), Some(0));
+        assert_eq!(matrix.get("Kant", "Locke"), None);
+        assert_eq!(
+            matrix.get_index(ChoiceIndex::new(1), ChoiceIndex::new(0)),
+            Some(3)
+        );
+        assert_eq!(
+            matrix.get_index(ChoiceIndex::new(3), ChoiceIndex::new(0)),
+            None
+        );
+    }
+
+    #[test]
+    fn from_ballots_test() {
+        let choices: Vec<String> = ["A", "B", "C"].iter().map(|c| c.to_string()).collect();
+        let second = Rank::new(2).unwrap();
+        let ballots = [
+            HashMap::from([("A".to_string(), Rank::HIGHEST), ("B".to_string(), second)]),
+            HashMap::from([("C".to_string(), Rank::HIGHEST), ("A".to_string(), second)]),
+            HashMap::from([("B".to_string(), Rank::HIGHEST), ("D".to_string(), second)]),
+        ];
+
+        let matrix = PairwiseMatrix::from_ballots(&choices, &ballots);
+
+        assert_eq!(matrix.get("A", "B"), Some(2));
+        assert_eq!(matrix.get("B", "A"), Some(1));
+        assert_eq!(matrix.get("A", "C"), Some(1));
+        assert_eq!(matrix.get("C", "A"), Some(1));
+        assert_eq!(matrix.margin("B", "C"), Some(1));
+        assert_eq!(matrix.get("A", "A"), Some(0));
+        assert_eq!(matrix.get("D", "A"), None);
+    }
+
+    #[test]
+    fn condorcet_winner_test() {
+        let choices: Vec<String> = ["A", "B", "C"].iter().map(|c| c.to_string()).collect();
+        let second = Rank::new(2).unwrap();
+        let third = Rank::new(3).unwrap();
+        let ballot = |a: Rank, b: Rank, c: Rank| {
+            HashMap::from([
+                ("A".to_string(), a),
+                ("B".to_string(), b),
+                ("C".to_string(), c),
+            ])
+        };
+
+        // B beats A and C head-to-head
+        let ballots = [
+            ballot(Rank::HIGHEST, second, third),
+            ballot(second, Rank::HIGHEST, third),
+            ballot(third, Rank::HIGHEST, second),
+        ];
+        let matrix = PairwiseMatrix::from_ballots(&choices, &ballots);
+        assert_eq!(matrix.condorcet_winner(), Some("B"));
+        assert_eq!(matrix.condorcet_winner_index(), Some(ChoiceIndex::new(1)));
+
+        // a cycle: A beats B, B beats C, C beats A
+        let ballots = [
+            ballot(Rank::HIGHEST, second, third),
+            ballot(third, Rank::HIGHEST, second),
+            ballot(second, third, Rank::HIGHEST),
+        ];
+        let matrix = PairwiseMatrix::from_ballots(&choices, &ballots);
+        assert_eq!(matrix.condorcet_winner(), None);
+
+        // a tie between A and B
+        let ballots = [
+            ballot(Rank::HIGHEST, second, third),
+            ballot(second, Rank::HIGHEST, third),
+        ];
+        let matrix = PairwiseMatrix::from_ballots(&choices, &ballots);
+        assert_eq!(matrix.condorcet_winner(), None);
+
+        let matrix = PairwiseMatrix::from_ballots(&choices[..1], &[]);
+        assert_eq!(matrix.condorcet_winner(), Some("A"));
+        assert_eq!(
+            PairwiseMatrix::from_ballots(&[], &[]).condorcet_winner(),
+            None
+        );
+    }
+
+    #[test]
+    fn to_dot_test() {
+        let choices: Vec<String> = ["A", "B \"2\"", "C"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let ballots = [
+            HashMap::from([("A".to_string(), Rank::HIGHEST)]),
+            HashMap::from([("A".to_string(), Rank::HIGHEST)]),
+            HashMap::from([("C".to_string(), Rank::HIGHEST)]),
+            HashMap::from([("B \"2\"".to_string(), Rank::HIGHEST)]),
+        ];
+
+        let matrix = PairwiseMatrix::from_ballots(&choices, &ballots);
+
+        // B and C are tied
+        assert_eq!(
+            matrix.to_dot(),
+            "digraph duels {\n    \"A\";\n    \"B \\\"2\\\"\";\n    \"C\";\n    \
+             \"A\" -> \"B \\\"2\\\"\" [label=2, weight=2];\n    \
+             \"A\" -> \"C\" [label=2, weight=2];\n    \
+             \"B \\\"2\\\"\" -> \"C\" [label=1, dir=none, style=dashed];\n}\n"
+        );
+
+        let matrix = PairwiseMatrix::from_ballots(&choices[..1], &[]);
+        assert_eq!(matrix.to_dot(), "digraph duels {\n    \"A\";\n}\n");
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+/// The type used for percentages in voting results.
+///
+/// By default percentages are deserialized into `f32`. With the `decimal` feature enabled,
+/// they are deserialized into `rust_decimal::Decimal`, which avoids binary floating point
+/// surprises when percentages are summed or compared.
+#[cfg(not(feature = "decimal"))]
+pub type Percentage = f32;
+
+/// The type used for percentages in voting results.
+///
+/// By default percentages are deserialized into `f32`. With the `decimal` feature enabled,
+/// they are deserialized into `rust_decimal::Decimal`, which avoids binary floating point
+/// surprises when percentages are summed or compared.
+#[cfg(feature = "decimal")]
+pub type Percentage = rust_decimal::Decimal;
+
+/// Formats a percentage rounded to the given number of decimal places.
+///
+/// Rounding is always performed on the decimal representation of the value, with midpoints
+/// rounded away from zero, so the output is the same regardless of whether the `decimal`
+/// feature is enabled. For example, `2.675` is always formatted as `2.68` with two decimals.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::format_percentage;
+///
+/// assert_eq!(format_percentage(50.0, 2), "50.00");
+/// assert_eq!(format_percentage(33.335, 2), "33.34");
+/// ```
+#[cfg(not(feature = "decimal"))]
+pub fn format_percentage(value: Percentage, decimals: u32) -> String {
+    // The Display implementation of f32 produces the shortest decimal representation that
+    // round-trips, which is the value as it was sent by the API.
+    round_decimal_str(&value.to_string(), decimals as usize)
+}
+
+/// Formats a percentage rounded to the given number of decimal places.
+///
+/// Rounding is always performed on the decimal representation of the value, with midpoints
+/// rounded away from zero, so the output is the same regardless of whether the `decimal`
+/// feature is enabled. For example, `2.675` is always formatted as `2.68` with two decimals.
+#[cfg(feature = "decimal")]
+pub fn format_percentage(value: Percentage, decimals: u32) -> String {
+    let rounded = value.round_dp_with_strategy(
+        decimals,
+        rust_decimal::RoundingStrategy::MidpointAwayFromZero,
+    );
+    format!("{:.*}", decimals as usize, rounded)
+}
+
+/// Returns `part` as a percentage of `total`, or zero if `total` is zero.
+#[cfg(not(feature = "decimal"))]
+pub(crate) fn percentage_of(part: usize, total: usize) -> Percentage {
+    if total == 0 {
+        return 0.0;
+    }
+    part as f32 * 100.0 / total as f32
+}
+
+/// Returns `part` as a percentage of `total`, or zero if `total` is zero.
+#[cfg(feature = "decimal")]
+pub(crate) fn percentage_of(part: usize, total: usize) -> Percentage {
+    if total == 0 {
+        return Percentage::ZERO;
+    }
+    Percentage::from(part) * Percentage::ONE_HUNDRED / Percentage::from(total)
+}
+
+#[cfg(not(feature = "decimal"))]
+fn round_decimal_str(value: &str, decimals: usize) -> String {
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value),
+    };
+    let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+
+    let mut kept: Vec<u8> = int_part
+        .bytes()
+        .chain(
+            frac_part
+                .bytes()
+                .chain(std::iter::repeat(b'0'))
+                .take(decimals),
+        )
+        .map(|b| b - b'0')
+        .collect();
+
+    if frac_part
+        .as_bytes()
+        .get(decimals)
+        .is_some_and(|&b| b >= b'5')
+    {
+        let mut carry = true;
+        for digit in kept.iter_mut().rev() {
+            if *digit == 9 {
+                *digit = 0;
+            } else {
+                *digit += 1;
+                carry = false;
+                break;
+            }
+        }
+        if carry {
+            kept.insert(0, 1);
+        }
+    }
+
+    let int_len = kept.len() - decimals;
+    let mut out = String::with_capacity(kept.len() + 2);
+    if negative && kept.iter().any(|&d| d != 0) {
+        out.push('-');
+    }
+    for (i, digit) in kept.iter().enumerate() {
+        if i == int_len {
+            out.push('.');
+        }
+        out.push((digit + b'0') as char);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "decimal"))]
+    fn percentage(value: &str) -> Percentage {
+        value.parse().unwrap()
+    }
+
+    #[cfg(feature = "decimal")]
+    fn percentage(value: &str) -> Percentage {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn format_percentage_test() {
+        let test_cases = vec![
+            ("50", 2, "50.00"),
+            ("33.335", 2, "33.34"),
+            ("2.675", 2, "2.68"),
+            ("66.666", 1, "66.7"),
+            ("49.5", 0, "50"),
+            ("99.96", 1, "100.0"),
+            ("0.004", 2, "0.00"),
+            ("12.5", 3, "12.500"),
+        ];
+
+        for (value, decimals, expected) in test_cases {
+            assert_eq!(
+                format_percentage(percentage(value), decimals),
+                expected,
+                "formatting {} with {} decimals",
+                value,
+                decimals
+            );
+        }
+    }
+}
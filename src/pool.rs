@@ -0,0 +1,153 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::http_config::HttpConfig;
+use crate::{BuildError, Client, ClientBuilder};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+type Configure = dyn Fn(ClientBuilder) -> ClientBuilder + Send + Sync;
+
+/// A pool of clients keyed by their API token, for applications acting on behalf of many
+/// API keys.
+///
+/// The clients are built when their token is first requested and cached for the following
+/// requests. All clients share the Reqwest client of the pool, and with it a single
+/// connection pool, instead of opening connections for each API key.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddclient_rs::{ClientPool, RetryPolicy};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let pool =
+///         ClientPool::new().configure(|builder| builder.retry_policy(RetryPolicy::default()));
+///
+///     let client = pool.get("customer-api-key").unwrap();
+///     let voting = client.get_voting("voting_id").await;
+///     // Handle result...
+/// }
+/// ```
+pub struct ClientPool {
+    http: reqwest::Client,
+    configure: Arc<Configure>,
+    clients: Mutex<HashMap<String, Client>>,
+}
+
+impl ClientPool {
+    /// Creates a new empty `ClientPool` with a default Reqwest client.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the Reqwest client cannot be created. Use `ClientPool::with_reqwest_client`
+    /// to handle this error instead.
+    pub fn new() -> Self {
+        let http = HttpConfig::default()
+            .build()
+            .expect("Invalid client configuration");
+        Self::with_reqwest_client(http)
+    }
+
+    /// Creates a new empty `ClientPool` whose clients share the given Reqwest client.
+    pub fn with_reqwest_client(client: reqwest::Client) -> Self {
+        Self {
+            http: client,
+            configure: Arc::new(|builder| builder),
+            clients: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets a function applied to the builder of every client of the pool, for example to
+    /// set the API URL or a retry policy.
+    ///
+    /// The builder already has the Reqwest client of the pool, which the function should
+    /// not replace, so the connections remain shared. Clients built before the function
+    /// was set are not changed.
+    pub fn configure(
+        mut self,
+        configure: impl Fn(ClientBuilder) -> ClientBuilder + Send + Sync + 'static,
+    ) -> Self {
+        self.configure = Arc::new(configure);
+        self
+    }
+
+    /// Returns the client of the API token, building it on the first request.
+    ///
+    /// Returns an `Err` with a `BuildError` if the token is invalid, or if the configured
+    /// builder fails to build the client.
+    pub fn get(&self, token: &str) -> Result<Client, BuildError> {
+        let mut clients = self.clients.lock().unwrap();
+        if let Some(client) = clients.get(token) {
+            return Ok(client.clone());
+        }
+
+        let builder = Client::builder(token.to_string()).reqwest_client(self.http.clone());
+        let client = (self.configure)(builder).try_build()?;
+        clients.insert(token.to_string(), client.clone());
+        Ok(client)
+    }
+
+    /// Removes the client of the API token, for example when the key is revoked. Returns
+    /// whether the pool had a client for the token.
+    ///
+    /// Clones of the removed client remain usable.
+    pub fn remove(&self, token: &str) -> bool {
+        self.clients.lock().unwrap().remove(token).is_some()
+    }
+
+    /// Returns the number of clients in the pool.
+    pub fn len(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    /// Reports whether the pool has no clients.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for ClientPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Formats the pool without the API tokens of its clients.
+impl fmt::Debug for ClientPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientPool")
+            .field("clients", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_test() {
+        let pool = ClientPool::new()
+            .configure(|builder| builder.api_url("http://localhost:8080".to_string()));
+        assert!(pool.is_empty());
+
+        let first = pool.get("first-token").unwrap();
+        pool.get("second-token").unwrap();
+        assert_eq!(pool.len(), 2);
+        assert!(format!("{:?}", first).contains("http://localhost:8080/"));
+
+        // the cached client is returned again
+        pool.get("first-token").unwrap();
+        assert_eq!(pool.len(), 2);
+
+        assert!(matches!(pool.get(""), Err(BuildError::InvalidToken)));
+        assert!(pool.remove("first-token"));
+        assert!(!pool.remove("first-token"));
+        assert_eq!(format!("{:?}", pool), "ClientPool { clients: 1, .. }");
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::{MetricsObserver, Rate};
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry};
+use reqwest::{Method, StatusCode};
+use std::time::Duration;
+
+/// A `MetricsObserver` that records Prometheus metrics.
+///
+/// The following metrics are registered against the given `prometheus::Registry`:
+///
+/// - `dd_requests_total` - Counter of completed requests, labeled by `method` and `status`.
+///   The status is `error` if no response was received.
+/// - `dd_request_duration_seconds` - Histogram of request latencies, including retries,
+///   labeled by `method`.
+/// - `dd_rate_remaining` - Gauge of the remaining requests in the current rate limit window.
+///
+/// This type is available with the `prometheus` feature.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{Client, PrometheusMetrics};
+/// use prometheus::Registry;
+///
+/// let registry = Registry::new();
+/// let client = Client::builder("my-api-key".to_string())
+///     .metrics_observer(PrometheusMetrics::new(&registry).unwrap())
+///     .try_build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrometheusMetrics {
+    requests: IntCounterVec,
+    duration: HistogramVec,
+    rate_remaining: IntGauge,
+}
+
+impl PrometheusMetrics {
+    /// Creates the metrics and registers them against the `registry`.
+    ///
+    /// Returns an error if metrics with the same names are already registered.
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let requests = IntCounterVec::new(
+            Opts::new(
+                "dd_requests_total",
+                "Total number of Direct Decisions API requests.",
+            ),
+            &["method", "status"],
+        )?;
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "dd_request_duration_seconds",
+                "Duration of Direct Decisions API requests in seconds.",
+            ),
+            &["method"],
+        )?;
+        let rate_remaining = IntGauge::new(
+            "dd_rate_remaining",
+            "Remaining Direct Decisions API requests in the current rate limit window.",
+        )?;
+
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(duration.clone()))?;
+        registry.register(Box::new(rate_remaining.clone()))?;
+
+        Ok(Self {
+            requests,
+            duration,
+            rate_remaining,
+        })
+    }
+}
+
+impl MetricsObserver for PrometheusMetrics {
+    fn request_completed(
+        &self,
+        method: &Method,
+        _path: &str,
+        status: Option<StatusCode>,
+        latency: Duration,
+    ) {
+        let status = status.map_or_else(|| "error".to_string(), |s| s.as_u16().to_string());
+        self.requests
+            .with_label_values(&[method.as_str(), &status])
+            .inc();
+        self.duration
+            .with_label_values(&[method.as_str()])
+            .observe(latency.as_secs_f64());
+    }
+
+    fn rate_updated(&self, rate: &Rate) {
+        self.rate_remaining.set(rate.remaining.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_test() {
+        let registry = Registry::new();
+        let metrics = PrometheusMetrics::new(&registry).unwrap();
+
+        metrics.request_completed(
+            &Method::GET,
+            "v1/votings/id",
+            Some(StatusCode::OK),
+            Duration::from_millis(20),
+        );
+        metrics.request_completed(&Method::GET, "v1/votings/id", None, Duration::ZERO);
+        metrics.rate_updated(&Rate {
+            limit: 100,
+            remaining: 42,
+            reset: 0,
+            retry: 0,
+        });
+
+        assert_eq!(metrics.requests.with_label_values(&["GET", "200"]).get(), 1);
+        assert_eq!(
+            metrics.requests.with_label_values(&["GET", "error"]).get(),
+            1
+        );
+        assert_eq!(
+            metrics
+                .duration
+                .with_label_values(&["GET"])
+                .get_sample_count(),
+            2
+        );
+        assert_eq!(metrics.rate_remaining.get(), 42);
+
+        // registering the same metrics twice fails
+        assert!(PrometheusMetrics::new(&registry).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::options::generate_idempotency_key;
+use crate::{ApiError, Client, Rank, RequestOptions};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A vote or unvote operation waiting in a `VoteQueue`.
+///
+/// Every operation gets an idempotency key when it is enqueued, which is sent with every
+/// attempt to flush it, so an operation is applied once even if its response was lost.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "operation", rename_all = "lowercase")]
+pub enum QueuedOperation {
+    /// Submits the ballot of the voter, see `Client::vote`.
+    Vote {
+        voting_id: String,
+        voter_id: String,
+        ballot: HashMap<String, Rank>,
+        idempotency_key: String,
+    },
+    /// Removes the ballot of the voter, see `Client::unvote`.
+    Unvote {
+        voting_id: String,
+        voter_id: String,
+        idempotency_key: String,
+    },
+}
+
+impl QueuedOperation {
+    /// Returns the ID of the voting the operation refers to.
+    pub fn voting_id(&self) -> &str {
+        match self {
+            QueuedOperation::Vote { voting_id, .. } | QueuedOperation::Unvote { voting_id, .. } => {
+                voting_id
+            }
+        }
+    }
+
+    /// Returns the ID of the voter the operation refers to.
+    pub fn voter_id(&self) -> &str {
+        match self {
+            QueuedOperation::Vote { voter_id, .. } | QueuedOperation::Unvote { voter_id, .. } => {
+                voter_id
+            }
+        }
+    }
+
+    /// Returns the idempotency key sent with the operation.
+    pub fn idempotency_key(&self) -> &str {
+        match self {
+            QueuedOperation::Vote {
+                idempotency_key, ..
+            }
+            | QueuedOperation::Unvote {
+                idempotency_key, ..
+            } => idempotency_key,
+        }
+    }
+
+    async fn send(&self, client: &Client) -> Result<(), ApiError> {
+        let options = RequestOptions::new().idempotency_key(self.idempotency_key().to_string());
+        match self {
+            QueuedOperation::Vote {
+                voting_id,
+                voter_id,
+                ballot,
+                ..
+            } => client
+                .vote_with_options(voting_id, voter_id, ballot.clone(), &options)
+                .await
+                .map(|_| ()),
+            QueuedOperation::Unvote {
+                voting_id,
+                voter_id,
+                ..
+            } => {
+                client
+                    .unvote_with_options(voting_id, voter_id, &options)
+                    .await
+            }
+        }
+    }
+}
+
+/// The outcome of flushing a `VoteQueue` with `VoteQueue::flush`.
+#[derive(Debug, Default)]
+pub struct FlushReport {
+    /// The number of operations applied by the API.
+    pub sent: usize,
+    /// The operations rejected by the API, which were removed from the queue, with their
+    /// errors.
+    pub rejected: Vec<(QueuedOperation, ApiError)>,
+    /// The error which stopped the flush, such as a connection error or an exhausted rate
+    /// limit, or `None` if the queue was flushed completely.
+    pub stopped: Option<ApiError>,
+    /// The number of operations left in the queue.
+    pub remaining: usize,
+}
+
+impl FlushReport {
+    /// Reports whether the queue was flushed completely and no operation was rejected.
+    pub fn is_success(&self) -> bool {
+        self.rejected.is_empty() && self.stopped.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct QueueFile<'a> {
+    operations: Cow<'a, [QueuedOperation]>,
+}
+
+/// A file-backed queue of vote and unvote operations, for applications which keep
+/// accepting ballots while the API is unreachable or rate limited.
+///
+/// Operations are written to the queue file as JSON when they are enqueued, so they
+/// survive restarts of the application, and are sent in order by `VoteQueue::flush`.
+/// A flush stops at the first operation which fails with an error other than a rejection
+/// by the API, such as a connection error, and keeps it and the following operations for
+/// the next flush. Operations rejected by the API, for example with
+/// `ApiError::BadRequest`, would fail again, so they are removed and returned in the
+/// `FlushReport`. Transient errors are also retried within a flush by the `RetryPolicy` of
+/// the client, if one is set.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddclient_rs::{Client, Rank, VoteQueue};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+///     let mut queue = VoteQueue::open("votes.json").unwrap();
+///
+///     queue
+///         .enqueue_vote("voting_id", "voter_id", [("Option 1", Rank::HIGHEST)])
+///         .unwrap();
+///
+///     let report = queue.flush(&client).await.unwrap();
+///     println!("{} sent, {} remaining", report.sent, report.remaining);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct VoteQueue {
+    path: PathBuf,
+    operations: Vec<QueuedOperation>,
+}
+
+impl VoteQueue {
+    /// Opens the queue stored in the file at `path`, or creates an empty queue if the file
+    /// does not exist. The file is created when the first operation is enqueued.
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let operations = match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice::<QueueFile>(&data)?
+                .operations
+                .into_owned(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self { path, operations })
+    }
+
+    /// Enqueues the ballot of a voter, and writes the queue to its file.
+    ///
+    /// Returns an error if the queue file cannot be written, in which case the operation is
+    /// not enqueued.
+    pub fn enqueue_vote(
+        &mut self,
+        voting_id: &str,
+        voter_id: &str,
+        ballot: impl IntoIterator<Item = (impl Into<String>, Rank)>,
+    ) -> io::Result<()> {
+        self.enqueue(QueuedOperation::Vote {
+            voting_id: voting_id.to_string(),
+            voter_id: voter_id.to_string(),
+            ballot: ballot
+                .into_iter()
+                .map(|(choice, rank)| (choice.into(), rank))
+                .collect(),
+            idempotency_key: generate_idempotency_key(),
+        })
+    }
+
+    /// Enqueues the removal of the ballot of a voter, and writes the queue to its file.
+    ///
+    /// Returns an error if the queue file cannot be written, in which case the operation is
+    /// not enqueued.
+    pub fn enqueue_unvote(&mut self, voting_id: &str, voter_id: &str) -> io::Result<()> {
+        self.enqueue(QueuedOperation::Unvote {
+            voting_id: voting_id.to_string(),
+            voter_id: voter_id.to_string(),
+            idempotency_key: generate_idempotency_key(),
+        })
+    }
+
+    /// Returns the operations in the queue, in the order they will be sent.
+    pub fn operations(&self) -> &[QueuedOperation] {
+        &self.operations
+    }
+
+    /// Returns the number of operations in the queue.
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Reports whether the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Sends the queued operations in order with the client, removing every operation
+    /// which was applied or rejected by the API from the queue file.
+    ///
+    /// Returns an error if the queue file cannot be written. The operations sent before the
+    /// error are applied, and may be sent again by the next flush, with the same
+    /// idempotency keys.
+    pub async fn flush(&mut self, client: &Client) -> io::Result<FlushReport> {
+        let mut report = FlushReport::default();
+
+        while let Some(operation) = self.operations.first() {
+            match operation.send(client).await {
+                Ok(()) => report.sent += 1,
+                Err(err) if err.is_client_error() => {
+                    report.rejected.push((operation.clone(), err));
+                }
+                Err(err) => {
+                    report.stopped = Some(err);
+                    break;
+                }
+            }
+            self.operations.remove(0);
+            self.save()?;
+        }
+
+        report.remaining = self.operations.len();
+        Ok(report)
+    }
+
+    fn enqueue(&mut self, operation: QueuedOperation) -> io::Result<()> {
+        self.operations.push(operation);
+        if let Err(err) = self.save() {
+            self.operations.pop();
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Writes the queue to a temporary file and moves it over the queue file, so the queue
+    /// file is never left partially written.
+    fn save(&self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+
+        let file = QueueFile {
+            operations: Cow::Borrowed(&self.operations),
+        };
+        std::fs::write(&temporary, serde_json::to_vec_pretty(&file)?)?;
+        std::fs::rename(&temporary, &self.path)
+    }
+}
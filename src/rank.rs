@@ -0,0 +1,130 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+/// Represents the rank of a choice on a ballot.
+///
+/// Ranks start from 1, which is the highest rank, and are bounded by `Rank::MAX`, the
+/// largest rank accepted by the API. Ballots are maps of choices to their ranks, so an
+/// invalid rank is rejected when the `Rank` is created, and never reaches the API.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::Rank;
+///
+/// let rank = Rank::new(2).unwrap();
+/// assert_eq!(rank.get(), 2);
+/// assert!(Rank::HIGHEST < rank);
+///
+/// assert!(Rank::new(0).is_none());
+/// assert!(Rank::try_from(-1).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "i64", into = "u32")]
+pub struct Rank(u32);
+
+impl Rank {
+    /// The highest rank, `1`.
+    pub const HIGHEST: Rank = Rank(1);
+
+    /// The largest rank accepted by the API, which stores ranks as signed 32-bit integers.
+    pub const MAX: Rank = Rank(i32::MAX as u32);
+
+    /// Creates a new `Rank`, returning `None` if the rank is `0` or greater than
+    /// `Rank::MAX`.
+    pub const fn new(rank: u32) -> Option<Self> {
+        if rank == 0 || rank > Self::MAX.0 {
+            return None;
+        }
+        Some(Self(rank))
+    }
+
+    /// Returns the rank as a `u32`.
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+}
+
+/// The error returned when converting an integer out of the range of `Rank`.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("rank {0} is out of range, ranks start from 1")]
+pub struct RankOutOfRange(i64);
+
+impl RankOutOfRange {
+    /// Returns the rejected rank.
+    pub fn rank(&self) -> i64 {
+        self.0
+    }
+}
+
+impl TryFrom<u32> for Rank {
+    type Error = RankOutOfRange;
+
+    fn try_from(rank: u32) -> Result<Self, Self::Error> {
+        Self::new(rank).ok_or(RankOutOfRange(rank.into()))
+    }
+}
+
+impl TryFrom<i32> for Rank {
+    type Error = RankOutOfRange;
+
+    fn try_from(rank: i32) -> Result<Self, Self::Error> {
+        Self::try_from(i64::from(rank))
+    }
+}
+
+impl TryFrom<i64> for Rank {
+    type Error = RankOutOfRange;
+
+    fn try_from(rank: i64) -> Result<Self, Self::Error> {
+        u32::try_from(rank)
+            .ok()
+            .and_then(Self::new)
+            .ok_or(RankOutOfRange(rank))
+    }
+}
+
+impl From<Rank> for u32 {
+    fn from(rank: Rank) -> Self {
+        rank.0
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_test() {
+        assert_eq!(Rank::new(1), Some(Rank::HIGHEST));
+        assert_eq!(Rank::new(0), None);
+        assert_eq!(Rank::new(i32::MAX as u32), Some(Rank::MAX));
+        assert_eq!(Rank::new(i32::MAX as u32 + 1), None);
+
+        assert_eq!(Rank::try_from(3i32).unwrap().get(), 3);
+        assert_eq!(Rank::try_from(-1i32), Err(RankOutOfRange(-1)));
+        assert_eq!(Rank::try_from(0u32).unwrap_err().rank(), 0);
+        assert_eq!(u32::from(Rank::HIGHEST), 1);
+    }
+
+    #[test]
+    fn rank_serde_test() {
+        let rank: Rank = serde_json::from_str("4").unwrap();
+        assert_eq!(rank, Rank::new(4).unwrap());
+        assert_eq!(serde_json::to_string(&rank).unwrap(), "4");
+        assert!(serde_json::from_str::<Rank>("0").is_err());
+        assert!(serde_json::from_str::<Rank>("-1").is_err());
+    }
+}
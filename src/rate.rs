@@ -3,13 +3,16 @@
 // Use of this source code is governed by a BSD-style
 // license that can be found in the LICENSE file.
 
-const HEADER_RATE_LIMIT: &str = "X-RateLimit-Limit";
-const HEADER_RATE_REMAINING: &str = "X-RateLimit-Remaining";
-const HEADER_RATE_RESET: &str = "X-RateLimit-Reset";
-const HEADER_RATE_RETRY: &str = "Retry-After";
+pub(crate) const HEADER_RATE_LIMIT: &str = "X-RateLimit-Limit";
+pub(crate) const HEADER_RATE_REMAINING: &str = "X-RateLimit-Remaining";
+pub(crate) const HEADER_RATE_RESET: &str = "X-RateLimit-Reset";
+pub(crate) const HEADER_RATE_RETRY: &str = "Retry-After";
 
 use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::Mutex;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Represents the rate limit information returned by the API.
@@ -18,7 +21,10 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 /// requests allowed, the number of requests remaining, and the time at which the rate limit will
 /// reset.
 ///
-#[derive(Clone, Default, Debug)]
+/// The `reset` and `retry` times are Unix timestamps in seconds, so a serialized `Rate` remains
+/// valid when it is restored after a restart. Use `Rate::reset_in` and `Rate::retry_in` for
+/// the durations until these times.
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Rate {
     pub limit: u32,
     pub remaining: u32,
@@ -44,6 +50,109 @@ impl Rate {
             retry: retry.as_secs(),
         })
     }
+
+    /// Returns the time at which the rate limit resets.
+    pub fn reset_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.reset)
+    }
+
+    /// Returns the time after which a rate limited request may be retried.
+    pub fn retry_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.retry)
+    }
+
+    /// Returns the duration until the rate limit resets, or zero if it already reset.
+    pub fn reset_in(&self) -> Duration {
+        until(self.reset_at())
+    }
+
+    /// Returns the duration until a rate limited request may be retried, or zero if it
+    /// may be retried now.
+    pub fn retry_in(&self) -> Duration {
+        until(self.retry_at())
+    }
+
+    /// Returns the duration until the rate limit resets, or zero if it already reset.
+    /// Same as `Rate::reset_in`.
+    pub fn time_until_reset(&self) -> Duration {
+        self.reset_in()
+    }
+
+    /// Reports whether no requests remain until the rate limit resets. Returns `false`
+    /// once the reset time passed, as the limit is then restored.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == 0 && !self.reset_in().is_zero()
+    }
+
+    /// Returns the fraction of the limit that remains, from `0.0` when no requests remain
+    /// to `1.0` when none were sent, for example to slow down below a threshold. Returns
+    /// `0.0` if the limit is zero.
+    pub fn remaining_fraction(&self) -> f64 {
+        if self.limit == 0 {
+            return 0.0;
+        }
+        (f64::from(self.remaining) / f64::from(self.limit)).min(1.0)
+    }
+}
+
+fn until(time: SystemTime) -> Duration {
+    time.duration_since(SystemTime::now()).unwrap_or_default()
+}
+
+/// A family of endpoints of the API, which may have its own rate limit.
+///
+/// Used with `Client::get_rate_for` to get the rate limit of the last response of the
+/// family, which is not overwritten by the responses of the other families.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointFamily {
+    /// The votings, their metadata, choices and statistics.
+    Votings,
+    /// The ballots of the votings.
+    Ballots,
+    /// The results of the votings, with or without the duels.
+    Results,
+}
+
+impl EndpointFamily {
+    /// Returns the family of the endpoint at the path, relative to the API URL or absolute,
+    /// or `None` if the path is not an endpoint of the votings.
+    pub(crate) fn from_path(path: &str) -> Option<Self> {
+        let path = path.split('?').next().unwrap_or_default();
+        let mut segments = path.split('/').skip_while(|segment| *segment != "votings");
+        segments.next()?;
+        let family = match (segments.next(), segments.next()) {
+            (Some(_), Some("ballots")) => EndpointFamily::Ballots,
+            (Some(_), Some("results")) => EndpointFamily::Results,
+            _ => EndpointFamily::Votings,
+        };
+        Some(family)
+    }
+}
+
+/// The last rate limit received for each `EndpointFamily`.
+#[derive(Debug, Default)]
+pub(crate) struct EndpointRates {
+    rates: Mutex<HashMap<EndpointFamily, Rate>>,
+}
+
+impl EndpointRates {
+    /// Stores the rate limit of a response from the endpoint at the path.
+    pub(crate) fn update(&self, path: &str, rate: &Rate) {
+        if let Some(family) = EndpointFamily::from_path(path) {
+            self.rates.lock().unwrap().insert(family, rate.clone());
+        }
+    }
+
+    pub(crate) fn get(&self, family: EndpointFamily) -> Option<Rate> {
+        self.rates.lock().unwrap().get(&family).cloned()
+    }
+}
+
+/// Returns the duration to wait before retrying a request, from the `Retry-After` header
+/// in seconds.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    fetch_header(headers, HEADER_RATE_RETRY).map(Duration::from_secs)
 }
 
 fn fetch_header<T>(headers: &HeaderMap, header: &str) -> Option<T>
@@ -102,4 +211,97 @@ mod tests {
         assert!(rate.is_none());
         mock.assert();
     }
+
+    #[test]
+    fn test_serde() {
+        let rate = Rate {
+            limit: 100,
+            remaining: 50,
+            reset: 1700000000,
+            retry: 1700000060,
+        };
+
+        let json = serde_json::to_value(&rate).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "limit": 100,
+                "remaining": 50,
+                "reset": 1700000000,
+                "retry": 1700000060
+            })
+        );
+        assert_eq!(serde_json::from_value::<Rate>(json).unwrap(), rate);
+    }
+
+    #[test]
+    fn test_endpoint_family() {
+        assert_eq!(
+            EndpointFamily::from_path("v1/votings?limit=10"),
+            Some(EndpointFamily::Votings)
+        );
+        assert_eq!(
+            EndpointFamily::from_path("v1/votings/abc/choices"),
+            Some(EndpointFamily::Votings)
+        );
+        assert_eq!(
+            EndpointFamily::from_path("/api/v1/votings/abc/ballots/einstein"),
+            Some(EndpointFamily::Ballots)
+        );
+        assert_eq!(
+            EndpointFamily::from_path("v1/votings/abc/results/duels"),
+            Some(EndpointFamily::Results)
+        );
+        assert_eq!(EndpointFamily::from_path("v1/health"), None);
+    }
+
+    #[test]
+    fn test_durations() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let rate = Rate {
+            limit: 100,
+            remaining: 0,
+            reset: now + 60,
+            retry: now - 1,
+        };
+
+        assert_eq!(rate.reset_at(), UNIX_EPOCH + Duration::from_secs(now + 60));
+        assert!(rate.reset_in() > Duration::from_secs(58));
+        assert!(rate.reset_in() <= Duration::from_secs(60));
+        assert_eq!(rate.retry_in(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_predicates() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let mut rate = Rate {
+            limit: 100,
+            remaining: 25,
+            reset: now + 60,
+            retry: now,
+        };
+        assert!(!rate.is_exhausted());
+        assert_eq!(rate.remaining_fraction(), 0.25);
+
+        rate.remaining = 0;
+        assert!(rate.is_exhausted());
+        assert_eq!(rate.remaining_fraction(), 0.0);
+
+        // the limit is restored once the reset time passed
+        rate.reset = now - 1;
+        assert!(!rate.is_exhausted());
+        assert_eq!(rate.time_until_reset(), Duration::ZERO);
+
+        rate.reset = now + 60;
+        assert!(rate.time_until_reset() > Duration::from_secs(58));
+        assert!(rate.time_until_reset() <= Duration::from_secs(60));
+
+        assert_eq!(Rate::default().remaining_fraction(), 0.0);
+    }
 }
@@ -0,0 +1,87 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Limits the rate of requests on the client side, independently of the rate limit of the API.
+///
+/// A token bucket holding up to `requests` tokens, refilled at `requests` tokens per second,
+/// implemented as a generic cell rate algorithm: every request reserves the next slot, so
+/// a burst of up to `requests` requests is sent at once, and the following requests are
+/// spaced evenly.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    burst: Duration,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing `requests` requests per second. A rate of `0` is treated
+    /// as `1`.
+    pub(crate) fn new(requests: u32) -> Self {
+        let requests = requests.max(1);
+        let interval = Duration::from_secs(1) / requests;
+        Self {
+            interval,
+            burst: interval * (requests - 1),
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    /// Reserves a slot for the next request, and returns how long the request should wait.
+    pub(crate) fn delay(&self) -> Duration {
+        self.delay_at(Instant::now())
+    }
+
+    fn delay_at(&self, now: Instant) -> Duration {
+        let mut next_slot = self.next_slot.lock().unwrap();
+        let slot = next_slot.filter(|slot| *slot > now).unwrap_or(now);
+        *next_slot = Some(slot + self.interval);
+
+        slot.checked_sub(self.burst)
+            .map_or(Duration::ZERO, |allowed| {
+                allowed.saturating_duration_since(now)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_test() {
+        let limiter = RateLimiter::new(4);
+        let now = Instant::now() + Duration::from_secs(10);
+
+        for _ in 0..4 {
+            assert_eq!(limiter.delay_at(now), Duration::ZERO);
+        }
+        assert_eq!(limiter.delay_at(now), Duration::from_millis(250));
+        assert_eq!(limiter.delay_at(now), Duration::from_millis(500));
+
+        // after an idle second, a full burst is allowed again
+        let later = now + Duration::from_millis(1500);
+        for _ in 0..4 {
+            assert_eq!(limiter.delay_at(later), Duration::ZERO);
+        }
+        assert_eq!(limiter.delay_at(later), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn single_request_test() {
+        let limiter = RateLimiter::new(0);
+        let now = Instant::now() + Duration::from_secs(10);
+
+        assert_eq!(limiter.delay_at(now), Duration::ZERO);
+        assert_eq!(limiter.delay_at(now), Duration::from_secs(1));
+        assert_eq!(
+            limiter.delay_at(now + Duration::from_millis(500)),
+            Duration::from_millis(1500)
+        );
+    }
+}
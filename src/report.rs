@@ -0,0 +1,440 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::{Duels, Voting, VotingResult, VotingResults, VotingStats};
+use std::fmt::Write;
+
+/// The maximum number of duels listed in the notable duels section of a summary.
+const NOTABLE_DUELS: usize = 3;
+
+/// Output format of a voting summary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    PlainText,
+    Markdown,
+    Html,
+}
+
+/// A single entry in the ranked standings of a voting.
+///
+/// Choices with the same number of wins share the same rank.
+#[derive(Debug, PartialEq)]
+pub struct Standing<'a> {
+    pub rank: usize,
+    pub result: &'a VotingResult,
+}
+
+/// A human-readable summary of a voting and its results.
+///
+/// The summary contains the voting choices, the turnout, the ranked standings, the closest
+/// duels between choices and the tie information, and can be rendered as plain text, Markdown
+/// or HTML. The turnout is only included if the statistics were provided with `with_stats`,
+/// as the API does not report it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddclient_rs::{Client, ReportFormat};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+///     let summary = client.summarize_voting("voting_id", ReportFormat::Markdown).await;
+///     // Post the summary...
+/// }
+/// ```
+#[derive(Debug)]
+pub struct VotingSummary {
+    pub voting: Voting,
+    pub results: VotingResults,
+    pub stats: Option<VotingStats>,
+}
+
+impl VotingSummary {
+    /// Creates a new summary from a voting and its results.
+    pub fn new(voting: Voting, results: VotingResults) -> Self {
+        Self {
+            voting,
+            results,
+            stats: None,
+        }
+    }
+
+    /// Adds the turnout statistics to the summary.
+    pub fn with_stats(mut self, stats: VotingStats) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    /// Returns the results ordered by the number of wins, with shared ranks for equal wins.
+    pub fn standings(&self) -> Vec<Standing<'_>> {
+        standings(&self.results)
+    }
+
+    /// Returns the duels with the smallest difference in strength, closest first.
+    ///
+    /// Returns an empty list if the results do not contain duels information.
+    pub fn notable_duels(&self) -> Vec<&Duels> {
+        let mut duels: Vec<&Duels> = self.results.duels.iter().flatten().collect();
+        duels.sort_by_key(|d| d.left.strength.abs_diff(d.right.strength));
+        duels.truncate(NOTABLE_DUELS);
+        duels
+    }
+
+    /// Renders the summary in the given format.
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::PlainText => self.render_plain_text(),
+            ReportFormat::Markdown => self.render_markdown(),
+            ReportFormat::Html => self.render_html(),
+        }
+    }
+
+    fn outcome(&self) -> String {
+        let standings = self.standings();
+        let leaders: Vec<&str> = standings
+            .iter()
+            .filter(|s| s.rank == 1)
+            .map(|s| s.result.choice.as_str())
+            .collect();
+
+        match (self.results.tie, leaders.as_slice()) {
+            (_, []) => "No results yet.".to_string(),
+            (false, [winner]) => format!("Winner: {}", winner),
+            (_, leaders) => format!("Tie between: {}", leaders.join(", ")),
+        }
+    }
+
+    fn turnout(&self) -> Option<String> {
+        let stats = self.stats.as_ref()?;
+        Some(format!(
+            "Turnout: {} ballots ({} revotes)",
+            stats.ballots, stats.revotes
+        ))
+    }
+
+    fn render_plain_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Voting {}", self.voting.id);
+        let _ = writeln!(out);
+        let _ = writeln!(out, "Choices:");
+        for choice in &self.voting.choices {
+            let _ = writeln!(out, "  - {}", choice);
+        }
+        let _ = writeln!(out);
+        if let Some(turnout) = self.turnout() {
+            let _ = writeln!(out, "{}", turnout);
+        }
+        let _ = writeln!(out, "{}", self.outcome());
+        let _ = writeln!(out);
+        let _ = writeln!(out, "Standings:");
+        for standing in self.standings() {
+            let _ = writeln!(
+                out,
+                "  {}. {} ({} wins, {}%)",
+                standing.rank,
+                standing.result.choice,
+                standing.result.wins,
+                standing.result.formatted_percentage(2)
+            );
+        }
+
+        let duels = self.notable_duels();
+        if !duels.is_empty() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "Notable duels:");
+            for duel in duels {
+                let _ = writeln!(
+                    out,
+                    "  {} vs {}: {} - {}",
+                    duel.left.choice, duel.right.choice, duel.left.strength, duel.right.strength
+                );
+            }
+        }
+        out
+    }
+
+    fn render_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "## Voting `{}`", self.voting.id);
+        let _ = writeln!(out);
+        let _ = writeln!(out, "**{}**", escape_markdown(&self.outcome()));
+        let _ = writeln!(out);
+        if let Some(turnout) = self.turnout() {
+            let _ = writeln!(out, "{}", turnout);
+            let _ = writeln!(out);
+        }
+        let _ = writeln!(out, "### Choices");
+        let _ = writeln!(out);
+        for choice in &self.voting.choices {
+            let _ = writeln!(out, "- {}", escape_markdown(choice));
+        }
+        let _ = writeln!(out);
+        let _ = writeln!(out, "### Standings");
+        let _ = writeln!(out);
+        let _ = writeln!(out, "| Rank | Choice | Wins | Percentage |");
+        let _ = writeln!(out, "| ---: | :--- | ---: | ---: |");
+        for standing in self.standings() {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {}% |",
+                standing.rank,
+                escape_markdown(&standing.result.choice),
+                standing.result.wins,
+                standing.result.formatted_percentage(2)
+            );
+        }
+
+        let duels = self.notable_duels();
+        if !duels.is_empty() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "### Notable duels");
+            let _ = writeln!(out);
+            for duel in duels {
+                let _ = writeln!(
+                    out,
+                    "- {} vs {}: {} - {}",
+                    escape_markdown(&duel.left.choice),
+                    escape_markdown(&duel.right.choice),
+                    duel.left.strength,
+                    duel.right.strength
+                );
+            }
+        }
+        out
+    }
+
+    fn render_html(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "<h2>Voting {}</h2>", escape_html(&self.voting.id));
+        let _ = writeln!(
+            out,
+            "<p><strong>{}</strong></p>",
+            escape_html(&self.outcome())
+        );
+        if let Some(turnout) = self.turnout() {
+            let _ = writeln!(out, "<p>{}</p>", turnout);
+        }
+        let _ = writeln!(out, "<h3>Choices</h3>");
+        let _ = writeln!(out, "<ul>");
+        for choice in &self.voting.choices {
+            let _ = writeln!(out, "<li>{}</li>", escape_html(choice));
+        }
+        let _ = writeln!(out, "</ul>");
+        let _ = writeln!(out, "<h3>Standings</h3>");
+        let _ = writeln!(out, "<table>");
+        let _ = writeln!(
+            out,
+            "<tr><th>Rank</th><th>Choice</th><th>Wins</th><th>Percentage</th></tr>"
+        );
+        for standing in self.standings() {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}%</td></tr>",
+                standing.rank,
+                escape_html(&standing.result.choice),
+                standing.result.wins,
+                standing.result.formatted_percentage(2)
+            );
+        }
+        let _ = writeln!(out, "</table>");
+
+        let duels = self.notable_duels();
+        if !duels.is_empty() {
+            let _ = writeln!(out, "<h3>Notable duels</h3>");
+            let _ = writeln!(out, "<ul>");
+            for duel in duels {
+                let _ = writeln!(
+                    out,
+                    "<li>{} vs {}: {} - {}</li>",
+                    escape_html(&duel.left.choice),
+                    escape_html(&duel.right.choice),
+                    duel.left.strength,
+                    duel.right.strength
+                );
+            }
+            let _ = writeln!(out, "</ul>");
+        }
+        out
+    }
+}
+
+/// Returns the results ordered by the number of wins, with shared ranks for equal wins.
+pub(crate) fn standings(results: &VotingResults) -> Vec<Standing<'_>> {
+    let mut sorted: Vec<&VotingResult> = results.results.iter().collect();
+    sorted.sort_by(|a, b| b.wins.cmp(&a.wins).then(a.index.cmp(&b.index)));
+
+    let mut standings: Vec<Standing> = Vec::with_capacity(sorted.len());
+    for (position, result) in sorted.into_iter().enumerate() {
+        let rank = match standings.last() {
+            Some(prev) if prev.result.wins == result.wins => prev.rank,
+            _ => position + 1,
+        };
+        standings.push(Standing { rank, result });
+    }
+    standings
+}
+
+pub(crate) fn escape_markdown(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChoiceIndex, ChoiceStrength, Choices};
+
+    fn result(choice: &str, index: usize, wins: i32, percentage: u8) -> VotingResult {
+        VotingResult {
+            choice: choice.to_string(),
+            index: ChoiceIndex::new(index),
+            wins,
+            percentage: percentage.into(),
+            strength: 0,
+            advantage: 0,
+        }
+    }
+
+    fn duel(left: (&str, usize, isize), right: (&str, usize, isize)) -> Duels {
+        Duels {
+            left: ChoiceStrength {
+                index: ChoiceIndex::new(left.1),
+                choice: left.0.to_string(),
+                strength: left.2,
+            },
+            right: ChoiceStrength {
+                index: ChoiceIndex::new(right.1),
+                choice: right.0.to_string(),
+                strength: right.2,
+            },
+        }
+    }
+
+    fn summary() -> VotingSummary {
+        VotingSummary::new(
+            Voting {
+                id: "40f80454800b2bd7c172".to_string(),
+                choices: Choices::new(vec![
+                    "Spinoza".to_string(),
+                    "Kant".to_string(),
+                    "Nietzsche".to_string(),
+                ]),
+                #[cfg(feature = "experimental")]
+                title: None,
+                #[cfg(feature = "experimental")]
+                description: None,
+                #[cfg(feature = "experimental")]
+                status: None,
+            },
+            VotingResults {
+                tie: false,
+                results: vec![
+                    result("Kant", 1, 2, 100),
+                    result("Spinoza", 0, 1, 50),
+                    result("Nietzsche", 2, 0, 0),
+                ],
+                duels: Some(vec![
+                    duel(("Spinoza", 0, 1), ("Kant", 1, 2)),
+                    duel(("Spinoza", 0, 3), ("Nietzsche", 2, 0)),
+                    duel(("Kant", 1, 3), ("Nietzsche", 2, 0)),
+                ]),
+            },
+        )
+    }
+
+    #[test]
+    fn standings_test() {
+        let mut summary = summary();
+        let ranks: Vec<(usize, &str)> = summary
+            .standings()
+            .iter()
+            .map(|s| (s.rank, s.result.choice.as_str()))
+            .collect();
+        assert_eq!(ranks, vec![(1, "Kant"), (2, "Spinoza"), (3, "Nietzsche")]);
+
+        summary.results.tie = true;
+        summary.results.results[1].wins = 2;
+        let ranks: Vec<(usize, &str)> = summary
+            .standings()
+            .iter()
+            .map(|s| (s.rank, s.result.choice.as_str()))
+            .collect();
+        assert_eq!(ranks, vec![(1, "Spinoza"), (1, "Kant"), (3, "Nietzsche")]);
+        assert_eq!(summary.outcome(), "Tie between: Spinoza, Kant");
+    }
+
+    #[test]
+    fn notable_duels_test() {
+        let summary = summary();
+        let duels = summary.notable_duels();
+        assert_eq!(duels.len(), 3);
+        assert_eq!(duels[0].left.choice, "Spinoza");
+        assert_eq!(duels[0].right.choice, "Kant");
+    }
+
+    #[test]
+    fn render_plain_text_test() {
+        let text = summary().render(ReportFormat::PlainText);
+        assert!(text.starts_with("Voting 40f80454800b2bd7c172\n"));
+        assert!(!text.contains("Turnout"));
+        assert!(text.contains("Winner: Kant\n"));
+        assert!(text.contains("  1. Kant (2 wins, 100.00%)\n"));
+        assert!(text.contains("  Spinoza vs Kant: 1 - 2\n"));
+
+        let text = summary()
+            .with_stats(VotingStats {
+                ballots: 3,
+                revotes: 1,
+            })
+            .render(ReportFormat::PlainText);
+        assert!(text.contains("Turnout: 3 ballots (1 revotes)\nWinner: Kant\n"));
+    }
+
+    #[test]
+    fn render_markdown_test() {
+        let mut summary = summary();
+        summary.voting.choices.extend(["Hegel | Marx".to_string()]);
+        let markdown = summary.render(ReportFormat::Markdown);
+        assert!(markdown.contains("**Winner: Kant**"));
+        assert!(markdown.contains("| 1 | Kant | 2 | 100.00% |"));
+        assert!(markdown.contains("- Hegel \\| Marx"));
+    }
+
+    #[test]
+    fn render_html_test() {
+        let mut summary = summary();
+        summary.voting.choices.extend(["<script>".to_string()]);
+        let html = summary.render(ReportFormat::Html);
+        assert!(html.contains("<p><strong>Winner: Kant</strong></p>"));
+        assert!(html.contains("<tr><td>1</td><td>Kant</td><td>2</td><td>100.00%</td></tr>"));
+        assert!(html.contains("<li>&lt;script&gt;</li>"));
+        assert!(!html.contains("<li><script></li>"));
+    }
+}
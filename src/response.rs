@@ -0,0 +1,83 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::{handle_api_response, request_id, ApiError, Rate};
+use reqwest::header::HeaderMap;
+use reqwest::{Response, StatusCode};
+
+/// The data of a successful response of the API, with the metadata of that response.
+///
+/// Returned by the `*_with_meta` methods of the `Client`. Unlike `Client::get_rate`, which
+/// returns the rate limit of whichever request finished last, `rate` is parsed from this
+/// response, so it is accurate when the client is shared by concurrent tasks.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddclient_rs::{Client, RequestOptions};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let client = Client::try_new("my-api-key".to_string()).unwrap();
+///     let response = client
+///         .get_voting_with_meta("voting_id", &RequestOptions::new())
+///         .await
+///         .unwrap();
+///     if let Some(rate) = &response.rate {
+///         println!("{} requests remaining", rate.remaining);
+///     }
+///     let voting = response.into_data();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ApiResponse<T> {
+    /// The deserialized body of the response.
+    pub data: T,
+    /// The HTTP status of the response.
+    pub status: StatusCode,
+    /// The headers of the response.
+    pub headers: HeaderMap,
+    /// The rate limit parsed from the headers of the response, if they contain it.
+    pub rate: Option<Rate>,
+}
+
+impl<T> ApiResponse<T> {
+    /// Reads the data of a response, keeping its status and headers.
+    pub(crate) async fn read(response: Response) -> Result<Self, ApiError>
+    where
+        T: serde::de::DeserializeOwned + serde::Serialize,
+    {
+        let status = response.status();
+        let headers = response.headers().clone();
+        let data = handle_api_response(response).await?;
+        Ok(Self {
+            data,
+            status,
+            rate: Rate::from_headers(&headers),
+            headers,
+        })
+    }
+
+    /// Returns the data of the response, dropping the metadata.
+    pub fn into_data(self) -> T {
+        self.data
+    }
+
+    /// Converts the data of the response, keeping the metadata.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ApiResponse<U> {
+        ApiResponse {
+            data: f(self.data),
+            status: self.status,
+            headers: self.headers,
+            rate: self.rate,
+        }
+    }
+
+    /// Returns the ID of the request, as sent by the API in the `X-Request-Id` response
+    /// header.
+    pub fn request_id(&self) -> Option<String> {
+        request_id(&self.headers)
+    }
+}
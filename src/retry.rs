@@ -0,0 +1,167 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::options::HEADER_IDEMPOTENCY_KEY;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+use std::time::Duration;
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(100);
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Configures automatic retries of requests that failed with a transient error.
+///
+/// Requests that fail with a `502 Bad Gateway` or `503 Service Unavailable` response, or
+/// with a connection error, are retried with an exponential backoff: the delay before the
+/// n-th retry is `base_delay * 2^(n - 1)`, capped at `max_delay`.
+///
+/// `502 Bad Gateway` responses, timeouts and connections reset after the request was sent
+/// are retried only for idempotent methods, and for requests with an idempotency key, as
+/// the API may have applied the request already. Without a key, a `create_voting` request
+/// that timed out is not sent again, so it does not create a duplicate voting.
+///
+/// Retries are disabled unless a policy is set with `ClientBuilder::retry_policy`.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{Client, RetryPolicy};
+/// use std::time::Duration;
+///
+/// let client = Client::builder("my-api-key".to_string())
+///     .retry_policy(
+///         RetryPolicy::new(5)
+///             .base_delay(Duration::from_millis(200))
+///             .max_delay(Duration::from_secs(10)),
+///     )
+///     .try_build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Creates a new `RetryPolicy` with the maximum number of attempts, including the first
+    /// request, and the default delays of 100 milliseconds base and 5 seconds maximum.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Sets the maximum delay between two attempts.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Returns the maximum number of attempts, including the first request.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Returns the delay to wait after the given failed attempt, starting from 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+
+    pub(crate) fn should_retry(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: DEFAULT_BASE_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+        }
+    }
+}
+
+/// Reports whether a request that resulted in the given response status or error should be
+/// retried.
+///
+/// Connection errors and `503 Service Unavailable` responses are retried for every request,
+/// as the request was not applied. A gateway may respond with `502 Bad Gateway` after the
+/// API applied the request, so these responses and other request errors, such as timeouts,
+/// are retried only if the request is `replayable`.
+pub(crate) fn is_transient(result: Result<StatusCode, &reqwest::Error>, replayable: bool) -> bool {
+    match result {
+        Ok(StatusCode::SERVICE_UNAVAILABLE) => true,
+        Ok(StatusCode::BAD_GATEWAY) => replayable,
+        Ok(_) => false,
+        Err(err) => err.is_connect() || (replayable && (err.is_timeout() || err.is_request())),
+    }
+}
+
+/// Reports whether a request can be sent again after it may have been applied by the API,
+/// because its method is idempotent, or it has an idempotency key.
+pub(crate) fn is_replayable(method: &Method, headers: &HeaderMap) -> bool {
+    method.is_idempotent() || headers.contains_key(HEADER_IDEMPOTENCY_KEY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_test() {
+        let policy = RetryPolicy::new(10)
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(1));
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+        assert_eq!(policy.delay(4), Duration::from_millis(800));
+        assert_eq!(policy.delay(5), Duration::from_secs(1));
+        assert_eq!(policy.delay(64), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn should_retry_test() {
+        let policy = RetryPolicy::new(3);
+
+        assert!(policy.should_retry(1));
+        assert!(policy.should_retry(2));
+        assert!(!policy.should_retry(3));
+    }
+
+    #[test]
+    fn is_transient_test() {
+        assert!(is_transient(Ok(StatusCode::SERVICE_UNAVAILABLE), false));
+        assert!(is_transient(Ok(StatusCode::BAD_GATEWAY), true));
+        assert!(!is_transient(Ok(StatusCode::BAD_GATEWAY), false));
+        assert!(!is_transient(Ok(StatusCode::INTERNAL_SERVER_ERROR), true));
+    }
+
+    #[test]
+    fn is_replayable_test() {
+        let mut headers = HeaderMap::new();
+        assert!(is_replayable(&Method::GET, &headers));
+        assert!(is_replayable(&Method::DELETE, &headers));
+        assert!(!is_replayable(&Method::POST, &headers));
+
+        headers.insert(HEADER_IDEMPOTENCY_KEY, "key".parse().unwrap());
+        assert!(is_replayable(&Method::POST, &headers));
+    }
+}
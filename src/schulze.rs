@@ -0,0 +1,241 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+//! Offline computation of voting results with the Schulze method.
+//!
+//! The results are computed in the same way as by the Direct Decisions API, which allows
+//! previewing results locally, validating the results returned by the API, and testing
+//! without the API.
+
+use crate::pairwise::preferences;
+use crate::percentage::percentage_of;
+use crate::{ChoiceIndex, ChoiceStrength, Duels, Rank, VotingResult, VotingResults};
+use std::collections::HashMap;
+
+/// Computes the results of a voting from its ballots with the Schulze method, including the
+/// duels between the choices.
+///
+/// Choices that are not ranked on a ballot are considered less preferred than all ranked
+/// choices, and choices that are not in `choices` are ignored. Results are ordered by the
+/// number of wins, and then by the choice index, as returned by
+/// `Client::get_voting_results_duels`.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{schulze, Rank};
+/// use std::collections::HashMap;
+///
+/// let choices = vec!["Einstein".to_string(), "Newton".to_string(), "Bohr".to_string()];
+/// let second = Rank::new(2).unwrap();
+/// let ballots = [
+///     HashMap::from([("Newton".to_string(), Rank::HIGHEST), ("Bohr".to_string(), second)]),
+///     HashMap::from([("Bohr".to_string(), Rank::HIGHEST), ("Newton".to_string(), second)]),
+///     HashMap::from([("Newton".to_string(), Rank::HIGHEST)]),
+/// ];
+///
+/// let results = schulze::compute(&choices, &ballots);
+/// assert!(!results.tie);
+/// assert_eq!(results.results[0].choice, "Newton");
+/// assert_eq!(results.results[0].wins, 2);
+/// ```
+pub fn compute(choices: &[String], ballots: &[HashMap<String, Rank>]) -> VotingResults {
+    let ballots: Vec<&HashMap<String, Rank>> = ballots.iter().collect();
+    compute_results(choices, &ballots, true)
+}
+
+/// Groups the choices of voting results by their position in the ranking, from the winners.
+///
+/// Choices with the same number of wins share a position, so the first group contains
+/// more than one choice if the voting is tied.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{schulze, Rank};
+/// use std::collections::HashMap;
+///
+/// let choices = vec!["Einstein".to_string(), "Newton".to_string(), "Bohr".to_string()];
+/// let ballots = [
+///     HashMap::from([("Newton".to_string(), Rank::HIGHEST)]),
+///     HashMap::from([("Bohr".to_string(), Rank::HIGHEST)]),
+/// ];
+///
+/// let results = schulze::compute(&choices, &ballots);
+/// assert_eq!(
+///     schulze::ranking(&results),
+///     vec![vec!["Newton", "Bohr"], vec!["Einstein"]]
+/// );
+/// ```
+pub fn ranking(results: &VotingResults) -> Vec<Vec<&str>> {
+    let mut ranking: Vec<(i32, Vec<&str>)> = Vec::new();
+    for result in &results.results {
+        match ranking.last_mut() {
+            Some((wins, choices)) if *wins == result.wins => choices.push(&result.choice),
+            _ => ranking.push((result.wins, vec![&result.choice])),
+        }
+    }
+    ranking.into_iter().map(|(_, choices)| choices).collect()
+}
+
+/// Computes the results of a voting from its ballots with the Schulze method.
+///
+/// Choices that are not ranked on a ballot are considered less preferred than all ranked
+/// choices. Results are ordered by the number of wins, and then by the choice index.
+pub(crate) fn compute_results(
+    choices: &[String],
+    ballots: &[&HashMap<String, Rank>],
+    duels: bool,
+) -> VotingResults {
+    let n = choices.len();
+
+    // preferences[i][j] is the number of voters preferring choice i over choice j
+    let preferences = preferences(choices, ballots);
+
+    // strengths[i][j] is the strength of the strongest path from choice i to choice j
+    let mut strengths = vec![vec![0usize; n]; n];
+    for i in 0..n {
+        for j in 0..n {
+            if preferences[i][j] > preferences[j][i] {
+                strengths[i][j] = preferences[i][j];
+            }
+        }
+    }
+    for k in 0..n {
+        for i in 0..n {
+            if i == k {
+                continue;
+            }
+            for j in 0..n {
+                if j == i || j == k {
+                    continue;
+                }
+                strengths[i][j] = strengths[i][j].max(strengths[i][k].min(strengths[k][j]));
+            }
+        }
+    }
+
+    let mut results: Vec<VotingResult> = (0..n)
+        .map(|i| {
+            let beaten: Vec<usize> = (0..n)
+                .filter(|&j| strengths[i][j] > strengths[j][i])
+                .collect();
+            VotingResult {
+                choice: choices[i].clone(),
+                index: ChoiceIndex::new(i),
+                wins: beaten.len() as i32,
+                percentage: percentage_of(beaten.len(), n.saturating_sub(1)),
+                strength: strengths[i].iter().sum(),
+                advantage: beaten
+                    .iter()
+                    .map(|&j| strengths[i][j] - strengths[j][i])
+                    .sum(),
+            }
+        })
+        .collect();
+    results.sort_by(|a, b| b.wins.cmp(&a.wins).then(a.index.cmp(&b.index)));
+
+    let tie = results.len() > 1 && results[0].wins == results[1].wins;
+
+    let duels = duels.then(|| {
+        let mut duels = Vec::new();
+        for (position, left) in results.iter().enumerate() {
+            for right in &results[position + 1..] {
+                let (l, r) = (left.index.get(), right.index.get());
+                duels.push(Duels {
+                    left: ChoiceStrength {
+                        index: left.index,
+                        choice: left.choice.clone(),
+                        strength: preferences[l][r] as isize,
+                    },
+                    right: ChoiceStrength {
+                        index: right.index,
+                        choice: right.choice.clone(),
+                        strength: preferences[r][l] as isize,
+                    },
+                });
+            }
+        }
+        duels
+    });
+
+    VotingResults {
+        tie,
+        results,
+        duels,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute_results_test() {
+        let choices: Vec<String> = ["A", "B", "C"].iter().map(|c| c.to_string()).collect();
+        let second = Rank::new(2).unwrap();
+        let ballots = [
+            HashMap::from([("A".to_string(), Rank::HIGHEST), ("B".to_string(), second)]),
+            HashMap::from([("A".to_string(), Rank::HIGHEST), ("C".to_string(), second)]),
+            HashMap::from([("B".to_string(), Rank::HIGHEST), ("A".to_string(), second)]),
+        ];
+        let ballots: Vec<&HashMap<String, Rank>> = ballots.iter().collect();
+
+        let results = compute_results(&choices, &ballots, true);
+
+        assert!(!results.tie);
+        let order: Vec<(&str, i32)> = results
+            .results
+            .iter()
+            .map(|r| (r.choice.as_str(), r.wins))
+            .collect();
+        assert_eq!(order, vec![("A", 2), ("B", 1), ("C", 0)]);
+        assert_eq!(results.results[0].formatted_percentage(0), "100");
+        assert_eq!(results.results[1].formatted_percentage(0), "50");
+
+        let duels = results.duels.unwrap();
+        assert_eq!(duels.len(), 3);
+        assert_eq!(duels[0].left.choice, "A");
+        assert_eq!(duels[0].left.strength, 2);
+        assert_eq!(duels[0].right.choice, "B");
+        assert_eq!(duels[0].right.strength, 1);
+    }
+
+    #[test]
+    fn compute_results_tie_test() {
+        let choices: Vec<String> = ["A", "B"].iter().map(|c| c.to_string()).collect();
+        let ballots = [
+            HashMap::from([("A".to_string(), Rank::HIGHEST)]),
+            HashMap::from([("B".to_string(), Rank::HIGHEST)]),
+        ];
+        let ballots: Vec<&HashMap<String, Rank>> = ballots.iter().collect();
+
+        let results = compute_results(&choices, &ballots, false);
+
+        assert!(results.tie);
+        assert_eq!(results.results[0].choice, "A");
+        assert!(results.duels.is_none());
+    }
+
+    #[test]
+    fn ranking_test() {
+        let choices: Vec<String> = ["A", "B", "C", "D"].iter().map(|c| c.to_string()).collect();
+        let second = Rank::new(2).unwrap();
+        let ballots = [
+            HashMap::from([("A".to_string(), Rank::HIGHEST), ("B".to_string(), second)]),
+            HashMap::from([("B".to_string(), Rank::HIGHEST), ("A".to_string(), second)]),
+            HashMap::from([("C".to_string(), Rank::HIGHEST)]),
+        ];
+
+        let results = compute(&choices, &ballots);
+
+        assert!(results.tie);
+        assert_eq!(results.duels.as_ref().map(Vec::len), Some(6));
+        assert_eq!(
+            ranking(&results),
+            vec![vec!["A", "B"], vec!["C"], vec!["D"]]
+        );
+    }
+}
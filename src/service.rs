@@ -0,0 +1,170 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::{ClientError, Transport};
+use async_trait::async_trait;
+use futures_util::future::{poll_fn, BoxFuture};
+use reqwest::{Request, Response};
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tower_service::Service;
+
+/// A `tower::Service` sending requests with a `Transport`.
+///
+/// Returned by `Client::service` for the transport of a client, so tower layers such as
+/// timeouts, load shedding or concurrency limits can be composed around it. The layered
+/// service is set as the transport of a client with `ServiceTransport`.
+#[derive(Clone)]
+pub struct TransportService {
+    transport: Arc<dyn Transport>,
+}
+
+impl TransportService {
+    /// Creates a new `TransportService` sending requests with the transport.
+    pub fn new(transport: impl Transport + 'static) -> Self {
+        Self {
+            transport: Arc::new(transport),
+        }
+    }
+
+    pub(crate) fn from_arc(transport: Arc<dyn Transport>) -> Self {
+        Self { transport }
+    }
+}
+
+impl Service<Request> for TransportService {
+    type Response = Response;
+    type Error = ClientError;
+    type Future = BoxFuture<'static, Result<Response, ClientError>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), ClientError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let transport = self.transport.clone();
+        Box::pin(async move { transport.execute(request).await })
+    }
+}
+
+/// A `Transport` sending requests through a `tower::Service`.
+///
+/// Every request is sent with a clone of the service, after it is ready. Errors of the
+/// service are returned as they are if they are a `ClientError` or a Reqwest error, and as
+/// `ClientError::Transport` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{Client, MockTransport, ServiceTransport, TransportService};
+///
+/// // Wrap the service with tower layers before it is set as the transport.
+/// let service = TransportService::new(MockTransport::new());
+///
+/// let client = Client::builder("my-api-key".to_string())
+///     .transport(ServiceTransport::new(service))
+///     .try_build()
+///     .unwrap();
+/// ```
+pub struct ServiceTransport<S> {
+    service: Mutex<S>,
+}
+
+impl<S> ServiceTransport<S> {
+    /// Creates a new `ServiceTransport` sending requests through the service.
+    pub fn new(service: S) -> Self {
+        Self {
+            service: Mutex::new(service),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> Transport for ServiceTransport<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Error: Into<Box<dyn Error + Send + Sync>>,
+    S::Future: Send,
+{
+    async fn execute(&self, request: Request) -> Result<Response, ClientError> {
+        let mut service = self.service.lock().unwrap().clone();
+        poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .map_err(client_error)?;
+        service.call(request).await.map_err(client_error)
+    }
+}
+
+fn client_error(err: impl Into<Box<dyn Error + Send + Sync>>) -> ClientError {
+    let err = match err.into().downcast::<ClientError>() {
+        Ok(err) => return *err,
+        Err(err) => err,
+    };
+    match err.downcast::<reqwest::Error>() {
+        Ok(err) => ClientError::HttpRequestError(*err),
+        Err(err) => ClientError::Transport(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MockResponse, MockTransport};
+    use reqwest::StatusCode;
+    use std::future::{ready, Ready};
+
+    /// A service failing every request with a custom error.
+    #[derive(Clone)]
+    struct Unavailable;
+
+    impl Service<Request> for Unavailable {
+        type Response = Response;
+        type Error = std::io::Error;
+        type Future = Ready<Result<Response, std::io::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), std::io::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: Request) -> Self::Future {
+            ready(Err(std::io::Error::other("overloaded")))
+        }
+    }
+
+    fn request() -> Request {
+        Request::new(
+            reqwest::Method::GET,
+            "http://localhost/v1/votings/1".parse().unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn service_transport_test() {
+        let mock = MockTransport::new();
+        mock.push(MockResponse::new(StatusCode::NO_CONTENT));
+        let transport = ServiceTransport::new(TransportService::new(mock.clone()));
+
+        let response = transport.execute(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(mock.requests().len(), 1);
+
+        // the errors of the transport are returned as they are
+        let err = transport.execute(request()).await.unwrap_err();
+        assert!(matches!(err, ClientError::Transport(_)));
+        assert_eq!(
+            err.to_string(),
+            "Transport Error: no mock response enqueued"
+        );
+    }
+
+    #[tokio::test]
+    async fn service_error_test() {
+        let transport = ServiceTransport::new(Unavailable);
+
+        let err = transport.execute(request()).await.unwrap_err();
+        assert_eq!(err.to_string(), "Transport Error: overloaded");
+    }
+}
@@ -0,0 +1,293 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+//! Generation of synthetic ballots, for load testing and demonstrating how the results
+//! behave with different electorates.
+//!
+//! The ballots can be submitted to a voting with `Simulation::submit`, or passed to
+//! `schulze::compute` to compute their results offline.
+
+use crate::client::ordered_ballot;
+use crate::{ApiError, BulkOptions, Client, Rank};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+
+/// The probability that a voter of a `Distribution::Polarized` simulation swaps two
+/// adjacent choices of their camp ranking.
+const POLARIZED_NOISE: f64 = 0.2;
+
+/// How the rankings of the simulated voters are distributed.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Distribution {
+    /// Every voter ranks the choices in a uniformly random order.
+    #[default]
+    Random,
+    /// The voters are split into two camps, ranking the choices mostly in their order and
+    /// in the reverse order, with some adjacent choices swapped by individual voters.
+    Polarized,
+    /// The choices lie on an axis in their order, such as from left to right. Every voter
+    /// prefers a random choice the most, and of two choices on the same side of it, ranks
+    /// the farther one lower.
+    SinglePeaked,
+}
+
+/// A generator of synthetic ballots over a set of choices.
+///
+/// Every simulated voter ranks all the choices, with strictly increasing ranks, and has a
+/// voter ID of the form `voter-1`, `voter-2` and so on. The ballots are generated from a
+/// seed, which is random unless it is set with `Simulation::seed`, so a simulation with
+/// a fixed seed always generates the same ballots.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::schulze;
+/// use ddclient_rs::simulation::{Distribution, Simulation};
+///
+/// let choices = vec!["Hume".to_string(), "Kant".to_string(), "Spinoza".to_string()];
+/// let simulation = Simulation::new(choices.clone())
+///     .distribution(Distribution::SinglePeaked)
+///     .voters(500)
+///     .seed(42);
+///
+/// let ballots: Vec<_> = simulation.ballots().into_iter().map(|(_, ballot)| ballot).collect();
+/// let results = schulze::compute(&choices, &ballots);
+/// assert_eq!(results.results.len(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    choices: Vec<String>,
+    distribution: Distribution,
+    voters: usize,
+    seed: u64,
+}
+
+impl Simulation {
+    /// Creates a new `Simulation` of 100 voters ranking the choices randomly.
+    pub fn new(choices: Vec<String>) -> Self {
+        Self {
+            choices,
+            distribution: Distribution::default(),
+            voters: 100,
+            seed: RandomState::new().hash_one(0u8),
+        }
+    }
+
+    /// Sets how the rankings of the voters are distributed.
+    pub fn distribution(mut self, distribution: Distribution) -> Self {
+        self.distribution = distribution;
+        self
+    }
+
+    /// Sets the number of simulated voters.
+    pub fn voters(mut self, voters: usize) -> Self {
+        self.voters = voters;
+        self
+    }
+
+    /// Sets the seed of the generated ballots.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Generates the ballots of the voters, as pairs of voter IDs and ballots accepted by
+    /// `Client::vote_many` and `blocking::Client::vote_many`.
+    pub fn ballots(&self) -> Vec<(String, HashMap<String, Rank>)> {
+        let mut rng = Rng(self.seed);
+        (1..=self.voters)
+            .map(|voter| {
+                let order = match self.distribution {
+                    Distribution::Random => random(&mut rng, self.choices.len()),
+                    Distribution::Polarized => polarized(&mut rng, self.choices.len()),
+                    Distribution::SinglePeaked => single_peaked(&mut rng, self.choices.len()),
+                };
+                let ranking = order.into_iter().map(|i| self.choices[i].clone()).collect();
+                (format!("voter-{}", voter), ordered_ballot(ranking))
+            })
+            .collect()
+    }
+
+    /// Generates the ballots and submits them to the voting with `Client::vote_many`.
+    ///
+    /// Returns the voter IDs with the results of their votes, in the order of the voters.
+    pub async fn submit(
+        &self,
+        client: &Client,
+        voting_id: &str,
+        options: &BulkOptions,
+    ) -> Vec<(String, Result<bool, ApiError>)> {
+        client.vote_many(voting_id, self.ballots(), options).await
+    }
+}
+
+/// Returns the indices of `n` choices in a uniformly random order.
+fn random(rng: &mut Rng, n: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..n).collect();
+    for i in (1..n).rev() {
+        order.swap(i, rng.below(i + 1));
+    }
+    order
+}
+
+/// Returns the indices of `n` choices in their order or in the reverse order, with some
+/// adjacent choices swapped.
+fn polarized(rng: &mut Rng, n: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..n).collect();
+    if rng.below(2) == 1 {
+        order.reverse();
+    }
+    for i in 1..n {
+        if rng.chance(POLARIZED_NOISE) {
+            order.swap(i - 1, i);
+        }
+    }
+    order
+}
+
+/// Returns the indices of `n` choices starting from a random peak, each followed by one of
+/// the nearest unranked choices on either side of the ranked ones.
+fn single_peaked(rng: &mut Rng, n: usize) -> Vec<usize> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let peak = rng.below(n);
+    let mut order = vec![peak];
+    let (mut left, mut right) = (peak, peak + 1);
+    while order.len() < n {
+        let take_left = match (left > 0, right < n) {
+            (true, true) => rng.below(2) == 0,
+            (can_left, _) => can_left,
+        };
+        if take_left {
+            left -= 1;
+            order.push(left);
+        } else {
+            order.push(right);
+            right += 1;
+        }
+    }
+    order
+}
+
+/// A SplitMix64 pseudorandom number generator, sufficient for simulations and stable
+/// across releases, so seeded simulations are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number lower than `n`, which must not be zero.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Returns `true` with the given probability.
+    fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn choices() -> Vec<String> {
+        ["Hume", "Kant", "Locke", "Spinoza", "Wittgenstein"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    /// Returns the choices of a ballot from the most preferred.
+    fn ranking(ballot: &HashMap<String, Rank>) -> Vec<&str> {
+        let mut ranking: Vec<(&String, &Rank)> = ballot.iter().collect();
+        ranking.sort_by_key(|(_, rank)| **rank);
+        ranking
+            .into_iter()
+            .map(|(choice, _)| choice.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn ballots_test() {
+        for distribution in [
+            Distribution::Random,
+            Distribution::Polarized,
+            Distribution::SinglePeaked,
+        ] {
+            let simulation = Simulation::new(choices())
+                .distribution(distribution)
+                .voters(50)
+                .seed(7);
+            let ballots = simulation.ballots();
+            assert_eq!(ballots.len(), 50);
+            assert_eq!(ballots[0].0, "voter-1");
+            assert_eq!(ballots[49].0, "voter-50");
+
+            // every choice is ranked once, with strictly increasing ranks
+            for (_, ballot) in &ballots {
+                let mut ranks: Vec<u32> = ballot.values().map(|rank| rank.get()).collect();
+                ranks.sort_unstable();
+                assert_eq!(ranks, vec![1, 2, 3, 4, 5]);
+            }
+
+            // the same seed generates the same ballots
+            assert_eq!(simulation.ballots(), ballots);
+        }
+
+        let ballots = Simulation::new(Vec::new()).voters(3).ballots();
+        assert!(ballots.iter().all(|(_, ballot)| ballot.is_empty()));
+    }
+
+    #[test]
+    fn single_peaked_test() {
+        let choices = choices();
+        let ballots = Simulation::new(choices.clone())
+            .distribution(Distribution::SinglePeaked)
+            .voters(200)
+            .seed(1)
+            .ballots();
+
+        // every choice is adjacent on the axis to the choices ranked above it
+        let position = |choice: &str| choices.iter().position(|c| c == choice).unwrap();
+        for (_, ballot) in &ballots {
+            let positions: Vec<usize> = ranking(ballot).into_iter().map(position).collect();
+            for k in 1..=positions.len() {
+                let ranked = &positions[..k];
+                let min = ranked.iter().min().unwrap();
+                let max = ranked.iter().max().unwrap();
+                assert_eq!(max - min + 1, k);
+            }
+        }
+    }
+
+    #[test]
+    fn polarized_test() {
+        let ballots = Simulation::new(choices())
+            .distribution(Distribution::Polarized)
+            .voters(400)
+            .seed(3)
+            .ballots();
+
+        let first = |choice: &str| {
+            ballots
+                .iter()
+                .filter(|(_, ballot)| ranking(ballot)[0] == choice)
+                .count()
+        };
+        // the voters mostly prefer one of the two ends
+        assert!(first("Hume") + first("Wittgenstein") > 300);
+        assert!(first("Hume") > 100);
+        assert!(first("Wittgenstein") > 100);
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::ClientError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Marks a response of a client built with `ClientBuilder::strict_responses`, in the
+/// response extensions.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StrictResponses;
+
+/// Deserializes a response body, failing if it has fields the model does not know, or
+/// misses fields of the model.
+///
+/// The deserialized value is serialized again and compared with the body, so fields
+/// ignored by serde and fields filled with their defaults are both detected. Fields which
+/// are `null` in the body are accepted for optional fields the model does not serialize.
+pub(crate) fn from_slice<T>(body: &[u8]) -> Result<T, ClientError>
+where
+    T: DeserializeOwned + Serialize,
+{
+    let value: Value = serde_json::from_slice(body).map_err(unexpected)?;
+    let data = T::deserialize(&value).map_err(unexpected)?;
+    let expected = serde_json::to_value(&data).map_err(unexpected)?;
+    compare(&value, &expected, "").map_err(ClientError::UnexpectedResponse)?;
+    Ok(data)
+}
+
+fn unexpected(err: serde_json::Error) -> ClientError {
+    ClientError::UnexpectedResponse(err.to_string())
+}
+
+/// Compares the fields of the received and the expected values, returning the first
+/// difference.
+fn compare(actual: &Value, expected: &Value, path: &str) -> Result<(), String> {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => {
+            for (key, value) in actual {
+                let field = field_path(path, key);
+                match expected.get(key) {
+                    Some(expected) => compare(value, expected, &field)?,
+                    None if value.is_null() => {}
+                    None => return Err(format!("unknown field `{}`", field)),
+                }
+            }
+            for key in expected.keys() {
+                if !actual.contains_key(key) {
+                    return Err(format!("missing field `{}`", field_path(path, key)));
+                }
+            }
+            Ok(())
+        }
+        (Value::Array(actual), Value::Array(expected)) => actual
+            .iter()
+            .zip(expected)
+            .enumerate()
+            .try_for_each(|(i, (actual, expected))| {
+                compare(actual, expected, &format!("{}[{}]", path, i))
+            }),
+        _ => Ok(()),
+    }
+}
+
+fn field_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Voting, VotingResults};
+
+    #[test]
+    fn from_slice_test() {
+        let voting: Voting =
+            from_slice(br#"{"id":"abc","choices":["Kant"],"title":null}"#).unwrap();
+        assert_eq!(voting.id, "abc");
+
+        let err =
+            from_slice::<Voting>(br#"{"id":"abc","choices":["Kant"],"tags":[]}"#).unwrap_err();
+        assert_eq!(err.to_string(), "Unexpected Response: unknown field `tags`");
+
+        let err = from_slice::<Voting>(br#"{"id":"abc"}"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unexpected Response: missing field `choices`"
+        );
+
+        let err = from_slice::<VotingResults>(
+            br#"{"tie":false,"results":[{"choice":"Kant","index":0,"wins":0,"percentage":0,"strength":0,"advantage":0,"x":1}],"duels":null}"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unexpected Response: unknown field `results[0].x`"
+        );
+
+        // optional fields are required in the strict mode
+        let err = from_slice::<VotingResults>(br#"{"tie":false,"results":[]}"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unexpected Response: missing field `duels`"
+        );
+    }
+}
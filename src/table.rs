@@ -0,0 +1,181 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::report::{escape_markdown, standings};
+use crate::VotingResults;
+use std::fmt::Write;
+
+const HEADERS: [&str; 4] = ["Rank", "Choice", "Wins", "Percentage"];
+
+/// Output format of a results table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    /// A Markdown table, for chat messages, issues and pull requests.
+    Markdown,
+    /// A plain text table with aligned columns, for terminal output.
+    Ascii,
+}
+
+/// Renders the results of a voting as a table with the rank, choice, wins and percentage
+/// of every choice, ordered by the rank.
+///
+/// Choices with the same number of wins share the same rank, as in
+/// `VotingSummary::standings`. Unlike `VotingSummary`, the table does not need the voting.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{render_table, schulze, Rank, TableFormat};
+/// use std::collections::HashMap;
+///
+/// let choices = vec!["Einstein".to_string(), "Newton".to_string()];
+/// let ballots = [HashMap::from([("Newton".to_string(), Rank::HIGHEST)])];
+/// let results = schulze::compute(&choices, &ballots);
+///
+/// assert_eq!(
+///     render_table(&results, TableFormat::Ascii),
+///     "Rank  Choice    Wins  Percentage\n\
+///      ----  --------  ----  ----------\n   \
+///         1  Newton       1     100.00%\n   \
+///         2  Einstein     0       0.00%\n"
+/// );
+/// ```
+pub fn render_table(results: &VotingResults, format: TableFormat) -> String {
+    let rows: Vec<[String; 4]> = standings(results)
+        .into_iter()
+        .map(|standing| {
+            [
+                standing.rank.to_string(),
+                standing.result.choice.clone(),
+                standing.result.wins.to_string(),
+                format!("{}%", standing.result.formatted_percentage(2)),
+            ]
+        })
+        .collect();
+
+    match format {
+        TableFormat::Markdown => render_markdown(&rows),
+        TableFormat::Ascii => render_ascii(&rows),
+    }
+}
+
+fn render_markdown(rows: &[[String; 4]]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "| {} |", HEADERS.join(" | "));
+    let _ = writeln!(out, "| ---: | :--- | ---: | ---: |");
+    for [rank, choice, wins, percentage] in rows {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} |",
+            rank,
+            escape_markdown(choice),
+            wins,
+            percentage
+        );
+    }
+    out
+}
+
+fn render_ascii(rows: &[[String; 4]]) -> String {
+    let mut widths = HEADERS.map(|header| header.chars().count());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    let header = HEADERS.map(String::from);
+    write_ascii_row(&mut out, &header, &widths, false);
+    let separator = widths.map(|width| "-".repeat(width));
+    write_ascii_row(&mut out, &separator, &widths, false);
+    for row in rows {
+        write_ascii_row(&mut out, row, &widths, true);
+    }
+    out
+}
+
+/// Writes a row of the ASCII table. Numeric columns are aligned to the right, except in the
+/// header and the separator.
+fn write_ascii_row(out: &mut String, row: &[String; 4], widths: &[usize; 4], align: bool) {
+    let mut line = String::new();
+    for (column, (cell, &width)) in row.iter().zip(widths).enumerate() {
+        if column > 0 {
+            line.push_str("  ");
+        }
+        let padding = " ".repeat(width - cell.chars().count());
+        if align && column != 1 {
+            line.push_str(&padding);
+            line.push_str(cell);
+        } else {
+            line.push_str(cell);
+            line.push_str(&padding);
+        }
+    }
+    let _ = writeln!(out, "{}", line.trim_end());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChoiceIndex, VotingResult};
+
+    fn result(choice: &str, index: usize, wins: i32, percentage: u8) -> VotingResult {
+        VotingResult {
+            choice: choice.to_string(),
+            index: ChoiceIndex::new(index),
+            wins,
+            percentage: percentage.into(),
+            strength: 0,
+            advantage: 0,
+        }
+    }
+
+    fn results() -> VotingResults {
+        VotingResults {
+            tie: true,
+            results: vec![
+                result("Kant", 1, 1, 50),
+                result("Spinoza | Hume", 0, 1, 50),
+                result("Schrödinger", 2, 0, 0),
+            ],
+            duels: None,
+        }
+    }
+
+    #[test]
+    fn render_markdown_test() {
+        assert_eq!(
+            render_table(&results(), TableFormat::Markdown),
+            "| Rank | Choice | Wins | Percentage |\n\
+             | ---: | :--- | ---: | ---: |\n\
+             | 1 | Spinoza \\| Hume | 1 | 50.00% |\n\
+             | 1 | Kant | 1 | 50.00% |\n\
+             | 3 | Schrödinger | 0 | 0.00% |\n"
+        );
+    }
+
+    #[test]
+    fn render_ascii_test() {
+        assert_eq!(
+            render_table(&results(), TableFormat::Ascii),
+            "Rank  Choice          Wins  Percentage\n\
+             ----  --------------  ----  ----------\n   \
+                1  Spinoza | Hume     1      50.00%\n   \
+                1  Kant               1      50.00%\n   \
+                3  Schrödinger        0       0.00%\n"
+        );
+
+        let empty = VotingResults {
+            tie: false,
+            results: vec![],
+            duels: None,
+        };
+        assert_eq!(
+            render_table(&empty, TableFormat::Ascii),
+            "Rank  Choice  Wins  Percentage\n----  ------  ----  ----------\n"
+        );
+    }
+}
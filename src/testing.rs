@@ -0,0 +1,32 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+//! Utilities for testing applications that use the Direct Decisions API offline.
+//!
+//! This module is available with the `test-util` feature, which is meant to be enabled
+//! only in the `dev-dependencies` of the application:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! ddclient-rs = { version = "0.1.3", features = ["test-util"] }
+//! ```
+
+mod cassette;
+mod fake_server;
+mod mock_client;
+
+pub use cassette::Cassette;
+pub use fake_server::FakeServer;
+pub use mock_client::MockClient;
+
+use crate::{ApiError, BadRequestError};
+
+fn not_found() -> ApiError {
+    ApiError::NotFound
+}
+
+fn bad_request(error: BadRequestError) -> ApiError {
+    ApiError::BadRequest(vec![error])
+}
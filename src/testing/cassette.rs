@@ -0,0 +1,226 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::{ClientError, MockResponse, Transport};
+use async_trait::async_trait;
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::{Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CassetteRequest {
+    method: String,
+    path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+}
+
+impl CassetteRequest {
+    fn new(request: &Request) -> Self {
+        let url = request.url();
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        Self {
+            method: request.method().to_string(),
+            path,
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|body| String::from_utf8_lossy(body).into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CassetteResponse {
+    status: u16,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl CassetteResponse {
+    fn to_response(&self) -> Result<Response, ClientError> {
+        let status = StatusCode::from_u16(self.status)
+            .map_err(|err| ClientError::Transport(Box::new(err)))?;
+        let mut response = MockResponse::new(status);
+        for (name, value) in &self.headers {
+            let name = HeaderName::try_from(name.as_str())
+                .map_err(|err| ClientError::Transport(Box::new(err)))?;
+            let value = HeaderValue::try_from(value.as_str())
+                .map_err(|err| ClientError::Transport(Box::new(err)))?;
+            response = response.header(name, value);
+        }
+        Ok(response.body(self.body.clone()).into_response())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: CassetteRequest,
+    response: CassetteResponse,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Fixture {
+    interactions: Vec<Interaction>,
+}
+
+enum Mode {
+    Record(Arc<dyn Transport>),
+    Replay,
+}
+
+struct CassetteState {
+    fixture: Fixture,
+    used: Vec<bool>,
+}
+
+/// A `Transport` that records requests and responses to a fixture file, or replays the
+/// responses from it without network access.
+///
+/// In record mode, the requests are sent with the given transport, usually a Reqwest
+/// client, and every request and response pair is written to the fixture file as JSON.
+/// The `Authorization` header and the other request headers are not recorded.
+///
+/// In replay mode, each request is answered with the first recorded response to a request
+/// with the same method, path, query and body that was not replayed yet. Requests without
+/// a matching recording fail with `ClientError::Transport`.
+///
+/// This type is available with the `test-util` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddclient_rs::testing::Cassette;
+/// use ddclient_rs::Client;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let cassette = if std::env::var("RECORD").is_ok() {
+///         Cassette::record("tests/fixtures/votings.json", reqwest::Client::new()).unwrap()
+///     } else {
+///         Cassette::replay("tests/fixtures/votings.json").unwrap()
+///     };
+///
+///     let client = Client::builder("my-api-key".to_string())
+///         .transport(cassette)
+///         .try_build()
+///         .unwrap();
+///     let voting = client.get_voting("voting-id").await;
+///     // Handle result...
+/// }
+/// ```
+pub struct Cassette {
+    path: PathBuf,
+    mode: Mode,
+    state: Mutex<CassetteState>,
+}
+
+impl Cassette {
+    /// Creates a `Cassette` which sends the requests with the `transport`, and records them
+    /// to the fixture file at `path`, replacing its previous content.
+    ///
+    /// Returns an error if the fixture file cannot be written.
+    pub fn record(path: impl AsRef<Path>, transport: impl Transport + 'static) -> io::Result<Self> {
+        let cassette = Self {
+            path: path.as_ref().to_path_buf(),
+            mode: Mode::Record(Arc::new(transport)),
+            state: Mutex::new(CassetteState {
+                fixture: Fixture::default(),
+                used: Vec::new(),
+            }),
+        };
+        cassette.save(&cassette.state.lock().unwrap().fixture)?;
+        Ok(cassette)
+    }
+
+    /// Creates a `Cassette` which replays the responses recorded in the fixture file at
+    /// `path`.
+    ///
+    /// Returns an error if the fixture file cannot be read or parsed.
+    pub fn replay(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let fixture: Fixture = serde_json::from_slice(&std::fs::read(&path)?)?;
+        Ok(Self {
+            path,
+            mode: Mode::Replay,
+            state: Mutex::new(CassetteState {
+                used: vec![false; fixture.interactions.len()],
+                fixture,
+            }),
+        })
+    }
+
+    fn save(&self, fixture: &Fixture) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_vec_pretty(fixture)?)
+    }
+}
+
+#[async_trait]
+impl Transport for Cassette {
+    async fn execute(&self, request: Request) -> Result<Response, ClientError> {
+        let recorded = CassetteRequest::new(&request);
+
+        let transport = match &self.mode {
+            Mode::Record(transport) => transport,
+            Mode::Replay => {
+                let mut state = self.state.lock().unwrap();
+                let CassetteState { fixture, used } = &mut *state;
+                let position = fixture
+                    .interactions
+                    .iter()
+                    .zip(used.iter())
+                    .position(|(interaction, used)| !used && interaction.request == recorded)
+                    .ok_or_else(|| {
+                        ClientError::Transport(
+                            format!(
+                                "no recorded response for {} {}",
+                                recorded.method, recorded.path
+                            )
+                            .into(),
+                        )
+                    })?;
+                used[position] = true;
+                return fixture.interactions[position].response.to_response();
+            }
+        };
+
+        let response = transport.execute(request).await?;
+        let status = response.status();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let body = response
+            .bytes()
+            .await
+            .map_err(ClientError::HttpRequestError)?;
+
+        let response = CassetteResponse {
+            status: status.as_u16(),
+            headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        };
+        let mut state = self.state.lock().unwrap();
+        state.fixture.interactions.push(Interaction {
+            request: recorded,
+            response: response.clone(),
+        });
+        self.save(&state.fixture)
+            .map_err(|err| ClientError::Transport(Box::new(err)))?;
+
+        response.to_response()
+    }
+}
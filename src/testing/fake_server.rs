@@ -0,0 +1,353 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use super::{bad_request, not_found, MockClient};
+use crate::client::{
+    Ballot, OkResponse, SetChoiceRequest, SetChoiceResponse, VoteResponse, VotingRequest,
+};
+use crate::rate::{HEADER_RATE_LIMIT, HEADER_RATE_REMAINING, HEADER_RATE_RESET, HEADER_RATE_RETRY};
+#[cfg(feature = "experimental")]
+use crate::ListOptions;
+use crate::{
+    ApiError, ApiErrorEntry, ApiErrorResponse, BadRequestError, DirectDecisionsApi, CONTENT_TYPE,
+    HEADER_REQUEST_ID,
+};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::convert::Infallible;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+const DEFAULT_RATE_LIMIT: u32 = 100;
+
+#[derive(Debug)]
+struct RateWindow {
+    limit: u32,
+    remaining: u32,
+    reset: Instant,
+}
+
+impl RateWindow {
+    fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            remaining: limit,
+            reset: Instant::now() + RATE_WINDOW,
+        }
+    }
+
+    /// Counts a request against the rate limit, and reports whether it is allowed.
+    fn acquire(&mut self) -> bool {
+        if Instant::now() >= self.reset {
+            *self = Self::new(self.limit);
+        }
+        if self.remaining == 0 {
+            return false;
+        }
+        self.remaining -= 1;
+        true
+    }
+}
+
+#[derive(Debug)]
+struct ServerState {
+    api: MockClient,
+    rate: Mutex<RateWindow>,
+    requests: AtomicU64,
+}
+
+/// A local HTTP server emulating the v1 endpoints of the Direct Decisions API.
+///
+/// The server stores the votings and ballots in a `MockClient`, so it supports all
+/// operations of the API, with the same validation errors and Schulze results. Requests
+/// without a bearer token are rejected with `401 Unauthorized`, and every response carries
+/// an `X-Request-Id` header and the rate limit headers. Once the rate limit of the current one minute window is
+/// exhausted, requests are rejected with `429 Too Many Requests`.
+///
+/// It is useful for end-to-end tests of applications that only take the base URL of the
+/// API. The server shuts down when the `FakeServer` is dropped.
+///
+/// This type is available with the `test-util` feature.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::testing::FakeServer;
+/// use ddclient_rs::Client;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let server = FakeServer::start().await;
+///     let client = Client::builder("my-api-key".to_string())
+///         .api_url(server.base_url())
+///         .try_build()
+///         .unwrap();
+///
+///     let voting = client
+///         .create_voting(vec!["Einstein".to_string(), "Newton".to_string()])
+///         .await
+///         .unwrap();
+///     assert_eq!(client.get_voting(&voting.id).await.unwrap(), voting);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FakeServer {
+    address: SocketAddr,
+    state: Arc<ServerState>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl FakeServer {
+    /// Starts a new `FakeServer` on a random local port, with a rate limit of 100 requests
+    /// per minute.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the server cannot bind a local port, or if it is not called from within a
+    /// Tokio runtime.
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind fake server");
+        let address = listener.local_addr().expect("Failed to bind fake server");
+        let state = Arc::new(ServerState {
+            api: MockClient::new(),
+            rate: Mutex::new(RateWindow::new(DEFAULT_RATE_LIMIT)),
+            requests: AtomicU64::new(0),
+        });
+
+        let service_state = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = service_state.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let state = state.clone();
+                    async move { Ok::<_, Infallible>(handle(&state, request).await) }
+                }))
+            }
+        });
+
+        let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+        let server = Server::from_tcp(listener)
+            .expect("Failed to start fake server")
+            .serve(make_service)
+            .with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            });
+        tokio::spawn(server);
+
+        Self {
+            address,
+            state,
+            shutdown: Some(shutdown),
+        }
+    }
+
+    /// Returns the base URL of the server, to be set with `ClientBuilder::api_url`.
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Returns the `MockClient` that stores the data of the server, for seeding votings
+    /// and inspecting them in tests.
+    pub fn api(&self) -> &MockClient {
+        &self.state.api
+    }
+
+    /// Sets the number of requests allowed per minute, and starts a new rate limit window.
+    pub fn set_rate_limit(&self, limit: u32) {
+        *self.state.rate.lock().unwrap() = RateWindow::new(limit);
+    }
+}
+
+impl Drop for FakeServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+async fn handle(state: &ServerState, request: Request<Body>) -> Response<Body> {
+    let (allowed, rate) = {
+        let mut window = state.rate.lock().unwrap();
+        let allowed = window.acquire();
+        let reset = window
+            .reset
+            .saturating_duration_since(Instant::now())
+            .as_secs();
+        (allowed, (window.limit, window.remaining, reset))
+    };
+
+    let authorized = request
+        .headers()
+        .get(hyper::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| !token.is_empty());
+
+    let result = if !authorized {
+        Err(ApiError::Unauthorized)
+    } else if !allowed {
+        Err(ApiError::RateLimited {
+            rate: None,
+            retry_after: None,
+        })
+    } else {
+        route(&state.api, request).await
+    };
+
+    let (status, body) = match result {
+        Ok(body) => (StatusCode::OK, body),
+        Err(err) => error_response(err),
+    };
+
+    let (limit, remaining, reset) = rate;
+    let request_id = state.requests.fetch_add(1, Ordering::Relaxed) + 1;
+    Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, CONTENT_TYPE)
+        .header(HEADER_REQUEST_ID, format!("req-{}", request_id))
+        .header(HEADER_RATE_LIMIT, limit)
+        .header(HEADER_RATE_REMAINING, remaining)
+        .header(HEADER_RATE_RESET, reset)
+        .header(HEADER_RATE_RETRY, reset)
+        .body(Body::from(body.to_string()))
+        .expect("Invalid fake server response")
+}
+
+async fn route(api: &MockClient, request: Request<Body>) -> Result<Value, ApiError> {
+    let (parts, body) = request.into_parts();
+    let body = hyper::body::to_bytes(body)
+        .await
+        .map_err(|_| bad_request(BadRequestError::InvalidData))?;
+    let segments: Vec<String> = parts
+        .uri
+        .path()
+        .trim_matches('/')
+        .split('/')
+        .map(|segment| url_escape::decode(segment).into_owned())
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+    let ["v1", "votings", rest @ ..] = segments.as_slice() else {
+        return Err(not_found());
+    };
+
+    match (&parts.method, rest) {
+        #[cfg(feature = "experimental")]
+        (&Method::GET, []) => to_json(api.list_votings(&list_options(parts.uri.query())).await?),
+        (&Method::POST, []) => {
+            let request: VotingRequest = from_json(&body)?;
+            to_json(api.create_voting(request.choices).await?)
+        }
+        (&Method::GET, [id]) => to_json(api.get_voting(id).await?),
+        (&Method::DELETE, [id]) => {
+            api.delete_voting(id).await?;
+            ok()
+        }
+        (&Method::POST, [id, "choices"]) => {
+            let request: SetChoiceRequest = from_json(&body)?;
+            let choices = api.set_choice(id, &request.choice, request.index).await?;
+            to_json(SetChoiceResponse { choices })
+        }
+        #[cfg(feature = "experimental")]
+        (&Method::GET, [id, "ballots"]) => to_json(
+            api.list_ballots(id, &list_options(parts.uri.query()))
+                .await?,
+        ),
+        (&Method::POST, [id, "ballots", voter_id]) => {
+            let request: Ballot = from_json(&body)?;
+            let revoted = api.vote(id, voter_id, request.ballot).await?;
+            to_json(VoteResponse { revoted })
+        }
+        (&Method::GET, [id, "ballots", voter_id]) => {
+            let ballot = api.get_ballot(id, voter_id).await?;
+            to_json(Ballot { ballot })
+        }
+        (&Method::DELETE, [id, "ballots", voter_id]) => {
+            api.unvote(id, voter_id).await?;
+            ok()
+        }
+        (&Method::GET, [id, "results"]) => to_json(api.get_voting_results(id).await?),
+        (&Method::GET, [id, "results", "duels"]) => {
+            to_json(api.get_voting_results_duels(id).await?)
+        }
+        (_, [] | [_] | [_, "choices" | "ballots" | "results"])
+        | (_, [_, "ballots", _] | [_, "results", "duels"]) => Err(ApiError::MethodNotAllowed),
+        _ => Err(not_found()),
+    }
+}
+
+#[cfg(feature = "experimental")]
+fn list_options(query: Option<&str>) -> ListOptions {
+    let mut options = ListOptions::new();
+    let Some(query) = query else {
+        return options;
+    };
+    for pair in query.split('&') {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = url_escape::decode(value).into_owned();
+        match name {
+            "limit" => {
+                if let Ok(limit) = value.parse() {
+                    options = options.limit(limit);
+                }
+            }
+            "cursor" => options = options.cursor(value),
+            _ => {}
+        }
+    }
+    options
+}
+
+fn from_json<T: DeserializeOwned>(body: &[u8]) -> Result<T, ApiError> {
+    serde_json::from_slice(body).map_err(|_| bad_request(BadRequestError::InvalidData))
+}
+
+fn to_json<T: Serialize>(value: T) -> Result<Value, ApiError> {
+    serde_json::to_value(value).map_err(|err| ApiError::InternalServerError(err.to_string()))
+}
+
+fn ok() -> Result<Value, ApiError> {
+    to_json(OkResponse {
+        code: 200,
+        message: "OK".to_string(),
+    })
+}
+
+fn error_response(err: ApiError) -> (StatusCode, Value) {
+    let (status, errors) = match err {
+        ApiError::BadRequest(errors) => (
+            StatusCode::BAD_REQUEST,
+            errors
+                .iter()
+                .filter_map(|err| serde_json::to_value(err).ok())
+                .filter_map(|err| err.as_str().map(String::from))
+                .map(ApiErrorEntry::Message)
+                .collect(),
+        ),
+        ApiError::NotFound => (StatusCode::NOT_FOUND, Vec::new()),
+        ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, Vec::new()),
+        ApiError::RateLimited { .. } => (StatusCode::TOO_MANY_REQUESTS, Vec::new()),
+        ApiError::MethodNotAllowed => (StatusCode::METHOD_NOT_ALLOWED, Vec::new()),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, Vec::new()),
+    };
+
+    let body = ApiErrorResponse {
+        code: status.as_u16().into(),
+        message: status.canonical_reason().unwrap_or_default().to_string(),
+        errors,
+    };
+    (
+        status,
+        serde_json::to_value(body).expect("Invalid fake server error response"),
+    )
+}
@@ -0,0 +1,295 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use super::{bad_request, not_found};
+use crate::schulze::compute_results;
+use crate::validation::{validate_choice, validate_choices};
+use crate::{
+    ApiError, BadRequestError, Choices, DirectDecisionsApi, Rank, ReportFormat, VoterBallot,
+    Voting, VotingResults, VotingSummary,
+};
+#[cfg(feature = "experimental")]
+use crate::{BallotsPage, ListOptions, VotingsPage};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+struct MockVoting {
+    id: String,
+    choices: Vec<String>,
+    ballots: Vec<VoterBallot>,
+}
+
+impl MockVoting {
+    fn voting(&self) -> Voting {
+        Voting {
+            id: self.id.clone(),
+            choices: Choices::new(self.choices.clone()),
+            #[cfg(feature = "experimental")]
+            title: None,
+            #[cfg(feature = "experimental")]
+            description: None,
+            #[cfg(feature = "experimental")]
+            status: None,
+        }
+    }
+
+    fn results(&self, duels: bool) -> VotingResults {
+        let ballots: Vec<&HashMap<String, Rank>> = self.ballots.iter().map(|b| &b.ballot).collect();
+        compute_results(&self.choices, &ballots, duels)
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    votings: Vec<MockVoting>,
+    next_id: u64,
+}
+
+impl MockState {
+    fn voting_mut(&mut self, id: &str) -> Result<&mut MockVoting, ApiError> {
+        self.votings
+            .iter_mut()
+            .find(|v| v.id == id)
+            .ok_or_else(not_found)
+    }
+}
+
+/// An in-memory implementation of `DirectDecisionsApi` for offline tests.
+///
+/// The `MockClient` stores votings and ballots in memory and validates the requests like
+/// the API does, returning `ApiError::NotFound` for unknown votings and ballots, and
+/// `ApiError::BadRequest` for invalid choices and ballots. Results are computed from the
+/// submitted ballots with the Schulze method.
+///
+/// Clones of a `MockClient` share the same votings.
+///
+/// This type is available with the `test-util` feature.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::testing::MockClient;
+/// use ddclient_rs::{DirectDecisionsApi, Rank};
+/// use std::collections::HashMap;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let client = MockClient::new();
+///     let voting = client
+///         .create_voting(vec!["Einstein".to_string(), "Newton".to_string()])
+///         .await
+///         .unwrap();
+///
+///     let ballot = HashMap::from([("Newton".to_string(), Rank::HIGHEST)]);
+///     client.vote(&voting.id, "voter", ballot).await.unwrap();
+///
+///     let results = client.get_voting_results(&voting.id).await.unwrap();
+///     assert_eq!(results.results[0].choice, "Newton");
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockClient {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockClient {
+    /// Creates a new `MockClient` without votings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_voting<T>(
+        &self,
+        id: &str,
+        f: impl FnOnce(&mut MockVoting) -> Result<T, ApiError>,
+    ) -> Result<T, ApiError> {
+        let mut state = self.state.lock().unwrap();
+        f(state.voting_mut(id)?)
+    }
+}
+
+/// Returns the items of a page selected by the `ListOptions`, and the cursor of the next
+/// page. Cursors are the offsets of the first item on a page.
+#[cfg(feature = "experimental")]
+fn page<T>(items: impl Iterator<Item = T>, options: &ListOptions) -> (Vec<T>, Option<String>) {
+    let offset: usize = options
+        .cursor
+        .as_deref()
+        .and_then(|cursor| cursor.parse().ok())
+        .unwrap_or(0);
+    let limit = options.limit.map_or(usize::MAX, |limit| limit as usize);
+
+    let mut items = items.skip(offset).peekable();
+    let page: Vec<T> = items.by_ref().take(limit).collect();
+    let next_cursor = items
+        .peek()
+        .is_some()
+        .then(|| (offset + page.len()).to_string());
+    (page, next_cursor)
+}
+
+#[async_trait]
+impl DirectDecisionsApi for MockClient {
+    async fn create_voting(&self, choices: Vec<String>) -> Result<Voting, ApiError> {
+        validate_choices(&choices).map_err(bad_request)?;
+
+        let mut state = self.state.lock().unwrap();
+        state.next_id += 1;
+        let voting = MockVoting {
+            id: format!("{:020x}", state.next_id),
+            choices,
+            ballots: Vec::new(),
+        };
+        let result = voting.voting();
+        state.votings.push(voting);
+        Ok(result)
+    }
+
+    async fn get_voting(&self, id: &str) -> Result<Voting, ApiError> {
+        self.with_voting(id, |voting| Ok(voting.voting()))
+    }
+
+    #[cfg(feature = "experimental")]
+    async fn list_votings(&self, options: &ListOptions) -> Result<VotingsPage, ApiError> {
+        let state = self.state.lock().unwrap();
+        let (votings, next_cursor) = page(state.votings.iter().map(MockVoting::voting), options);
+        Ok(VotingsPage {
+            votings,
+            next_cursor,
+            total: Some(state.votings.len() as u64),
+        })
+    }
+
+    async fn delete_voting(&self, id: &str) -> Result<(), ApiError> {
+        let mut state = self.state.lock().unwrap();
+        state.voting_mut(id)?;
+        state.votings.retain(|v| v.id != id);
+        Ok(())
+    }
+
+    async fn set_choice(
+        &self,
+        voting_id: &str,
+        choice: &str,
+        index: i32,
+    ) -> Result<Choices, ApiError> {
+        validate_choice(choice).map_err(bad_request)?;
+        self.with_voting(voting_id, |voting| {
+            let existing = voting.choices.iter().position(|c| c == choice);
+            if index == -1 {
+                let position = existing.ok_or_else(not_found)?;
+                voting.choices.remove(position);
+                return Ok(Choices::new(voting.choices.clone()));
+            }
+
+            let mut choices = voting.choices.clone();
+            if let Some(position) = existing {
+                choices.remove(position);
+            }
+            // index 0 appends the choice and index equal to the number of choices prepends it
+            let position = usize::try_from(index)
+                .ok()
+                .and_then(|index| choices.len().checked_sub(index))
+                .ok_or_else(|| bad_request(BadRequestError::InvalidData))?;
+            choices.insert(position, choice.to_string());
+            voting.choices = choices;
+            Ok(Choices::new(voting.choices.clone()))
+        })
+    }
+
+    async fn vote(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+        ballot: HashMap<String, Rank>,
+    ) -> Result<bool, ApiError> {
+        self.with_voting(voting_id, |voting| {
+            if voter_id.is_empty() {
+                return Err(bad_request(BadRequestError::InvalidVoterID));
+            }
+            if ballot.is_empty() {
+                return Err(bad_request(BadRequestError::BallotRequired));
+            }
+            if ballot.keys().any(|choice| !voting.choices.contains(choice)) {
+                return Err(bad_request(BadRequestError::InvalidData));
+            }
+
+            match voting.ballots.iter_mut().find(|b| b.voter_id == voter_id) {
+                Some(existing) => {
+                    existing.ballot = ballot;
+                    Ok(true)
+                }
+                None => {
+                    voting.ballots.push(VoterBallot {
+                        voter_id: voter_id.to_string(),
+                        ballot,
+                    });
+                    Ok(false)
+                }
+            }
+        })
+    }
+
+    async fn unvote(&self, voting_id: &str, voter_id: &str) -> Result<(), ApiError> {
+        self.with_voting(voting_id, |voting| {
+            voting.ballots.retain(|b| b.voter_id != voter_id);
+            Ok(())
+        })
+    }
+
+    async fn get_ballot(
+        &self,
+        voting_id: &str,
+        voter_id: &str,
+    ) -> Result<HashMap<String, Rank>, ApiError> {
+        self.with_voting(voting_id, |voting| {
+            voting
+                .ballots
+                .iter()
+                .find(|b| b.voter_id == voter_id)
+                .map(|b| b.ballot.clone())
+                .ok_or_else(not_found)
+        })
+    }
+
+    #[cfg(feature = "experimental")]
+    async fn list_ballots(
+        &self,
+        voting_id: &str,
+        options: &ListOptions,
+    ) -> Result<BallotsPage, ApiError> {
+        self.with_voting(voting_id, |voting| {
+            let ballots = voting.ballots.iter().map(|b| VoterBallot {
+                voter_id: b.voter_id.clone(),
+                ballot: b.ballot.clone(),
+            });
+            let (ballots, next_cursor) = page(ballots, options);
+            Ok(BallotsPage {
+                ballots,
+                next_cursor,
+            })
+        })
+    }
+
+    async fn get_voting_results(&self, voting_id: &str) -> Result<VotingResults, ApiError> {
+        self.with_voting(voting_id, |voting| Ok(voting.results(false)))
+    }
+
+    async fn get_voting_results_duels(&self, voting_id: &str) -> Result<VotingResults, ApiError> {
+        self.with_voting(voting_id, |voting| Ok(voting.results(true)))
+    }
+
+    async fn summarize_voting(
+        &self,
+        voting_id: &str,
+        format: ReportFormat,
+    ) -> Result<String, ApiError> {
+        self.with_voting(voting_id, |voting| {
+            Ok(VotingSummary::new(voting.voting(), voting.results(true)).render(format))
+        })
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::Rate;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Delays requests when the remaining rate limit drops to a threshold.
+///
+/// Below the threshold, the remaining requests are spread evenly until the rate limit
+/// resets, instead of being sent at once and failing with `429 Too Many Requests`.
+/// When no requests remain, all requests wait for the reset.
+#[derive(Debug)]
+pub(crate) struct Throttle {
+    threshold: u32,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl Throttle {
+    pub(crate) fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    /// Returns how long the next request should wait based on the last known rate.
+    pub(crate) fn delay(&self, rate: Option<&Rate>) -> Duration {
+        let now_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.delay_at(rate, Instant::now(), now_secs)
+    }
+
+    fn delay_at(&self, rate: Option<&Rate>, now: Instant, now_secs: u64) -> Duration {
+        let Some(rate) = rate.filter(|rate| rate.remaining <= self.threshold) else {
+            return Duration::ZERO;
+        };
+
+        let until_reset = Duration::from_secs(rate.reset.saturating_sub(now_secs));
+        if rate.remaining == 0 {
+            return until_reset;
+        }
+
+        let spacing = until_reset / rate.remaining;
+        let mut next_slot = self.next_slot.lock().unwrap();
+        let slot = next_slot.filter(|slot| *slot > now).unwrap_or(now);
+        *next_slot = Some(slot + spacing);
+
+        slot - now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(remaining: u32, reset: u64) -> Rate {
+        Rate {
+            limit: 100,
+            remaining,
+            reset,
+            retry: reset,
+        }
+    }
+
+    #[test]
+    fn no_delay_above_threshold_test() {
+        let throttle = Throttle::new(10);
+        let now = Instant::now();
+
+        assert_eq!(throttle.delay_at(None, now, 1000), Duration::ZERO);
+        assert_eq!(
+            throttle.delay_at(Some(&rate(11, 1010)), now, 1000),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn wait_for_reset_when_exhausted_test() {
+        let throttle = Throttle::new(10);
+        let now = Instant::now();
+
+        assert_eq!(
+            throttle.delay_at(Some(&rate(0, 1010)), now, 1000),
+            Duration::from_secs(10)
+        );
+        // reset already passed
+        assert_eq!(
+            throttle.delay_at(Some(&rate(0, 990)), now, 1000),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn spread_requests_below_threshold_test() {
+        let throttle = Throttle::new(10);
+        let now = Instant::now();
+        let rate = rate(5, 1010);
+
+        assert_eq!(throttle.delay_at(Some(&rate), now, 1000), Duration::ZERO);
+        assert_eq!(
+            throttle.delay_at(Some(&rate), now, 1000),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            throttle.delay_at(Some(&rate), now, 1000),
+            Duration::from_secs(4)
+        );
+
+        // once the scheduled slots are in the past, requests are not delayed
+        let later = now + Duration::from_secs(7);
+        assert_eq!(throttle.delay_at(Some(&rate), later, 1007), Duration::ZERO);
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use async_trait::async_trait;
+use std::error::Error;
+use std::sync::Arc;
+
+/// Provides the API token for each request.
+///
+/// A `TokenProvider` is consulted by the `Client` before every request, which allows
+/// applications that rotate API keys, or fetch them from a secrets manager, to change
+/// the token without rebuilding the client. Implementations are responsible for caching
+/// the token if fetching it is expensive.
+///
+/// Set a provider with `ClientBuilder::token_provider`.
+///
+/// # Examples
+///
+/// ```
+/// use async_trait::async_trait;
+/// use ddclient_rs::{Client, TokenProvider};
+/// use std::error::Error;
+///
+/// struct EnvTokenProvider;
+///
+/// #[async_trait]
+/// impl TokenProvider for EnvTokenProvider {
+///     async fn token(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+///         Ok(std::env::var("DIRECT_DECISIONS_API_KEY")?)
+///     }
+/// }
+///
+/// let client = Client::builder(String::new())
+///     .token_provider(EnvTokenProvider)
+///     .try_build()
+///     .unwrap();
+/// ```
+#[async_trait]
+pub trait TokenProvider: Send + Sync {
+    /// Returns the API token to authenticate the next request with.
+    ///
+    /// An error fails the request with `ClientError::TokenProvider`.
+    async fn token(&self) -> Result<String, Box<dyn Error + Send + Sync>>;
+}
+
+#[async_trait]
+impl<T: TokenProvider + ?Sized> TokenProvider for Arc<T> {
+    async fn token(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        (**self).token().await
+    }
+}
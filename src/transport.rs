@@ -0,0 +1,210 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::ClientError;
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, Request, Response, StatusCode, Url};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Sends the requests built by the `Client`.
+///
+/// The default transport is the Reqwest client of the `Client`. A custom transport can be
+/// set with `ClientBuilder::transport`, for example a `MockTransport` in tests.
+///
+/// A client with an existing middleware stack, such as a
+/// `reqwest_middleware::ClientWithMiddleware`, is adapted with a transport which sends the
+/// requests through it:
+///
+/// ```ignore
+/// struct MiddlewareTransport(reqwest_middleware::ClientWithMiddleware);
+///
+/// #[async_trait]
+/// impl Transport for MiddlewareTransport {
+///     async fn execute(&self, request: Request) -> Result<Response, ClientError> {
+///         self.0
+///             .execute(request)
+///             .await
+///             .map_err(|err| ClientError::Transport(err.into()))
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Sends the request and returns the received response.
+    async fn execute(&self, request: Request) -> Result<Response, ClientError>;
+}
+
+#[async_trait]
+impl Transport for reqwest::Client {
+    async fn execute(&self, request: Request) -> Result<Response, ClientError> {
+        reqwest::Client::execute(self, request)
+            .await
+            .map_err(ClientError::HttpRequestError)
+    }
+}
+
+#[async_trait]
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    async fn execute(&self, request: Request) -> Result<Response, ClientError> {
+        (**self).execute(request).await
+    }
+}
+
+/// A canned response returned by a `MockTransport`.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl MockResponse {
+    /// Creates a new `MockResponse` with the given status and an empty body.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Adds a header to the response.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Sets the body of the response to the given value serialized as JSON.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value cannot be serialized.
+    pub fn json<T: serde::Serialize + ?Sized>(mut self, body: &T) -> Self {
+        self.headers.insert(
+            reqwest::header::CONTENT_TYPE,
+            HeaderValue::from_static(crate::CONTENT_TYPE),
+        );
+        self.body = serde_json::to_vec(body).expect("Invalid mock response body");
+        self
+    }
+
+    /// Sets the raw body of the response.
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub(crate) fn into_response(self) -> Response {
+        let mut response = http::Response::new(self.body);
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers;
+        Response::from(response)
+    }
+}
+
+/// A request received by a `MockTransport`.
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Option<Vec<u8>>,
+}
+
+impl RecordedRequest {
+    /// Returns the body of the request parsed as JSON, if it has one.
+    pub fn json(&self) -> Option<serde_json::Value> {
+        serde_json::from_slice(self.body.as_ref()?).ok()
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    responses: VecDeque<MockResponse>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// A `Transport` that returns canned responses instead of sending requests over the network.
+///
+/// Responses are returned in the order they were enqueued, and every received request is
+/// recorded, so code using the `Client` can be unit tested without a mock HTTP server.
+/// When no response is enqueued, requests fail with `ClientError::Transport`.
+///
+/// Clones of a `MockTransport` share the enqueued responses and recorded requests.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{Client, MockResponse, MockTransport};
+/// use reqwest::StatusCode;
+/// use serde_json::json;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let transport = MockTransport::new();
+///     transport.push(
+///         MockResponse::new(StatusCode::OK).json(&json!({"id": "1", "choices": ["A", "B"]})),
+///     );
+///
+///     let client = Client::builder("my-api-key".to_string())
+///         .transport(transport.clone())
+///         .try_build()
+///         .unwrap();
+///
+///     let voting = client.get_voting("1").await.unwrap();
+///     assert_eq!(voting.choices, vec!["A", "B"]);
+///     assert_eq!(transport.requests()[0].url.path(), "/v1/votings/1");
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockTransport {
+    /// Creates a new `MockTransport` without enqueued responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enqueues a response returned to a future request.
+    pub fn push(&self, response: MockResponse) {
+        self.state.lock().unwrap().responses.push_back(response);
+    }
+
+    /// Returns the requests received so far.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Returns the number of enqueued responses that were not returned yet.
+    pub fn pending(&self) -> usize {
+        self.state.lock().unwrap().responses.len()
+    }
+}
+
+#[async_trait]
+impl Transport for MockTransport {
+    async fn execute(&self, request: Request) -> Result<Response, ClientError> {
+        let mut state = self.state.lock().unwrap();
+        state.requests.push(RecordedRequest {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            headers: request.headers().clone(),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(<[u8]>::to_vec),
+        });
+
+        state
+            .responses
+            .pop_front()
+            .map(MockResponse::into_response)
+            .ok_or_else(|| ClientError::Transport("no mock response enqueued".into()))
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use crate::{ApiError, BadRequestError, Rank};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Represents a ballot rejected by the client-side validation, before it is sent to the API.
+///
+/// Ballots are validated when ballot validation is enabled with
+/// `ClientBuilder::validate_ballots`, or when the choices of the voting are provided with
+/// `RequestOptions::known_choices`.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "diagnostics", derive(miette::Diagnostic))]
+#[non_exhaustive]
+pub enum ValidationError {
+    #[error("Ballot is empty")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::empty_ballot),
+            help("the ballot must rank at least one choice")
+        )
+    )]
+    EmptyBallot,
+    #[error("Unknown choice {choice}")]
+    #[cfg_attr(
+        feature = "diagnostics",
+        diagnostic(
+            code(ddclient::unknown_choice),
+            help("the ballot may rank only the choices of the voting")
+        )
+    )]
+    UnknownChoice { choice: String },
+}
+
+/// Validates a ballot without sending it to the API.
+///
+/// The ballot must not be empty. If `choices` are given, the ballot may rank only these
+/// choices.
+///
+/// Choices are validated in their alphabetical order, so the same ballot always returns the
+/// same error.
+///
+/// # Examples
+///
+/// ```
+/// use ddclient_rs::{validate_ballot, Rank, ValidationError};
+/// use std::collections::HashMap;
+///
+/// let choices = vec!["Einstein".to_string(), "Newton".to_string()];
+/// let ballot = HashMap::from([("Bohr".to_string(), Rank::HIGHEST)]);
+///
+/// assert_eq!(
+///     validate_ballot(&ballot, Some(&choices)),
+///     Err(ValidationError::UnknownChoice {
+///         choice: "Bohr".to_string()
+///     })
+/// );
+/// ```
+pub fn validate_ballot(
+    ballot: &HashMap<String, Rank>,
+    choices: Option<&[String]>,
+) -> Result<(), ValidationError> {
+    if ballot.is_empty() {
+        return Err(ValidationError::EmptyBallot);
+    }
+
+    let mut ranked: Vec<&String> = ballot.keys().collect();
+    ranked.sort();
+    for choice in ranked {
+        if choices.is_some_and(|choices| !choices.contains(choice)) {
+            return Err(ValidationError::UnknownChoice {
+                choice: choice.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Validates that a new voting has choices, and that none of them is empty.
+///
+/// The limits of the number and length of the choices are not checked, as the API reports
+/// them with `BadRequestError::TooManyChoices` and `BadRequestError::ChoiceTooLong`.
+pub(crate) fn validate_choices(choices: &[String]) -> Result<(), BadRequestError> {
+    if choices.is_empty() {
+        return Err(BadRequestError::MissingChoices);
+    }
+    choices
+        .iter()
+        .try_for_each(|choice| validate_choice(choice))
+}
+
+/// Validates that a choice is not empty.
+pub(crate) fn validate_choice(choice: &str) -> Result<(), BadRequestError> {
+    if choice.is_empty() {
+        return Err(BadRequestError::ChoiceRequired);
+    }
+    Ok(())
+}
+
+/// Creates the `ApiError` of a request rejected by the client-side validation of the
+/// choices, before it was sent.
+pub(crate) fn rejected(error: BadRequestError) -> ApiError {
+    ApiError::BadRequest(vec![error])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_ballot_test() {
+        let choices = vec!["Kant".to_string(), "Spinoza".to_string()];
+
+        let ballot = HashMap::from([
+            ("Kant".to_string(), Rank::HIGHEST),
+            ("Spinoza".to_string(), Rank::new(2).unwrap()),
+        ]);
+        assert_eq!(validate_ballot(&ballot, Some(&choices)), Ok(()));
+
+        assert_eq!(
+            validate_ballot(&HashMap::new(), None),
+            Err(ValidationError::EmptyBallot)
+        );
+
+        let ballot = HashMap::from([("Hume".to_string(), Rank::HIGHEST)]);
+        assert_eq!(validate_ballot(&ballot, None), Ok(()));
+        assert_eq!(
+            validate_ballot(&ballot, Some(&choices)),
+            Err(ValidationError::UnknownChoice {
+                choice: "Hume".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn validate_choices_test() {
+        let choices = vec!["Kant".to_string(), "Spinoza".to_string()];
+        assert_eq!(validate_choices(&choices), Ok(()));
+        assert_eq!(validate_choices(&[]), Err(BadRequestError::MissingChoices));
+        assert_eq!(
+            validate_choices(&["Kant".to_string(), String::new()]),
+            Err(BadRequestError::ChoiceRequired)
+        );
+        assert_eq!(validate_choice(&"a".repeat(10_000)), Ok(()));
+    }
+}
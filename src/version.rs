@@ -0,0 +1,43 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+use std::fmt;
+
+/// The version of the Direct Decisions API used by a client, set with
+/// `ClientBuilder::api_version`.
+///
+/// The version selects the path prefix of all endpoints, such as `v1/votings`. The response
+/// models of a version are in the module of the same name, like `ddclient_rs::v1`. New
+/// versions of the API will be added as variants, with their own models.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ApiVersion {
+    /// The version 1 API, at `https://api.directdecisions.com/v1`.
+    #[default]
+    V1,
+}
+
+impl ApiVersion {
+    /// Returns the path prefix of the version, such as `v1`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "v1",
+        }
+    }
+
+    /// Returns the version of a path prefix, or `None` if the prefix is not a known version.
+    pub(crate) fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            "v1" => Some(ApiVersion::V1),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
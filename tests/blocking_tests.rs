@@ -0,0 +1,269 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+#![cfg(feature = "blocking")]
+
+use ddclient_rs::blocking::Client;
+use ddclient_rs::{ApiError, BadRequestError, BulkOptions, ClientError, Rank, RetryPolicy};
+use httpmock::prelude::*;
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const CONTENT_TYPE: &str = "application/json; charset=utf-8";
+
+fn prepare_client_server() -> (MockServer, Client) {
+    let server = MockServer::start();
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .try_build()
+        .unwrap();
+    (server, client)
+}
+
+#[test]
+fn create_voting_test() {
+    let (server, client) = prepare_client_server();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/votings")
+            .header("Authorization", "Bearer test-token")
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({"choices":["Spinoza","Kant"]}));
+
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .header("X-RateLimit-Limit", "100")
+            .header("X-RateLimit-Remaining", "99")
+            .header("X-RateLimit-Reset", "60")
+            .header("Retry-After", "60")
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"]}));
+    });
+
+    let voting = client
+        .create_voting(vec!["Spinoza".to_string(), "Kant".to_string()])
+        .unwrap();
+
+    assert_eq!(voting.id, "40f80454800b2bd7c172");
+    assert_eq!(voting.choices, vec!["Spinoza", "Kant"]);
+    assert_eq!(client.get_rate().unwrap().remaining, 99);
+    mock.assert();
+}
+
+#[test]
+fn create_voting_builder_test() {
+    let (server, client) = prepare_client_server();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/votings")
+            .json_body(json!({"choices":["Spinoza","Kant"],"title":"Philosophers"}));
+
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"]}));
+    });
+
+    let voting = client
+        .create_voting_builder()
+        .choices(vec!["Spinoza".to_string(), "Kant".to_string()])
+        .title("Philosophers".to_string())
+        .send()
+        .unwrap();
+
+    assert_eq!(voting.id, "40f80454800b2bd7c172");
+    mock.assert();
+}
+
+#[test]
+fn vote_test() {
+    let (server, client) = prepare_client_server();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/votings/40f80454800b2bd7c172/ballots/einstein")
+            .json_body(json!({"ballot":{"Spinoza":1}}));
+
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({"revoted":true}));
+    });
+
+    let revoted = client
+        .vote(
+            "40f80454800b2bd7c172",
+            "einstein",
+            HashMap::from([("Spinoza".to_string(), Rank::HIGHEST)]),
+        )
+        .unwrap();
+
+    assert!(revoted);
+    mock.assert();
+}
+
+#[test]
+fn errors_test() {
+    let (server, client) = prepare_client_server();
+
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/missing");
+        then.status(404);
+    });
+    server.mock(|when, then| {
+        when.method(POST).path("/v1/votings");
+        then.status(400)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({
+                "code": 400,
+                "message": "Bad Request",
+                "errors": ["MissingChoices"]
+            }));
+    });
+
+    let got_err = client.get_voting("missing").unwrap_err();
+    assert!(matches!(got_err, ApiError::NotFound));
+    let context = client.last_error_context().unwrap();
+    assert_eq!(context.path.as_deref(), Some("v1/votings/missing"));
+    assert_eq!(context.voting_id.as_deref(), Some("missing"));
+    assert!(client.get_voting_opt("missing").unwrap().is_none());
+
+    let got_err = client.create_voting(Vec::<String>::new()).unwrap_err();
+    match got_err {
+        ApiError::BadRequest(errors) => {
+            assert_eq!(errors, vec![BadRequestError::MissingChoices])
+        }
+        err => panic!("Expected bad request error {:?}", err),
+    }
+}
+
+#[test]
+#[cfg(feature = "experimental")]
+fn ballots_iter_test() {
+    let (server, client) = prepare_client_server();
+
+    let last_page = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings/40f80454800b2bd7c172/ballots")
+            .query_param("cursor", "c2");
+
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({
+                "ballots": [{"voter_id":"bohr","ballot":{"Kant":1}}]
+            }));
+    });
+
+    let first_page = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings/40f80454800b2bd7c172/ballots");
+
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({
+                "ballots": [
+                    {"voter_id":"einstein","ballot":{"Spinoza":1,"Kant":2}},
+                    {"voter_id":"curie","ballot":{"Kant":1,"Spinoza":2}}
+                ],
+                "next_cursor": "c2"
+            }));
+    });
+
+    let ballots: Vec<(String, HashMap<String, Rank>)> = client
+        .ballots_iter("40f80454800b2bd7c172")
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let voters: Vec<&str> = ballots.iter().map(|(voter, _)| voter.as_str()).collect();
+    assert_eq!(voters, vec!["einstein", "curie", "bohr"]);
+
+    first_page.assert();
+    last_page.assert();
+}
+
+#[test]
+fn retry_test() {
+    let server = MockServer::start();
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .retry_policy(
+            RetryPolicy::new(3)
+                .base_delay(Duration::from_millis(1))
+                .max_delay(Duration::from_millis(5)),
+        )
+        .try_build()
+        .unwrap();
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(503);
+    });
+
+    let got_err = client.get_voting("40f80454800b2bd7c172").unwrap_err();
+    assert!(matches!(
+        got_err,
+        ApiError::Client(ClientError::ServiceUnavailable)
+    ));
+    mock.assert_hits(3);
+}
+
+#[test]
+fn vote_many_test() {
+    let (server, client) = prepare_client_server();
+
+    let voted = server.mock(|when, then| {
+        when.method(POST).path_matches(
+            httpmock::Regex::new("^/v1/votings/40f80454800b2bd7c172/ballots/voter-[0-9]+$")
+                .unwrap(),
+        );
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({"revoted":false}));
+    });
+    let missing = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/votings/40f80454800b2bd7c172/ballots/missing");
+        then.status(404);
+    });
+
+    let mut ballots: Vec<(String, HashMap<String, Rank>)> = (0..10)
+        .map(|i| {
+            (
+                format!("voter-{}", i),
+                HashMap::from([("Spinoza".to_string(), Rank::HIGHEST)]),
+            )
+        })
+        .collect();
+    let ballot = HashMap::from([("Spinoza".to_string(), Rank::HIGHEST)]);
+    ballots.insert(5, ("missing".to_string(), ballot));
+
+    let results = client.vote_many(
+        "40f80454800b2bd7c172",
+        ballots,
+        &BulkOptions::new().concurrency(3),
+    );
+
+    assert_eq!(results.len(), 11);
+    assert_eq!(results[5].0, "missing");
+    assert!(matches!(results[5].1, Err(ApiError::NotFound)));
+    assert_eq!(results[6].0, "voter-5");
+    assert!(results
+        .iter()
+        .filter(|(voter_id, _)| voter_id != "missing")
+        .all(|(_, result)| matches!(result, Ok(false))));
+    voted.assert_hits(10);
+    missing.assert_hits(1);
+}
+
+#[test]
+fn client_debug_test() {
+    let client = Client::builder("secret-token".to_string())
+        .try_build()
+        .unwrap();
+
+    let debug = format!("{:?}", client);
+    assert!(!debug.contains("secret-token"));
+    assert!(debug.contains("[REDACTED]"));
+}
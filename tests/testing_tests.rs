@@ -0,0 +1,310 @@
+// Copyright (c) 2023, Direct Decisions Rust client AUTHORS.
+// All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+#![cfg(feature = "test-util")]
+
+use ddclient_rs::testing::{Cassette, FakeServer, MockClient};
+#[cfg(feature = "experimental")]
+use ddclient_rs::ListOptions;
+use ddclient_rs::{
+    ApiError, BadRequestError, Client, ClientError, DirectDecisionsApi, Rank, ReportFormat,
+};
+use std::collections::HashMap;
+
+fn ballot(ranks: &[(&str, u32)]) -> HashMap<String, Rank> {
+    ranks
+        .iter()
+        .map(|(choice, rank)| (choice.to_string(), Rank::new(*rank).unwrap()))
+        .collect()
+}
+
+#[tokio::test]
+async fn mock_client_votings_test() {
+    let client = MockClient::new();
+
+    let got = client.create_voting(vec![]).await;
+    assert!(
+        matches!(got, Err(ApiError::BadRequest(errors)) if errors == vec![BadRequestError::MissingChoices])
+    );
+
+    let first = client
+        .create_voting(vec!["Spinoza".to_string(), "Kant".to_string()])
+        .await
+        .unwrap();
+    let second = client
+        .create_voting(vec!["Hume".to_string()])
+        .await
+        .unwrap();
+    assert_ne!(first.id, second.id);
+    assert_eq!(client.get_voting(&first.id).await.unwrap(), first);
+
+    #[cfg(feature = "experimental")]
+    {
+        let page = client
+            .list_votings(&ListOptions::new().limit(1))
+            .await
+            .unwrap();
+        assert_eq!(page.votings.len(), 1);
+        assert_eq!(page.votings[0].id, first.id);
+        assert_eq!(page.total, Some(2));
+        let page = client
+            .list_votings(&ListOptions::new().cursor(page.next_cursor.unwrap()))
+            .await
+            .unwrap();
+        assert_eq!(page.votings[0].id, second.id);
+        assert!(!page.has_more());
+    }
+
+    let choices = client.set_choice(&first.id, "Hegel", 0).await.unwrap();
+    assert_eq!(choices, vec!["Spinoza", "Kant", "Hegel"]);
+    let choices = client.set_choice(&first.id, "Hegel", 2).await.unwrap();
+    assert_eq!(choices, vec!["Hegel", "Spinoza", "Kant"]);
+    let choices = client.set_choice(&first.id, "Hegel", -1).await.unwrap();
+    assert_eq!(choices, vec!["Spinoza", "Kant"]);
+
+    client.delete_voting(&first.id).await.unwrap();
+    assert!(matches!(
+        client.get_voting(&first.id).await,
+        Err(ApiError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn mock_client_ballots_test() {
+    let client = MockClient::new();
+    let voting = client
+        .create_voting(vec!["Spinoza".to_string(), "Kant".to_string()])
+        .await
+        .unwrap();
+
+    let revoted = client
+        .vote(&voting.id, "einstein", ballot(&[("Kant", 1)]))
+        .await
+        .unwrap();
+    assert!(!revoted);
+    let revoted = client
+        .vote(
+            &voting.id,
+            "einstein",
+            ballot(&[("Spinoza", 1), ("Kant", 2)]),
+        )
+        .await
+        .unwrap();
+    assert!(revoted);
+    client
+        .vote(&voting.id, "curie", ballot(&[("Spinoza", 1)]))
+        .await
+        .unwrap();
+
+    let got = client
+        .vote(&voting.id, "bohr", ballot(&[("Hume", 1)]))
+        .await;
+    assert!(
+        matches!(got, Err(ApiError::BadRequest(errors)) if errors == vec![BadRequestError::InvalidData])
+    );
+
+    assert_eq!(
+        client.get_ballot(&voting.id, "einstein").await.unwrap(),
+        ballot(&[("Spinoza", 1), ("Kant", 2)])
+    );
+    #[cfg(feature = "experimental")]
+    {
+        let page = client
+            .list_ballots(&voting.id, &ListOptions::new())
+            .await
+            .unwrap();
+        assert_eq!(page.ballots.len(), 2);
+    }
+
+    let results = client.get_voting_results(&voting.id).await.unwrap();
+    assert!(!results.tie);
+    assert_eq!(results.results[0].choice, "Spinoza");
+    assert_eq!(results.results[0].wins, 1);
+    assert!(results.duels.is_none());
+
+    let summary = client
+        .summarize_voting(&voting.id, ReportFormat::PlainText)
+        .await
+        .unwrap();
+    assert!(summary.contains("Spinoza"));
+
+    client.unvote(&voting.id, "einstein").await.unwrap();
+    assert!(matches!(
+        client.get_ballot(&voting.id, "einstein").await,
+        Err(ApiError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn fake_server_test() {
+    let server = FakeServer::start().await;
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .try_build()
+        .unwrap();
+
+    let voting = client
+        .create_voting(vec!["Spinoza".to_string(), "Kant".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(server.api().get_voting(&voting.id).await.unwrap(), voting);
+    assert_eq!(client.get_rate().unwrap().limit, 100);
+    assert_eq!(client.get_rate().unwrap().remaining, 99);
+
+    let revoted = client
+        .vote(&voting.id, "einstein", ballot(&[("Kant", 1)]))
+        .await
+        .unwrap();
+    assert!(!revoted);
+    assert_eq!(
+        client.get_ballot(&voting.id, "einstein").await.unwrap(),
+        ballot(&[("Kant", 1)])
+    );
+
+    let results = client.get_voting_results_duels(&voting.id).await.unwrap();
+    assert_eq!(results.results[0].choice, "Kant");
+    assert_eq!(results.duels.unwrap().len(), 1);
+
+    let got = client.create_voting(Vec::<String>::new()).await;
+    assert!(
+        matches!(got, Err(ApiError::BadRequest(errors)) if errors == vec![BadRequestError::MissingChoices])
+    );
+
+    client.delete_voting(&voting.id).await.unwrap();
+    assert!(client.get_last_request_id().is_some());
+    let got = client.get_voting(&voting.id).await;
+    assert!(matches!(got, Err(ApiError::NotFound)));
+    let context = client.last_error_context().unwrap();
+    assert!(context.request_id.is_some());
+    assert_eq!(context.method, Some(reqwest::Method::GET));
+    assert_eq!(context.voting_id.as_deref(), Some(voting.id.as_str()));
+    assert_eq!(context.status, Some(reqwest::StatusCode::NOT_FOUND));
+
+    server.set_rate_limit(1);
+    client.get_voting("missing").await.unwrap_err();
+    assert!(matches!(
+        client.get_voting("missing").await,
+        Err(ApiError::RateLimited { .. })
+    ));
+}
+
+#[tokio::test]
+async fn cassette_test() {
+    let path = std::env::temp_dir().join(format!("ddclient-cassette-{}.json", std::process::id()));
+
+    let server = FakeServer::start().await;
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .transport(Cassette::record(&path, reqwest::Client::new()).unwrap())
+        .try_build()
+        .unwrap();
+    let voting = client
+        .create_voting(vec!["Spinoza".to_string(), "Kant".to_string()])
+        .await
+        .unwrap();
+    let got = client.get_voting("missing").await;
+    assert!(matches!(got, Err(ApiError::NotFound)));
+    drop(server);
+
+    let client = Client::builder("test-token".to_string())
+        .api_url("http://localhost:1".to_string())
+        .transport(Cassette::replay(&path).unwrap())
+        .try_build()
+        .unwrap();
+    let replayed = client
+        .create_voting(vec!["Spinoza".to_string(), "Kant".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(replayed, voting);
+    assert_eq!(client.get_rate().unwrap().remaining, 99);
+    let got = client.get_voting("missing").await;
+    assert!(matches!(got, Err(ApiError::NotFound)));
+
+    // every recording is replayed once
+    let got = client.get_voting("missing").await;
+    assert!(matches!(
+        got,
+        Err(ApiError::Client(ClientError::Transport(_)))
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn set_choices_test() {
+    let server = FakeServer::start().await;
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .try_build()
+        .unwrap();
+    let voting = client
+        .create_voting(vec![
+            "Spinoza".to_string(),
+            "Kant".to_string(),
+            "Hume".to_string(),
+        ])
+        .await
+        .unwrap();
+
+    let desired = vec![
+        "Hegel".to_string(),
+        "Hume".to_string(),
+        "Spinoza".to_string(),
+    ];
+    let choices = client.set_choices(&voting.id, desired).await.unwrap();
+    assert_eq!(choices, vec!["Hegel", "Hume", "Spinoza"]);
+    assert_eq!(
+        server.api().get_voting(&voting.id).await.unwrap().choices,
+        choices
+    );
+
+    let got = client
+        .set_choices(&voting.id, vec!["Kant".to_string(), "Kant".to_string()])
+        .await;
+    assert!(
+        matches!(got, Err(ApiError::BadRequest(errors)) if errors == vec![BadRequestError::InvalidData])
+    );
+}
+
+#[tokio::test]
+async fn rename_choice_test() {
+    let server = FakeServer::start().await;
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .try_build()
+        .unwrap();
+    let voting = client
+        .create_voting(vec![
+            "Spinoza".to_string(),
+            "Kant".to_string(),
+            "Hume".to_string(),
+        ])
+        .await
+        .unwrap();
+
+    let choices = client
+        .rename_choice(&voting.id, "Kant", "Hegel")
+        .await
+        .unwrap();
+    assert_eq!(choices, vec!["Spinoza", "Hegel", "Hume"]);
+
+    let choices = client
+        .rename_choice(&voting.id, "Hume", "Hume")
+        .await
+        .unwrap();
+    assert_eq!(choices, vec!["Spinoza", "Hegel", "Hume"]);
+
+    let got = client.rename_choice(&voting.id, "Kant", "Locke").await;
+    assert!(matches!(got, Err(ApiError::NotFound)));
+
+    let got = client.rename_choice(&voting.id, "Hegel", "Hume").await;
+    assert!(
+        matches!(got, Err(ApiError::BadRequest(errors)) if errors == vec![BadRequestError::InvalidData])
+    );
+    assert_eq!(
+        server.api().get_voting(&voting.id).await.unwrap().choices,
+        vec!["Spinoza", "Hegel", "Hume"]
+    );
+}
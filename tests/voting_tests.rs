@@ -3,7 +3,13 @@
 // Use of this source code is governed by a BSD-style
 // license that can be found in the LICENSE file.
 
-use ddclient_rs::{ApiError, BadRequestError, Client, VotingResult};
+use ddclient_rs::{
+    ApiError, BadRequestError, BuildError, BulkOptions, ChoiceIndex, Client, ClientError,
+    DirectDecisionsApi, MockResponse, MockTransport, Rank, ReportFormat, RequestOptions,
+    ValidationError, Voting, VotingResult,
+};
+#[cfg(feature = "experimental")]
+use ddclient_rs::{ListOptions, VotingMetadata, VotingStatus};
 use httpmock::prelude::*;
 use httpmock::Mock;
 use serde::{Deserialize, Serialize};
@@ -19,7 +25,7 @@ fn request_mock(
     status: u16,
     req_body: Option<Value>,
     resp_body: Value,
-) -> Mock {
+) -> Mock<'_> {
     if let Some(body) = req_body {
         server.mock(|when, then| {
             when.method(method)
@@ -51,7 +57,8 @@ fn prepare_client_server() -> (MockServer, Client) {
     let server = MockServer::start();
     let client = Client::builder("test-token".to_string())
         .api_url(server.base_url())
-        .build();
+        .try_build()
+        .unwrap();
     (server, client)
 }
 
@@ -79,7 +86,66 @@ async fn create_voting_test() {
 
     assert_eq!(got_voting.id, "40f80454800b2bd7c172");
     assert_eq!(got_voting.choices, vec!["Spinoza", "Kant", "Nietzsche"]);
+
+    let got_voting = client
+        .create_voting(["Spinoza", "Kant", "Nietzsche"])
+        .await
+        .unwrap();
+    assert_eq!(got_voting.choices, vec!["Spinoza", "Kant", "Nietzsche"]);
+
+    let choices = ["spinoza", "kant", "nietzsche"];
+    let got_voting = client
+        .create_voting(
+            choices
+                .iter()
+                .map(|choice| choice[..1].to_uppercase() + &choice[1..]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(got_voting.id, "40f80454800b2bd7c172");
+    mock.assert_hits(3);
+}
+
+#[tokio::test]
+async fn create_voting_builder_test() {
+    let (server, client) = prepare_client_server();
+
+    let mock = request_mock(
+        &server,
+        POST,
+        "/v1/votings".to_string(),
+        200,
+        Some(json!({
+            "choices":["Spinoza","Kant"],
+            "title":"Philosophers",
+            "description":"Who is the greatest?",
+            "duels":true
+        })),
+        json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"]}),
+    );
+
+    let got_voting = client
+        .create_voting_builder()
+        .choice("Spinoza".to_string())
+        .choice("Kant".to_string())
+        .title("Philosophers".to_string())
+        .description("Who is the greatest?".to_string())
+        .option("duels".to_string(), json!(true))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(got_voting.id, "40f80454800b2bd7c172");
     mock.assert();
+
+    let got = client
+        .create_voting_builder()
+        .title("Empty".to_string())
+        .send()
+        .await;
+    assert!(
+        matches!(got, Err(ApiError::BadRequest(errors)) if errors == vec![BadRequestError::MissingChoices])
+    );
 }
 
 #[tokio::test]
@@ -98,7 +164,106 @@ async fn get_voting_test() {
     let got_voting = client.get_voting("40f80454800b2bd7c172").await.unwrap();
     assert_eq!(got_voting.id, "40f80454800b2bd7c172");
     assert_eq!(got_voting.choices, vec!["Spinoza", "Kant", "Nietzsche"]);
+    assert_eq!(got_voting.index_of("Kant"), Some(ChoiceIndex::new(1)));
+    assert_eq!(got_voting.index_of("Hegel"), None);
+    assert_eq!(got_voting.choice(ChoiceIndex::new(2)), Some("Nietzsche"));
+    assert_eq!(got_voting.choice(ChoiceIndex::new(3)), None);
+    mock.assert();
+}
+
+#[tokio::test]
+#[cfg(feature = "experimental")]
+async fn voting_metadata_test() {
+    let (server, client) = prepare_client_server();
+
+    let mock = request_mock(
+        &server,
+        GET,
+        "/v1/votings/40f80454800b2bd7c172".to_string(),
+        200,
+        None,
+        json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"],"title":"Philosophers"}),
+    );
+    let voting = client.get_voting("40f80454800b2bd7c172").await.unwrap();
+    assert_eq!(voting.title.as_deref(), Some("Philosophers"));
+    assert_eq!(voting.description, None);
+    mock.assert();
+
+    let mock = request_mock(
+        &server,
+        PUT,
+        "/v1/votings/40f80454800b2bd7c172/metadata".to_string(),
+        200,
+        Some(json!({"title":"Philosophers","description":"Who is the greatest?"})),
+        json!({"title":"Philosophers","description":"Who is the greatest?"}),
+    );
+    let metadata = VotingMetadata {
+        title: Some("Philosophers".to_string()),
+        description: Some("Who is the greatest?".to_string()),
+    };
+    let got = client
+        .set_voting_metadata("40f80454800b2bd7c172", metadata.clone())
+        .await
+        .unwrap();
+    assert_eq!(got, metadata);
+    mock.assert();
+
+    let mock = request_mock(
+        &server,
+        GET,
+        "/v1/votings/40f80454800b2bd7c172/metadata".to_string(),
+        200,
+        None,
+        json!({"title":"Philosophers"}),
+    );
+    let got = client
+        .get_voting_metadata("40f80454800b2bd7c172")
+        .await
+        .unwrap();
+    assert_eq!(got.title.as_deref(), Some("Philosophers"));
+    assert_eq!(got.description, None);
+    mock.assert();
+}
+
+#[tokio::test]
+#[cfg(feature = "experimental")]
+async fn close_reopen_voting_test() {
+    let (server, client) = prepare_client_server();
+
+    let mock = request_mock(
+        &server,
+        POST,
+        "/v1/votings/40f80454800b2bd7c172/close".to_string(),
+        200,
+        None,
+        json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"],"status":"closed"}),
+    );
+    let voting = client.close_voting("40f80454800b2bd7c172").await.unwrap();
+    assert_eq!(voting.status, Some(VotingStatus::Closed));
+    assert!(voting.is_closed());
+    mock.assert();
+
+    let mock = request_mock(
+        &server,
+        POST,
+        "/v1/votings/40f80454800b2bd7c172/reopen".to_string(),
+        200,
+        None,
+        json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"],"status":"open"}),
+    );
+    let voting = client.reopen_voting("40f80454800b2bd7c172").await.unwrap();
+    assert_eq!(voting.status, Some(VotingStatus::Open));
     mock.assert();
+
+    let voting: Voting =
+        serde_json::from_value(json!({"id":"40f80454800b2bd7c172","choices":["Kant"]})).unwrap();
+    assert_eq!(voting.status, None);
+    assert!(!voting.is_closed());
+    let voting: Voting = serde_json::from_value(
+        json!({"id":"40f80454800b2bd7c172","choices":["Kant"],"status":"archived"}),
+    )
+    .unwrap();
+    assert_eq!(voting.status, Some(VotingStatus::Unknown));
 }
 
 #[tokio::test]
@@ -152,15 +317,15 @@ async fn vote_test() {
 
     #[derive(Debug, Serialize, Deserialize)]
     struct Ballot {
-        ballot: HashMap<String, i32>,
+        ballot: HashMap<String, Rank>,
     }
 
     let ballot = Ballot {
         ballot: HashMap::from([
-            ("Schopenhauer".to_string(), 1),
-            ("Spinoza".to_string(), 1),
-            ("Kant".to_string(), 1),
-            ("Nietzsche".to_string(), 1),
+            ("Schopenhauer".to_string(), Rank::HIGHEST),
+            ("Spinoza".to_string(), Rank::HIGHEST),
+            ("Kant".to_string(), Rank::HIGHEST),
+            ("Nietzsche".to_string(), Rank::HIGHEST),
         ]),
     };
 
@@ -177,8 +342,23 @@ async fn vote_test() {
         .vote("40f80454800b2bd7c172", "einstein", ballot.ballot)
         .await
         .unwrap();
-    assert_eq!(revoted, false);
-    mock.assert();
+    assert!(!revoted);
+
+    let revoted = client
+        .vote(
+            "40f80454800b2bd7c172",
+            "einstein",
+            [
+                ("Schopenhauer", Rank::HIGHEST),
+                ("Spinoza", Rank::HIGHEST),
+                ("Kant", Rank::HIGHEST),
+                ("Nietzsche", Rank::HIGHEST),
+            ],
+        )
+        .await
+        .unwrap();
+    assert!(!revoted);
+    mock.assert_hits(2);
 }
 
 #[tokio::test]
@@ -194,7 +374,7 @@ async fn unvote_test() {
         json!({"code":200,"message":"OK"}),
     );
 
-    let _ = client
+    client
         .unvote("40f80454800b2bd7c172", "einstein")
         .await
         .unwrap();
@@ -218,10 +398,126 @@ async fn get_ballot_test() {
         .get_ballot("40f80454800b2bd7c172", "einstein")
         .await
         .unwrap();
-    assert_eq!(got_ballot["Schopenhauer"], 1);
-    assert_eq!(got_ballot["Spinoza"], 1);
-    assert_eq!(got_ballot["Kant"], 1);
-    assert_eq!(got_ballot["Nietzsche"], 1);
+    assert_eq!(got_ballot["Schopenhauer"], Rank::HIGHEST);
+    assert_eq!(got_ballot["Spinoza"], Rank::HIGHEST);
+    assert_eq!(got_ballot["Kant"], Rank::HIGHEST);
+    assert_eq!(got_ballot["Nietzsche"], Rank::HIGHEST);
+    mock.assert();
+}
+
+#[tokio::test]
+async fn get_opt_test() {
+    let (server, client) = prepare_client_server();
+
+    let mock = request_mock(
+        &server,
+        GET,
+        "/v1/votings/40f80454800b2bd7c172".to_string(),
+        200,
+        None,
+        json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"]}),
+    );
+    let voting = client
+        .get_voting_opt("40f80454800b2bd7c172")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(voting.choices, vec!["Spinoza", "Kant"]);
+    mock.assert();
+
+    let mock = request_mock(
+        &server,
+        GET,
+        "/v1/votings/a5f33b0c4e3d2c1b0a99".to_string(),
+        404,
+        None,
+        json!({"code":404,"message":"Not Found"}),
+    );
+    assert!(client
+        .get_voting_opt("a5f33b0c4e3d2c1b0a99")
+        .await
+        .unwrap()
+        .is_none());
+    mock.assert();
+
+    let mock = request_mock(
+        &server,
+        GET,
+        "/v1/votings/40f80454800b2bd7c172/ballots/einstein".to_string(),
+        404,
+        None,
+        json!({"code":404,"message":"Not Found"}),
+    );
+    assert!(client
+        .get_ballot_opt("40f80454800b2bd7c172", "einstein")
+        .await
+        .unwrap()
+        .is_none());
+    mock.assert();
+
+    let mock = request_mock(
+        &server,
+        GET,
+        "/v1/votings/40f80454800b2bd7c172/ballots/bohr".to_string(),
+        500,
+        None,
+        json!({"code":500,"message":"Internal Server Error"}),
+    );
+    let got_err = client
+        .get_ballot_opt("40f80454800b2bd7c172", "bohr")
+        .await
+        .unwrap_err();
+    assert!(matches!(got_err, ApiError::InternalServerError(_)));
+    mock.assert();
+}
+
+#[tokio::test]
+async fn has_ballot_test() {
+    let (server, client) = prepare_client_server();
+
+    let mut mock = request_mock(
+        &server,
+        GET,
+        "/v1/votings/40f80454800b2bd7c172/ballots/einstein".to_string(),
+        200,
+        None,
+        json!({"ballot":{"Spinoza":1}}),
+    );
+    assert!(client
+        .has_ballot("40f80454800b2bd7c172", "einstein")
+        .await
+        .unwrap());
+    mock.assert();
+    mock.delete();
+
+    let mut mock = request_mock(
+        &server,
+        GET,
+        "/v1/votings/40f80454800b2bd7c172/ballots/einstein".to_string(),
+        404,
+        None,
+        json!({"code":404,"message":"Not Found"}),
+    );
+    assert!(!client
+        .has_ballot("40f80454800b2bd7c172", "einstein")
+        .await
+        .unwrap());
+    mock.assert();
+    mock.delete();
+
+    let mock = request_mock(
+        &server,
+        GET,
+        "/v1/votings/40f80454800b2bd7c172/ballots/einstein".to_string(),
+        403,
+        None,
+        json!({"code":403,"message":"Forbidden"}),
+    );
+    let got_err = client
+        .has_ballot("40f80454800b2bd7c172", "einstein")
+        .await
+        .unwrap_err();
+    assert!(matches!(got_err, ApiError::Forbidden));
     mock.assert();
 }
 
@@ -233,33 +529,33 @@ async fn get_voting_results_test() {
         results: vec![
             VotingResult {
                 choice: "Schopenhauer".to_string(),
-                index: 0,
+                index: ChoiceIndex::new(0),
                 wins: 1,
-                percentage: 50.0,
+                percentage: 50u8.into(),
                 strength: 1,
                 advantage: 0,
             },
             VotingResult {
                 choice: "Spinoza".to_string(),
-                index: 1,
+                index: ChoiceIndex::new(1),
                 wins: 1,
-                percentage: 50.0,
+                percentage: 50u8.into(),
                 strength: 1,
                 advantage: 0,
             },
             VotingResult {
                 choice: "Kant".to_string(),
-                index: 2,
+                index: ChoiceIndex::new(2),
                 wins: 1,
-                percentage: 50.0,
+                percentage: 50u8.into(),
                 strength: 1,
                 advantage: 0,
             },
             VotingResult {
                 choice: "Nietzsche".to_string(),
-                index: 3,
+                index: ChoiceIndex::new(3),
                 wins: 1,
-                percentage: 50.0,
+                percentage: 50u8.into(),
                 strength: 1,
                 advantage: 0,
             },
@@ -286,6 +582,37 @@ async fn get_voting_results_test() {
     mock.assert();
 }
 
+#[tokio::test]
+async fn get_voting_results_strength_test() {
+    let (server, client) = prepare_client_server();
+
+    let mock = request_mock(
+        &server,
+        GET,
+        "/v1/votings/40f80454800b2bd7c172/results".to_string(),
+        200,
+        None,
+        json!({
+            "tie": false,
+            "results": [
+                {"choice":"Kant","index":1,"wins":1,"percentage":100.0,"strength":3,"advantage":2},
+                {"choice":"Spinoza","index":0,"wins":0,"percentage":0.0,"strength":1,"advantage":0}
+            ]
+        }),
+    );
+
+    let got_results = client
+        .get_voting_results("40f80454800b2bd7c172")
+        .await
+        .unwrap();
+
+    assert_eq!(got_results.results[0].strength, 3);
+    assert_eq!(got_results.results[0].advantage, 2);
+    assert_eq!(got_results.results[1].strength, 1);
+    assert_eq!(got_results.results[1].advantage, 0);
+    mock.assert();
+}
+
 #[tokio::test]
 async fn get_voting_results_duels_test() {
     let (server, client) = prepare_client_server();
@@ -294,33 +621,33 @@ async fn get_voting_results_duels_test() {
         results: vec![
             VotingResult {
                 choice: "Schopenhauer".to_string(),
-                index: 0,
+                index: ChoiceIndex::new(0),
                 wins: 1,
-                percentage: 50.0,
+                percentage: 50u8.into(),
                 strength: 1,
                 advantage: 0,
             },
             VotingResult {
                 choice: "Spinoza".to_string(),
-                index: 1,
+                index: ChoiceIndex::new(1),
                 wins: 1,
-                percentage: 50.0,
+                percentage: 50u8.into(),
                 strength: 1,
                 advantage: 0,
             },
             VotingResult {
                 choice: "Kant".to_string(),
-                index: 2,
+                index: ChoiceIndex::new(2),
                 wins: 1,
-                percentage: 50.0,
+                percentage: 50u8.into(),
                 strength: 1,
                 advantage: 0,
             },
             VotingResult {
                 choice: "Nietzsche".to_string(),
-                index: 3,
+                index: ChoiceIndex::new(3),
                 wins: 1,
-                percentage: 50.0,
+                percentage: 50u8.into(),
                 strength: 1,
                 advantage: 0,
             },
@@ -329,24 +656,24 @@ async fn get_voting_results_duels_test() {
         duels: Some(vec![
             ddclient_rs::Duels {
                 left: ddclient_rs::ChoiceStrength {
-                    index: 0,
+                    index: ChoiceIndex::new(0),
                     choice: "Schopenhauer".to_string(),
                     strength: 1,
                 },
                 right: ddclient_rs::ChoiceStrength {
-                    index: 1,
+                    index: ChoiceIndex::new(1),
                     choice: "Spinoza".to_string(),
                     strength: 1,
                 },
             },
             ddclient_rs::Duels {
                 left: ddclient_rs::ChoiceStrength {
-                    index: 2,
+                    index: ChoiceIndex::new(2),
                     choice: "Kant".to_string(),
                     strength: 1,
                 },
                 right: ddclient_rs::ChoiceStrength {
-                    index: 3,
+                    index: ChoiceIndex::new(3),
                     choice: "Nietzsche".to_string(),
                     strength: 1,
                 },
@@ -357,14 +684,14 @@ async fn get_voting_results_duels_test() {
     let mock = request_mock(
         &server,
         GET,
-        "/v1/votings/40f80454800b2bd7c172/results".to_string(),
+        "/v1/votings/40f80454800b2bd7c172/results/duels".to_string(),
         200,
         None,
         json!(voting_results),
     );
 
     let got_results = client
-        .get_voting_results("40f80454800b2bd7c172")
+        .get_voting_results_duels("40f80454800b2bd7c172")
         .await
         .unwrap();
 
@@ -372,6 +699,65 @@ async fn get_voting_results_duels_test() {
     mock.assert();
 }
 
+#[tokio::test]
+async fn get_pairwise_matrix_test() {
+    let (server, client) = prepare_client_server();
+
+    let mock = request_mock(
+        &server,
+        GET,
+        "/v1/votings/40f80454800b2bd7c172/results/duels".to_string(),
+        200,
+        None,
+        json!({
+            "tie": false,
+            "results": [
+                {"choice":"Kant","index":1,"wins":1,"percentage":100,"strength":3,"advantage":2},
+                {"choice":"Spinoza","index":0,"wins":0,"percentage":0,"strength":1,"advantage":0}
+            ],
+            "duels": [
+                {
+                    "left": {"choice":"Kant","index":1,"strength":3},
+                    "right": {"choice":"Spinoza","index":0,"strength":1}
+                }
+            ]
+        }),
+    );
+
+    let matrix = client
+        .get_pairwise_matrix("40f80454800b2bd7c172")
+        .await
+        .unwrap();
+
+    assert_eq!(matrix.choices(), ["Spinoza", "Kant"]);
+    assert_eq!(matrix.get("Kant", "Spinoza"), Some(3));
+    assert_eq!(matrix.get("Spinoza", "Kant"), Some(1));
+    assert_eq!(matrix.margin("Spinoza", "Kant"), Some(-2));
+    mock.assert();
+}
+
+#[tokio::test]
+async fn get_voting_results_duels_empty_test() {
+    let (server, client) = prepare_client_server();
+
+    let mock = request_mock(
+        &server,
+        GET,
+        "/v1/votings/40f80454800b2bd7c172/results/duels".to_string(),
+        200,
+        None,
+        json!({"tie": false, "results": []}),
+    );
+
+    let got_results = client
+        .get_voting_results_duels("40f80454800b2bd7c172")
+        .await
+        .unwrap();
+
+    assert_eq!(got_results.duels, Some(vec![]));
+    mock.assert();
+}
+
 #[tokio::test]
 async fn error_test() {
     let (server, client) = prepare_client_server();
@@ -431,7 +817,7 @@ async fn rate_test() {
     mock.assert();
     mock.delete();
 
-    assert_eq!(true, client.get_rate().is_none());
+    assert!(client.get_rate().is_none());
 
     let mock = server.mock(|when, then| {
         when.method(GET)
@@ -464,3 +850,2011 @@ async fn rate_test() {
     assert_eq!(rate.retry, retry.as_secs());
     mock.assert();
 }
+
+#[tokio::test]
+async fn summarize_voting_test() {
+    let (server, client) = prepare_client_server();
+
+    let voting_mock = request_mock(
+        &server,
+        GET,
+        "/v1/votings/40f80454800b2bd7c172".to_string(),
+        200,
+        None,
+        json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"]}),
+    );
+
+    let results_mock = request_mock(
+        &server,
+        GET,
+        "/v1/votings/40f80454800b2bd7c172/results/duels".to_string(),
+        200,
+        None,
+        json!({
+            "tie": false,
+            "results": [
+                {"choice":"Kant","index":1,"wins":1,"percentage":100.0,"strength":2,"advantage":1},
+                {"choice":"Spinoza","index":0,"wins":0,"percentage":0.0,"strength":1,"advantage":0}
+            ],
+            "duels": [
+                {"left":{"index":0,"choice":"Spinoza","strength":1},"right":{"index":1,"choice":"Kant","strength":2}}
+            ]
+        }),
+    );
+
+    let summary = client
+        .summarize_voting("40f80454800b2bd7c172", ReportFormat::PlainText)
+        .await
+        .unwrap();
+
+    // the turnout is not reported by the API
+    assert!(!summary.contains("Turnout"));
+    assert!(summary.contains("Winner: Kant"));
+    assert!(summary.contains("  1. Kant (1 wins, 100.00%)"));
+    assert!(summary.contains("  2. Spinoza (0 wins, 0.00%)"));
+    assert!(summary.contains("  Spinoza vs Kant: 1 - 2"));
+    voting_mock.assert();
+    results_mock.assert();
+}
+
+#[tokio::test]
+#[cfg(feature = "experimental")]
+async fn list_votings_test() {
+    let (server, client) = prepare_client_server();
+
+    // the more specific mock is registered first, so it takes precedence
+    let last_page = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings")
+            .query_param("limit", "2")
+            .query_param("cursor", "next/page+1")
+            .header("Authorization", "Bearer test-token");
+
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({
+                "votings": [{"id":"c0e3d2fa51b64d8a0e12","choices":["Hegel"]}]
+            }));
+    });
+
+    let first_page = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings")
+            .query_param("limit", "2")
+            .header("Authorization", "Bearer test-token");
+
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({
+                "votings": [
+                    {"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"]},
+                    {"id":"a2f00ab8b31c42e5d5f1","choices":["Hume","Locke"]}
+                ],
+                "next_cursor": "next/page+1",
+                "total": 3
+            }));
+    });
+
+    let options = ListOptions::new().limit(2);
+    let page = client.list_votings(&options).await.unwrap();
+    assert_eq!(page.votings.len(), 2);
+    assert_eq!(page.votings[1].id, "a2f00ab8b31c42e5d5f1");
+    assert_eq!(page.total, Some(3));
+    assert!(page.has_more());
+
+    let options = options.cursor(page.next_cursor.unwrap());
+    let page = client.list_votings(&options).await.unwrap();
+    assert_eq!(page.votings.len(), 1);
+    assert_eq!(page.votings[0].choices, vec!["Hegel"]);
+    assert_eq!(page.total, None);
+    assert!(!page.has_more());
+
+    first_page.assert();
+    last_page.assert();
+}
+
+#[tokio::test]
+#[cfg(feature = "experimental")]
+async fn ballots_stream_test() {
+    use futures_util::TryStreamExt;
+
+    let (server, client) = prepare_client_server();
+
+    let last_page = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings/40f80454800b2bd7c172/ballots")
+            .query_param("cursor", "c2")
+            .header("Authorization", "Bearer test-token");
+
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({
+                "ballots": [{"voter_id":"bohr","ballot":{"Kant":1}}]
+            }));
+    });
+
+    let first_page = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings/40f80454800b2bd7c172/ballots")
+            .header("Authorization", "Bearer test-token");
+
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({
+                "ballots": [
+                    {"voter_id":"einstein","ballot":{"Spinoza":1,"Kant":2}},
+                    {"voter_id":"curie","ballot":{"Kant":1,"Spinoza":2}}
+                ],
+                "next_cursor": "c2"
+            }));
+    });
+
+    let ballots: Vec<(String, HashMap<String, Rank>)> = client
+        .ballots_stream("40f80454800b2bd7c172")
+        .try_collect()
+        .await
+        .unwrap();
+
+    let voters: Vec<&str> = ballots.iter().map(|(voter, _)| voter.as_str()).collect();
+    assert_eq!(voters, vec!["einstein", "curie", "bohr"]);
+    assert_eq!(ballots[0].1["Kant"].get(), 2);
+    assert_eq!(ballots[2].1["Kant"], Rank::HIGHEST);
+
+    first_page.assert();
+    last_page.assert();
+}
+
+#[tokio::test]
+async fn retry_test() {
+    use ddclient_rs::{ClientError, RetryPolicy};
+    use std::time::Duration;
+
+    let server = MockServer::start();
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .retry_policy(
+            RetryPolicy::new(3)
+                .base_delay(Duration::from_millis(1))
+                .max_delay(Duration::from_millis(5)),
+        )
+        .try_build()
+        .unwrap();
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(503);
+    });
+
+    let got_err = client.get_voting("40f80454800b2bd7c172").await.unwrap_err();
+    assert!(matches!(
+        got_err,
+        ApiError::Client(ClientError::ServiceUnavailable)
+    ));
+    mock.assert_hits(3);
+}
+
+#[tokio::test]
+async fn no_retry_test() {
+    let (server, client) = prepare_client_server();
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(502);
+    });
+
+    let _ = client.get_voting("40f80454800b2bd7c172").await.unwrap_err();
+    mock.assert_hits(1);
+
+    // non-transient errors are not retried even when a policy is set
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .retry_policy(ddclient_rs::RetryPolicy::new(3))
+        .try_build()
+        .unwrap();
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/a2f00ab8b31c42e5d5f1");
+        then.status(404);
+    });
+
+    let got_err = client.get_voting("a2f00ab8b31c42e5d5f1").await.unwrap_err();
+    assert!(matches!(got_err, ApiError::NotFound));
+    mock.assert_hits(1);
+}
+
+#[tokio::test]
+async fn timeout_retry_test() {
+    use ddclient_rs::RetryPolicy;
+    use std::time::Duration;
+
+    let server = MockServer::start();
+    let builder = || {
+        Client::builder("test-token".to_string())
+            .api_url(server.base_url())
+            .timeout(Duration::from_millis(50))
+            .retry_policy(RetryPolicy::new(3).base_delay(Duration::from_millis(1)))
+    };
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/votings");
+        then.status(200)
+            .delay(Duration::from_millis(300))
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+
+    // a timed out POST request may have created the voting, so it is not sent again
+    let client = builder().try_build().unwrap();
+    let err = client
+        .create_voting(vec!["Spinoza".to_string()])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ApiError::Client(ClientError::HttpRequestError(ref err)) if err.is_timeout()
+    ));
+    mock.assert_hits(1);
+
+    // with an idempotency key, the API does not apply the request twice
+    let client = builder().idempotency_keys(true).try_build().unwrap();
+    let _ = client
+        .create_voting(vec!["Spinoza".to_string()])
+        .await
+        .unwrap_err();
+    mock.assert_hits(4);
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(200)
+            .delay(Duration::from_millis(300))
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+
+    let client = builder().try_build().unwrap();
+    let _ = client.get_voting("40f80454800b2bd7c172").await.unwrap_err();
+    mock.assert_hits(3);
+}
+
+#[tokio::test]
+async fn bad_gateway_retry_test() {
+    use ddclient_rs::RetryPolicy;
+    use std::time::Duration;
+
+    let server = MockServer::start();
+    let builder = || {
+        Client::builder("test-token".to_string())
+            .api_url(server.base_url())
+            .retry_policy(RetryPolicy::new(3).base_delay(Duration::from_millis(1)))
+    };
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/votings/40f80454800b2bd7c172/ballots/einstein");
+        then.status(502);
+    });
+
+    // the gateway may have failed after the API applied the vote, so it is not sent again
+    let client = builder().try_build().unwrap();
+    let err = client
+        .vote(
+            "40f80454800b2bd7c172",
+            "einstein",
+            [("Spinoza", Rank::new(1).unwrap())],
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Client(ClientError::BadGateway)));
+    mock.assert_hits(1);
+
+    let client = builder().idempotency_keys(true).try_build().unwrap();
+    let _ = client
+        .vote(
+            "40f80454800b2bd7c172",
+            "einstein",
+            [("Spinoza", Rank::new(1).unwrap())],
+        )
+        .await
+        .unwrap_err();
+    mock.assert_hits(4);
+}
+
+#[tokio::test]
+async fn timeout_test() {
+    use ddclient_rs::ClientError;
+    use std::time::Duration;
+
+    let server = MockServer::start();
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .timeout(Duration::from_millis(50))
+        .connect_timeout(Duration::from_secs(1))
+        .try_build()
+        .unwrap();
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(200)
+            .delay(Duration::from_millis(500))
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+
+    let got_err = client.get_voting("40f80454800b2bd7c172").await.unwrap_err();
+    match got_err {
+        ApiError::Client(ClientError::HttpRequestError(err)) => assert!(err.is_timeout()),
+        err => panic!("Expected timeout error {:?}", err),
+    }
+    mock.assert();
+}
+
+#[tokio::test]
+async fn http_version_test() {
+    use ddclient_rs::HttpVersion;
+    use std::time::Duration;
+
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(200)
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+
+    for version in [
+        HttpVersion::Auto,
+        HttpVersion::Http1Only,
+        HttpVersion::Http2Only,
+    ] {
+        let client = Client::builder("test-token".to_string())
+            .api_url(server.base_url())
+            .http_version(version)
+            .http2_adaptive_window(true)
+            .http2_keep_alive_interval(Duration::from_secs(30))
+            .http2_keep_alive_timeout(Duration::from_secs(5))
+            .try_build()
+            .unwrap();
+
+        let got = client.get_voting("40f80454800b2bd7c172").await;
+        assert!(got.is_ok(), "{:?}: {:?}", version, got);
+    }
+    mock.assert_hits(3);
+}
+
+#[tokio::test]
+async fn max_concurrent_requests_test() {
+    use std::time::{Duration, Instant};
+
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(200)
+            .delay(Duration::from_millis(50))
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .max_concurrent_requests(2)
+        .try_build()
+        .unwrap();
+
+    let started = Instant::now();
+    let requests = (0..6).map(|_| {
+        let client = client.clone();
+        async move { client.get_voting("40f80454800b2bd7c172").await }
+    });
+    for got in futures_util::future::join_all(requests).await {
+        got.unwrap();
+    }
+
+    // six requests, two at a time
+    assert!(started.elapsed() >= Duration::from_millis(150));
+    mock.assert_hits(6);
+}
+
+#[cfg(feature = "rate-limit")]
+#[tokio::test]
+async fn max_requests_per_second_test() {
+    use std::time::{Duration, Instant};
+
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(200)
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .max_requests_per_second(10)
+        .try_build()
+        .unwrap();
+
+    let started = Instant::now();
+    for _ in 0..12 {
+        client.get_voting("40f80454800b2bd7c172").await.unwrap();
+    }
+
+    // a burst of ten requests, then two requests spaced by 100ms
+    assert!(started.elapsed() >= Duration::from_millis(200));
+    mock.assert_hits(12);
+}
+
+#[tokio::test]
+async fn hedge_test() {
+    use std::time::Duration;
+
+    let server = MockServer::start();
+    let get_mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(200)
+            .delay(Duration::from_millis(200))
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+    let create_mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/votings");
+        then.status(200)
+            .delay(Duration::from_millis(200))
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .hedge(Duration::from_millis(20))
+        .try_build()
+        .unwrap();
+
+    client.get_voting("40f80454800b2bd7c172").await.unwrap();
+    client.create_voting(["Spinoza"]).await.unwrap();
+
+    // the slow read is hedged, the mutating request is sent once
+    get_mock.assert_hits(2);
+    create_mock.assert_hits(1);
+}
+
+#[tokio::test]
+async fn deadline_test() {
+    use ddclient_rs::{RequestOptions, RetryPolicy};
+    use std::time::{Duration, Instant};
+
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(503);
+    });
+
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .retry_policy(RetryPolicy::new(10).base_delay(Duration::from_secs(1)))
+        .try_build()
+        .unwrap();
+
+    let options = RequestOptions::new().deadline(Instant::now() + Duration::from_millis(200));
+    let started = Instant::now();
+    let got = client
+        .get_voting_with_options("40f80454800b2bd7c172", &options)
+        .await;
+
+    // the deadline passes during the backoff delay after the first attempt
+    assert!(matches!(got, Err(ApiError::DeadlineExceeded)));
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(
+        client.last_error_context().unwrap().path.as_deref(),
+        Some("v1/votings/40f80454800b2bd7c172")
+    );
+    mock.assert_hits(1);
+}
+
+#[tokio::test]
+async fn cancellation_token_test() {
+    use ddclient_rs::{CancellationToken, RequestOptions};
+    use std::time::Duration;
+
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(200)
+            .delay(Duration::from_secs(5))
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .try_build()
+        .unwrap();
+
+    let token = CancellationToken::new();
+    let options = RequestOptions::new().cancellation_token(token.clone());
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        token.cancel();
+    });
+
+    let got = client
+        .get_voting_with_options("40f80454800b2bd7c172", &options)
+        .await;
+    assert!(matches!(got, Err(ApiError::Cancelled)));
+    mock.assert_hits(1);
+
+    // a cancelled token aborts requests before they are sent
+    let got = client
+        .get_voting_with_options("40f80454800b2bd7c172", &options)
+        .await;
+    assert!(matches!(got, Err(ApiError::Cancelled)));
+    mock.assert_hits(1);
+}
+
+#[tokio::test]
+async fn cache_test() {
+    use std::time::Duration;
+
+    let server = MockServer::start();
+    let voting_mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(200)
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"]}));
+    });
+    let ballot_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings/40f80454800b2bd7c172/ballots/einstein");
+        then.status(200).json_body(json!({"ballot":{"Kant":1}}));
+    });
+    let results_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings/40f80454800b2bd7c172/results");
+        then.status(200)
+            .json_body(json!({"tie":false,"results":[]}));
+    });
+    let vote_mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/votings/40f80454800b2bd7c172/ballots/einstein");
+        then.status(200).json_body(json!({"revoted": true}));
+    });
+
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .cache(Duration::from_secs(60), 100)
+        .try_build()
+        .unwrap();
+
+    for _ in 0..3 {
+        client.get_voting("40f80454800b2bd7c172").await.unwrap();
+        client
+            .get_ballot("40f80454800b2bd7c172", "einstein")
+            .await
+            .unwrap();
+        client
+            .get_voting_results("40f80454800b2bd7c172")
+            .await
+            .unwrap();
+    }
+    voting_mock.assert_hits(1);
+    ballot_mock.assert_hits(1);
+    results_mock.assert_hits(1);
+
+    // a mutating request removes the cached responses of the voting
+    client
+        .vote(
+            "40f80454800b2bd7c172",
+            "einstein",
+            [("Kant", Rank::HIGHEST)],
+        )
+        .await
+        .unwrap();
+    client.get_voting("40f80454800b2bd7c172").await.unwrap();
+    client
+        .get_voting_results("40f80454800b2bd7c172")
+        .await
+        .unwrap();
+    voting_mock.assert_hits(2);
+    results_mock.assert_hits(2);
+    vote_mock.assert_hits(1);
+
+    client.invalidate_cache("40f80454800b2bd7c172");
+    client.get_voting("40f80454800b2bd7c172").await.unwrap();
+    voting_mock.assert_hits(3);
+}
+
+#[tokio::test]
+async fn vote_queue_test() {
+    use ddclient_rs::{QueuedOperation, VoteQueue};
+
+    let path = std::env::temp_dir().join(format!("ddclient-queue-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut queue = VoteQueue::open(&path).unwrap();
+    assert!(queue.is_empty());
+    queue
+        .enqueue_vote(
+            "40f80454800b2bd7c172",
+            "einstein",
+            [("Kant", Rank::HIGHEST)],
+        )
+        .unwrap();
+    queue
+        .enqueue_vote("40f80454800b2bd7c172", "bohr", [("Hegel", Rank::HIGHEST)])
+        .unwrap();
+    queue
+        .enqueue_unvote("40f80454800b2bd7c172", "planck")
+        .unwrap();
+
+    // the queue survives reopening
+    let mut queue = VoteQueue::open(&path).unwrap();
+    assert_eq!(queue.len(), 3);
+    let key = queue.operations()[0].idempotency_key().to_string();
+
+    let server = MockServer::start();
+    let vote_mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/votings/40f80454800b2bd7c172/ballots/einstein")
+            .header("Idempotency-Key", key.as_str());
+        then.status(200).json_body(json!({"revoted": false}));
+    });
+    let rejected_mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/votings/40f80454800b2bd7c172/ballots/bohr");
+        then.status(400).json_body(json!({
+            "code": 400,
+            "message": "Bad Request",
+            "errors": ["Invalid data"]
+        }));
+    });
+    let unvote_mock = server.mock(|when, then| {
+        when.method(DELETE)
+            .path("/v1/votings/40f80454800b2bd7c172/ballots/planck");
+        then.status(503);
+    });
+
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .try_build()
+        .unwrap();
+
+    let report = queue.flush(&client).await.unwrap();
+    assert_eq!(report.sent, 1);
+    assert_eq!(report.rejected.len(), 1);
+    assert_eq!(report.rejected[0].0.voter_id(), "bohr");
+    assert!(report.stopped.as_ref().unwrap().is_retryable());
+    assert_eq!(report.remaining, 1);
+    assert!(!report.is_success());
+    vote_mock.assert_hits(1);
+    rejected_mock.assert_hits(1);
+    unvote_mock.assert_hits(1);
+
+    let queue = VoteQueue::open(&path).unwrap();
+    assert!(matches!(
+        queue.operations(),
+        [QueuedOperation::Unvote { voter_id, .. }] if voter_id == "planck"
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn offline_fallback_test() {
+    use ddclient_rs::MaybeStale;
+    use reqwest::StatusCode;
+
+    let transport = MockTransport::new();
+    let client = Client::builder("test-token".to_string())
+        .transport(transport.clone())
+        .offline_fallback(true)
+        .try_build()
+        .unwrap();
+
+    transport.push(
+        MockResponse::new(StatusCode::OK)
+            .json(&json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"]})),
+    );
+    transport.push(MockResponse::new(StatusCode::OK).json(&json!({"tie":false,"results":[]})));
+    let voting = client
+        .get_voting_or_stale("40f80454800b2bd7c172")
+        .await
+        .unwrap();
+    assert!(matches!(voting, MaybeStale::Fresh(_)));
+    let results = client
+        .get_voting_results_or_stale("40f80454800b2bd7c172")
+        .await
+        .unwrap();
+    assert!(!results.is_stale());
+
+    // every read is sent to the API, and the last responses are served when it fails
+    transport.push(MockResponse::new(StatusCode::SERVICE_UNAVAILABLE));
+    let voting = client
+        .get_voting_or_stale("40f80454800b2bd7c172")
+        .await
+        .unwrap();
+    assert!(voting.is_stale());
+    assert!(voting.age().is_some());
+    assert_eq!(voting.value().choices, vec!["Spinoza", "Kant"]);
+    match client
+        .get_voting_results_or_stale("40f80454800b2bd7c172")
+        .await
+        .unwrap()
+    {
+        MaybeStale::Stale { value, error, .. } => {
+            assert!(!value.tie);
+            assert!(matches!(error, ApiError::Client(ClientError::Transport(_))));
+        }
+        MaybeStale::Fresh(_) => panic!("expected stale results"),
+    }
+    assert_eq!(transport.requests().len(), 4);
+
+    // errors of the API are returned
+    transport.push(MockResponse::new(StatusCode::NOT_FOUND));
+    let got = client.get_voting_or_stale("40f80454800b2bd7c172").await;
+    assert!(matches!(got, Err(ApiError::NotFound)));
+
+    // votings which were never retrieved are not served
+    let got = client.get_voting_or_stale("6145a0d1bf2bd4a3b4ec").await;
+    assert!(matches!(
+        got,
+        Err(ApiError::Client(ClientError::Transport(_)))
+    ));
+}
+
+#[tokio::test]
+async fn request_options_test() {
+    use ddclient_rs::{ClientError, RequestOptions};
+    use reqwest::header::{HeaderName, HeaderValue};
+    use std::time::Duration;
+
+    let (server, client) = prepare_client_server();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/votings")
+            .header("Authorization", "Bearer test-token")
+            .header("X-Tenant-Id", "tenant-1")
+            .header("Idempotency-Key", "create-1")
+            .json_body(json!({"choices":["Spinoza","Kant"]}));
+
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"]}));
+    });
+
+    let options = RequestOptions::new()
+        .header(
+            HeaderName::from_static("x-tenant-id"),
+            HeaderValue::from_static("tenant-1"),
+        )
+        .idempotency_key("create-1".to_string());
+
+    let voting = client
+        .create_voting_with_options(vec!["Spinoza".to_string(), "Kant".to_string()], &options)
+        .await
+        .unwrap();
+    assert_eq!(voting.id, "40f80454800b2bd7c172");
+    mock.assert();
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(200)
+            .delay(Duration::from_millis(500))
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+
+    let options = RequestOptions::new().timeout(Duration::from_millis(50));
+    let got_err = client
+        .get_voting_with_options("40f80454800b2bd7c172", &options)
+        .await
+        .unwrap_err();
+    match got_err {
+        ApiError::Client(ClientError::HttpRequestError(err)) => assert!(err.is_timeout()),
+        err => panic!("Expected timeout error {:?}", err),
+    }
+    mock.assert();
+}
+
+#[test]
+fn try_build_test() {
+    let got = Client::builder(String::new()).try_build();
+    assert!(matches!(got, Err(BuildError::InvalidToken)));
+
+    let got = Client::builder("my-api-key\n".to_string()).try_build();
+    assert!(matches!(got, Err(BuildError::InvalidToken)));
+
+    let got = Client::builder("my-api-key".to_string())
+        .api_url("localhost".to_string())
+        .try_build();
+    assert!(matches!(got, Err(BuildError::InvalidUrl(_))));
+}
+
+#[tokio::test]
+async fn token_provider_test() {
+    use async_trait::async_trait;
+    use ddclient_rs::{ClientError, TokenProvider};
+    use std::error::Error;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct RotatingTokenProvider {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl TokenProvider for RotatingTokenProvider {
+        async fn token(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+            match self.calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Ok("token-1".to_string()),
+                1 => Ok("token-2".to_string()),
+                2 => Ok("token\n3".to_string()),
+                _ => Err("secrets manager unavailable".into()),
+            }
+        }
+    }
+
+    let server = MockServer::start();
+    let provider = Arc::new(RotatingTokenProvider {
+        calls: AtomicUsize::new(0),
+    });
+    let client = Client::builder(String::new())
+        .api_url(server.base_url())
+        .token_provider(provider.clone())
+        .try_build()
+        .unwrap();
+
+    let mocks: Vec<Mock> = ["token-1", "token-2"]
+        .into_iter()
+        .map(|token| {
+            server.mock(|when, then| {
+                when.method(GET)
+                    .path("/v1/votings/40f80454800b2bd7c172")
+                    .header("Authorization", format!("Bearer {}", token));
+                then.status(200)
+                    .header("Content-Type", CONTENT_TYPE)
+                    .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+            })
+        })
+        .collect();
+
+    client.get_voting("40f80454800b2bd7c172").await.unwrap();
+    client.get_voting("40f80454800b2bd7c172").await.unwrap();
+    for mock in &mocks {
+        mock.assert();
+    }
+
+    let got_err = client.get_voting("40f80454800b2bd7c172").await.unwrap_err();
+    match got_err {
+        ApiError::Client(ClientError::TokenProvider(err)) => {
+            assert_eq!(err.to_string(), "the token is not a valid header value")
+        }
+        err => panic!("Expected token provider error {:?}", err),
+    }
+
+    let got_err = client.get_voting("40f80454800b2bd7c172").await.unwrap_err();
+    match got_err {
+        ApiError::Client(ClientError::TokenProvider(err)) => {
+            assert_eq!(err.to_string(), "secrets manager unavailable")
+        }
+        err => panic!("Expected token provider error {:?}", err),
+    }
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn middleware_test() {
+    use ddclient_rs::Middleware;
+    use reqwest::header::HeaderValue;
+    use reqwest::{Request, Response};
+    use std::sync::{Arc, Mutex};
+
+    struct AuditLog(Arc<Mutex<Vec<String>>>);
+
+    impl Middleware for AuditLog {
+        fn on_response(&self, response: &Response) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{} {}", response.url().path(), response.status()));
+        }
+    }
+
+    let server = MockServer::start();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .on_request(|request: &mut Request| {
+            request
+                .headers_mut()
+                .insert("X-Tenant-Id", HeaderValue::from_static("tenant-1"));
+        })
+        .middleware(AuditLog(log.clone()))
+        .try_build()
+        .unwrap();
+
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings/40f80454800b2bd7c172")
+            .header("Authorization", "Bearer test-token")
+            .header("X-Tenant-Id", "tenant-1");
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+
+    client.get_voting("40f80454800b2bd7c172").await.unwrap();
+    mock.assert();
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["/v1/votings/40f80454800b2bd7c172 200 OK"]
+    );
+}
+
+#[tokio::test]
+async fn metrics_observer_test() {
+    use ddclient_rs::{MetricsObserver, RetryPolicy};
+    use reqwest::{Method, StatusCode};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl MetricsObserver for Recorder {
+        fn request_started(&self, method: &Method, path: &str) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("started {} {}", method, path));
+        }
+
+        fn request_completed(
+            &self,
+            method: &Method,
+            path: &str,
+            status: Option<StatusCode>,
+            _latency: Duration,
+        ) {
+            self.0.lock().unwrap().push(format!(
+                "completed {} {} {}",
+                method,
+                path,
+                status.unwrap().as_u16()
+            ));
+        }
+
+        fn request_retried(&self, _method: &Method, _path: &str, attempt: u32) {
+            self.0.lock().unwrap().push(format!("retried {}", attempt));
+        }
+
+        fn rate_limited(&self, _method: &Method, path: &str) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("rate limited {}", path));
+        }
+    }
+
+    let server = MockServer::start();
+    let recorder = Arc::new(Recorder::default());
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .retry_policy(RetryPolicy::new(3).base_delay(Duration::from_millis(1)))
+        .metrics_observer(recorder.clone())
+        .try_build()
+        .unwrap();
+
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/unavailable");
+        then.status(503);
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/limited");
+        then.status(429);
+    });
+
+    let _ = client.get_voting("unavailable").await.unwrap_err();
+    let _ = client.get_voting("limited").await.unwrap_err();
+
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        vec![
+            "started GET v1/votings/unavailable",
+            "retried 1",
+            "retried 2",
+            "completed GET v1/votings/unavailable 503",
+            "started GET v1/votings/limited",
+            "rate limited v1/votings/limited",
+            "completed GET v1/votings/limited 429",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn default_header_test() {
+    use ddclient_rs::RequestOptions;
+    use reqwest::header::{HeaderName, HeaderValue};
+
+    let server = MockServer::start();
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .default_header(
+            HeaderName::from_static("x-tenant-id"),
+            HeaderValue::from_static("tenant-1"),
+        )
+        .try_build()
+        .unwrap();
+
+    let overridden = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings/40f80454800b2bd7c172")
+            .header("X-Tenant-Id", "tenant-2");
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+    let default = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings/40f80454800b2bd7c172")
+            .header("Authorization", "Bearer test-token")
+            .header("X-Tenant-Id", "tenant-1");
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+
+    client.get_voting("40f80454800b2bd7c172").await.unwrap();
+    default.assert();
+
+    let options = RequestOptions::new().header(
+        HeaderName::from_static("x-tenant-id"),
+        HeaderValue::from_static("tenant-2"),
+    );
+    client
+        .get_voting_with_options("40f80454800b2bd7c172", &options)
+        .await
+        .unwrap();
+    overridden.assert();
+    default.assert_hits(1);
+}
+
+#[tokio::test]
+async fn proxy_test() {
+    let proxy = MockServer::start();
+    let client = Client::builder("test-token".to_string())
+        .api_url("http://api.directdecisions.invalid".to_string())
+        .proxy(proxy.base_url())
+        .proxy_auth("user".to_string(), "secret".to_string())
+        .try_build()
+        .unwrap();
+
+    let mock = proxy.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings/40f80454800b2bd7c172")
+            .header("Authorization", "Bearer test-token")
+            .header("Proxy-Authorization", "Basic dXNlcjpzZWNyZXQ=");
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+
+    client.get_voting("40f80454800b2bd7c172").await.unwrap();
+    mock.assert();
+
+    let got = Client::builder("test-token".to_string())
+        .proxy("not a url".to_string())
+        .try_build();
+    assert!(matches!(got, Err(BuildError::InvalidProxy(_))));
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+#[test]
+fn root_certificate_test() {
+    let certificate = reqwest::Certificate::from_pem(include_bytes!("data/ca.pem")).unwrap();
+
+    let got = Client::builder("test-token".to_string())
+        .add_root_certificate(certificate)
+        .try_build();
+    assert!(got.is_ok());
+}
+
+#[tokio::test]
+async fn clone_shares_rate_test() {
+    let (server, client) = prepare_client_server();
+    let cloned = client.clone();
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(200)
+            .header("Content-Type", CONTENT_TYPE)
+            .header("X-RateLimit-Limit", "100")
+            .header("X-RateLimit-Remaining", "42")
+            .header("X-RateLimit-Reset", "60")
+            .header("Retry-After", "60")
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza"]}));
+    });
+
+    tokio::spawn(async move { cloned.get_voting("40f80454800b2bd7c172").await })
+        .await
+        .unwrap()
+        .unwrap();
+    mock.assert();
+
+    assert_eq!(client.get_rate().unwrap().remaining, 42);
+}
+
+#[tokio::test]
+async fn mock_transport_test() {
+    let transport = MockTransport::new();
+    transport.push(
+        MockResponse::new(reqwest::StatusCode::OK)
+            .json(&json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"]})),
+    );
+    transport.push(MockResponse::new(reqwest::StatusCode::NOT_FOUND));
+
+    let client = Client::builder("test-token".to_string())
+        .transport(transport.clone())
+        .try_build()
+        .unwrap();
+
+    let voting = client
+        .create_voting(vec!["Spinoza".to_string(), "Kant".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(voting.id, "40f80454800b2bd7c172");
+
+    let got = client.get_voting("missing").await;
+    assert!(matches!(got, Err(ApiError::NotFound)));
+
+    let got = client.get_voting("missing").await;
+    assert!(matches!(
+        got,
+        Err(ApiError::Client(ClientError::Transport(_)))
+    ));
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].method, reqwest::Method::POST);
+    assert_eq!(requests[0].url.path(), "/v1/votings");
+    assert_eq!(
+        requests[0].headers.get("Authorization").unwrap(),
+        "Bearer test-token"
+    );
+    assert_eq!(
+        requests[0].json(),
+        Some(json!({"choices":["Spinoza","Kant"]}))
+    );
+    assert_eq!(requests[1].url.path(), "/v1/votings/missing");
+    assert_eq!(transport.pending(), 0);
+}
+
+#[tokio::test]
+async fn api_trait_test() {
+    async fn first_choice(api: &dyn DirectDecisionsApi, id: &str) -> Result<String, ApiError> {
+        let voting = api.get_voting(id).await?;
+        Ok(voting.choices[0].clone())
+    }
+
+    let transport = MockTransport::new();
+    transport.push(
+        MockResponse::new(reqwest::StatusCode::OK)
+            .json(&json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"]})),
+    );
+    let client = Client::builder("test-token".to_string())
+        .transport(transport.clone())
+        .try_build()
+        .unwrap();
+
+    let got = first_choice(&client, "40f80454800b2bd7c172").await.unwrap();
+    assert_eq!(got, "Spinoza");
+    assert_eq!(
+        transport.requests()[0].url.path(),
+        "/v1/votings/40f80454800b2bd7c172"
+    );
+}
+
+#[tokio::test]
+async fn idempotency_keys_test() {
+    use ddclient_rs::{RequestOptions, RetryPolicy};
+    use std::time::Duration;
+
+    let transport = MockTransport::new();
+    transport.push(MockResponse::new(reqwest::StatusCode::SERVICE_UNAVAILABLE));
+    transport.push(
+        MockResponse::new(reqwest::StatusCode::OK)
+            .json(&json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"]})),
+    );
+    transport.push(
+        MockResponse::new(reqwest::StatusCode::OK)
+            .json(&json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"]})),
+    );
+    transport.push(MockResponse::new(reqwest::StatusCode::OK).json(&json!({"revoted":false})));
+
+    let client = Client::builder("test-token".to_string())
+        .transport(transport.clone())
+        .retry_policy(RetryPolicy::new(2).base_delay(Duration::from_millis(1)))
+        .idempotency_keys(true)
+        .try_build()
+        .unwrap();
+
+    client
+        .create_voting(vec!["Spinoza".to_string(), "Kant".to_string()])
+        .await
+        .unwrap();
+    client.get_voting("40f80454800b2bd7c172").await.unwrap();
+    client
+        .vote_with_options(
+            "40f80454800b2bd7c172",
+            "einstein",
+            HashMap::from([("Spinoza".to_string(), Rank::HIGHEST)]),
+            &RequestOptions::new().idempotency_key("vote-1".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let keys: Vec<Option<String>> = transport
+        .requests()
+        .iter()
+        .map(|r| {
+            r.headers
+                .get("Idempotency-Key")
+                .map(|v| v.to_str().unwrap().to_string())
+        })
+        .collect();
+    // the retried request reuses the generated key
+    assert!(keys[0].is_some());
+    assert_eq!(keys[0], keys[1]);
+    assert_eq!(keys[2], None);
+    assert_eq!(keys[3].as_deref(), Some("vote-1"));
+}
+
+#[tokio::test]
+async fn error_context_test() {
+    let (server, client) = prepare_client_server();
+
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings/40f80454800b2bd7c172/ballots/einstein");
+        then.status(404)
+            .header("Content-Type", CONTENT_TYPE)
+            .header("X-Request-Id", "req-42")
+            .json_body(json!({"code":404,"message":"Not Found"}));
+    });
+
+    let got_err = client
+        .get_ballot("40f80454800b2bd7c172", "einstein")
+        .await
+        .unwrap_err();
+
+    assert!(matches!(got_err, ApiError::NotFound));
+    assert_eq!(got_err.to_string(), "Not Found");
+
+    let context = client.last_error_context().unwrap();
+    assert_eq!(context.method, Some(reqwest::Method::GET));
+    assert_eq!(
+        context.path.as_deref(),
+        Some("v1/votings/40f80454800b2bd7c172/ballots/einstein")
+    );
+    assert_eq!(context.status, Some(reqwest::StatusCode::NOT_FOUND));
+    assert_eq!(context.voting_id.as_deref(), Some("40f80454800b2bd7c172"));
+    assert_eq!(context.voter_id.as_deref(), Some("einstein"));
+    assert_eq!(context.request_id.as_deref(), Some("req-42"));
+}
+
+#[tokio::test]
+async fn rate_limited_test() {
+    let (server, client) = prepare_client_server();
+
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(429)
+            .header("X-RateLimit-Limit", "100")
+            .header("X-RateLimit-Remaining", "0")
+            .header("X-RateLimit-Reset", "30")
+            .header("Retry-After", "30");
+    });
+
+    let err = client.get_voting("40f80454800b2bd7c172").await.unwrap_err();
+    match err {
+        ApiError::RateLimited {
+            rate, retry_after, ..
+        } => {
+            let rate = rate.unwrap();
+            assert_eq!(rate.limit, 100);
+            assert_eq!(rate.remaining, 0);
+            assert_eq!(retry_after, Some(std::time::Duration::from_secs(30)));
+        }
+        err => panic!("unexpected error: {}", err),
+    }
+    mock.assert();
+}
+
+#[tokio::test]
+async fn validate_ballots_test() {
+    let transport = MockTransport::new();
+    let client = Client::builder("test-token".to_string())
+        .transport(transport.clone())
+        .validate_ballots(true)
+        .try_build()
+        .unwrap();
+
+    let got = client
+        .vote(
+            "40f80454800b2bd7c172",
+            "einstein",
+            HashMap::<String, Rank>::new(),
+        )
+        .await;
+    assert!(matches!(
+        got,
+        Err(ApiError::Validation(ValidationError::EmptyBallot))
+    ));
+
+    let options = RequestOptions::new().known_choices(vec!["Spinoza".to_string()]);
+    let ballot = HashMap::from([("Kant".to_string(), Rank::HIGHEST)]);
+    let got = client
+        .vote_with_options("40f80454800b2bd7c172", "einstein", ballot, &options)
+        .await;
+    assert!(matches!(
+        got,
+        Err(ApiError::Validation(ValidationError::UnknownChoice { .. }))
+    ));
+
+    assert!(transport.requests().is_empty());
+}
+
+#[tokio::test]
+async fn choice_limits_test() {
+    let transport = MockTransport::new();
+    let client = Client::builder("test-token".to_string())
+        .transport(transport.clone())
+        .try_build()
+        .unwrap();
+
+    let err = client
+        .create_voting(Vec::<String>::new())
+        .await
+        .unwrap_err();
+    assert_eq!(err.bad_request_errors(), [BadRequestError::MissingChoices]);
+
+    let err = client
+        .set_choice("40f80454800b2bd7c172", "", 0)
+        .await
+        .unwrap_err();
+    assert_eq!(err.bad_request_errors(), [BadRequestError::ChoiceRequired]);
+
+    assert!(transport.requests().is_empty());
+    assert!(client.last_error_context().is_none());
+}
+
+#[tokio::test]
+async fn vote_ranked_test() {
+    let transport = MockTransport::new();
+    transport.push(MockResponse::new(reqwest::StatusCode::OK).json(&json!({"revoted":false})));
+    let client = Client::builder("test-token".to_string())
+        .transport(transport.clone())
+        .try_build()
+        .unwrap();
+
+    let ranks = vec![
+        vec!["Spinoza".to_string()],
+        vec![],
+        vec!["Kant".to_string(), "Hume".to_string()],
+        vec!["Spinoza".to_string()],
+    ];
+    let revoted = client
+        .vote_ranked("40f80454800b2bd7c172", "einstein", ranks)
+        .await
+        .unwrap();
+    assert!(!revoted);
+
+    let requests = transport.requests();
+    assert_eq!(
+        requests[0].url.path(),
+        "/v1/votings/40f80454800b2bd7c172/ballots/einstein"
+    );
+    assert_eq!(
+        requests[0].json(),
+        Some(json!({"ballot":{"Spinoza":1,"Kant":2,"Hume":2}}))
+    );
+}
+
+#[tokio::test]
+async fn vote_ordered_test() {
+    let transport = MockTransport::new();
+    transport.push(MockResponse::new(reqwest::StatusCode::OK).json(&json!({"revoted":true})));
+    let client = Client::builder("test-token".to_string())
+        .transport(transport.clone())
+        .try_build()
+        .unwrap();
+
+    let choices = vec![
+        "Kant".to_string(),
+        "Spinoza".to_string(),
+        "Hume".to_string(),
+        "Kant".to_string(),
+    ];
+    let revoted = client
+        .vote_ordered("40f80454800b2bd7c172", "einstein", choices)
+        .await
+        .unwrap();
+    assert!(revoted);
+
+    assert_eq!(
+        transport.requests()[0].json(),
+        Some(json!({"ballot":{"Kant":1,"Spinoza":2,"Hume":3}}))
+    );
+}
+
+#[tokio::test]
+async fn choice_helpers_test() {
+    let transport = MockTransport::new();
+    let client = Client::builder("test-token".to_string())
+        .transport(transport.clone())
+        .try_build()
+        .unwrap();
+    let voting = json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant","Hume"]});
+    let choices = json!({"choices":["Spinoza","Kant","Hume"]});
+    transport.push(MockResponse::new(reqwest::StatusCode::OK).json(&choices));
+    transport.push(MockResponse::new(reqwest::StatusCode::OK).json(&choices));
+    transport.push(MockResponse::new(reqwest::StatusCode::OK).json(&voting));
+    transport.push(MockResponse::new(reqwest::StatusCode::OK).json(&choices));
+    transport.push(MockResponse::new(reqwest::StatusCode::OK).json(&voting));
+    transport.push(MockResponse::new(reqwest::StatusCode::OK).json(&choices));
+    transport.push(MockResponse::new(reqwest::StatusCode::OK).json(&voting));
+
+    client
+        .append_choice("40f80454800b2bd7c172", "Hegel")
+        .await
+        .unwrap();
+    client
+        .remove_choice("40f80454800b2bd7c172", "Hume")
+        .await
+        .unwrap();
+    client
+        .prepend_choice("40f80454800b2bd7c172", "Hegel")
+        .await
+        .unwrap();
+    client
+        .move_choice("40f80454800b2bd7c172", "Hume", ChoiceIndex::new(1))
+        .await
+        .unwrap();
+    let err = client
+        .insert_choice_at("40f80454800b2bd7c172", "Hegel", ChoiceIndex::new(4))
+        .await
+        .unwrap_err();
+    assert_eq!(err.bad_request_errors(), [BadRequestError::InvalidData]);
+
+    let indexes: Vec<Value> = transport
+        .requests()
+        .iter()
+        .filter_map(|request| request.json())
+        .map(|body| json!([body["choice"], body["index"]]))
+        .collect();
+    assert_eq!(
+        indexes,
+        vec![
+            json!(["Hegel", 0]),
+            json!(["Hume", -1]),
+            json!(["Hegel", 3]),
+            json!(["Hume", 1]),
+        ]
+    );
+    assert_eq!(transport.pending(), 0);
+}
+
+#[tokio::test]
+async fn vote_many_test() {
+    use reqwest::header::{HeaderName, HeaderValue};
+    use reqwest::StatusCode;
+
+    let transport = MockTransport::new();
+    transport.push(MockResponse::new(StatusCode::OK).json(&json!({"revoted":false})));
+    transport.push(MockResponse::new(StatusCode::TOO_MANY_REQUESTS).header(
+        HeaderName::from_static("retry-after"),
+        HeaderValue::from_static("0"),
+    ));
+    transport.push(MockResponse::new(StatusCode::OK).json(&json!({"revoted":true})));
+    transport.push(MockResponse::new(StatusCode::NOT_FOUND));
+    let client = Client::builder("test-token".to_string())
+        .transport(transport.clone())
+        .try_build()
+        .unwrap();
+
+    let ballots = ["einstein", "bohr", "planck"].into_iter().map(|voter_id| {
+        (
+            voter_id.to_string(),
+            HashMap::from([("Spinoza".to_string(), Rank::HIGHEST)]),
+        )
+    });
+    let results = client
+        .vote_many(
+            "40f80454800b2bd7c172",
+            ballots,
+            &BulkOptions::new().concurrency(1),
+        )
+        .await;
+
+    let voters: Vec<&str> = results
+        .iter()
+        .map(|(voter_id, _)| voter_id.as_str())
+        .collect();
+    assert_eq!(voters, vec!["einstein", "bohr", "planck"]);
+    assert!(matches!(results[0].1, Ok(false)));
+    assert!(matches!(results[1].1, Ok(true)));
+    assert!(matches!(results[2].1, Err(ApiError::NotFound)));
+
+    let urls: Vec<String> = transport
+        .requests()
+        .iter()
+        .map(|request| request.url.path().to_string())
+        .collect();
+    assert_eq!(
+        urls,
+        vec![
+            "/v1/votings/40f80454800b2bd7c172/ballots/einstein",
+            "/v1/votings/40f80454800b2bd7c172/ballots/bohr",
+            "/v1/votings/40f80454800b2bd7c172/ballots/bohr",
+            "/v1/votings/40f80454800b2bd7c172/ballots/planck",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn delete_votings_test() {
+    use reqwest::StatusCode;
+
+    let transport = MockTransport::new();
+    transport.push(MockResponse::new(StatusCode::OK).json(&json!({"code":200,"message":"OK"})));
+    transport.push(MockResponse::new(StatusCode::NOT_FOUND));
+    transport.push(MockResponse::new(StatusCode::INTERNAL_SERVER_ERROR));
+    let client = Client::builder("test-token".to_string())
+        .transport(transport.clone())
+        .try_build()
+        .unwrap();
+
+    let ids = [
+        "40f80454800b2bd7c172",
+        "a5f33b0c4e3d2c1b0a99",
+        "0b7c2c9d4e5f6a7b8c9d",
+    ]
+    .map(String::from);
+    let report = client
+        .delete_votings(ids, &BulkOptions::new().concurrency(1))
+        .await;
+
+    assert!(!report.is_success());
+    assert_eq!(report.deleted, vec!["40f80454800b2bd7c172"]);
+    assert_eq!(report.not_found, vec!["a5f33b0c4e3d2c1b0a99"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, "0b7c2c9d4e5f6a7b8c9d");
+    assert!(matches!(
+        report.failed[0].1,
+        ApiError::InternalServerError(_)
+    ));
+    assert!(transport
+        .requests()
+        .iter()
+        .all(|request| request.method == reqwest::Method::DELETE));
+}
+
+#[tokio::test]
+async fn watch_results_test() {
+    use futures_util::StreamExt;
+    use reqwest::header::{HeaderName, HeaderValue};
+    use reqwest::StatusCode;
+
+    let first = json!({
+        "tie": false,
+        "results": [{"choice":"Kant","index":1,"wins":1,"percentage":100,"strength":1,"advantage":1}]
+    });
+    let second = json!({
+        "tie": false,
+        "results": [{"choice":"Spinoza","index":0,"wins":1,"percentage":100,"strength":2,"advantage":2}]
+    });
+
+    let transport = MockTransport::new();
+    transport.push(MockResponse::new(StatusCode::OK).json(&first));
+    transport.push(MockResponse::new(StatusCode::OK).json(&first));
+    transport.push(MockResponse::new(StatusCode::TOO_MANY_REQUESTS).header(
+        HeaderName::from_static("retry-after"),
+        HeaderValue::from_static("0"),
+    ));
+    transport.push(MockResponse::new(StatusCode::OK).json(&second));
+    transport.push(MockResponse::new(StatusCode::NOT_FOUND));
+    let client = Client::builder("test-token".to_string())
+        .transport(transport.clone())
+        .try_build()
+        .unwrap();
+
+    let results = client.watch_results("40f80454800b2bd7c172", std::time::Duration::from_millis(1));
+    futures_util::pin_mut!(results);
+
+    let got = results.next().await.unwrap().unwrap();
+    assert_eq!(got.results[0].choice, "Kant");
+    let got = results.next().await.unwrap().unwrap();
+    assert_eq!(got.results[0].choice, "Spinoza");
+    assert_eq!(transport.pending(), 1);
+
+    let got = results.next().await.unwrap();
+    assert!(matches!(got, Err(ApiError::NotFound)));
+    assert_eq!(transport.requests().len(), 5);
+}
+
+#[cfg(feature = "sse")]
+#[tokio::test]
+async fn subscribe_events_test() {
+    use ddclient_rs::VotingEvent;
+    use futures_util::StreamExt;
+    use reqwest::StatusCode;
+
+    let body = concat!(
+        ": connected\n\n",
+        "event: ballot_submitted\ndata: {\"voter_id\":\"einstein\"}\n\n",
+        "event: choices_changed\ndata: {\"choices\":[\"Kant\",\"Hume\"]}\n\n",
+        "event: voting_renamed\ndata: {}\n\n",
+    );
+
+    let transport = MockTransport::new();
+    transport.push(MockResponse::new(StatusCode::OK).body(body));
+    transport.push(MockResponse::new(StatusCode::NOT_FOUND));
+    let client = Client::builder("test-token".to_string())
+        .transport(transport.clone())
+        .try_build()
+        .unwrap();
+
+    let events: Vec<_> = client
+        .subscribe_events("40f80454800b2bd7c172")
+        .collect()
+        .await;
+    assert_eq!(events.len(), 3);
+    assert_eq!(
+        events[0].as_ref().unwrap(),
+        &VotingEvent::BallotSubmitted {
+            voter_id: "einstein".to_string()
+        }
+    );
+    assert!(matches!(events[1], Ok(VotingEvent::ChoicesChanged { .. })));
+    assert!(matches!(
+        &events[2],
+        Ok(VotingEvent::Unknown { event, .. }) if event == "voting_renamed"
+    ));
+
+    let request = &transport.requests()[0];
+    assert_eq!(
+        request.url.path(),
+        "/v1/votings/40f80454800b2bd7c172/events"
+    );
+    assert_eq!(request.headers["accept"], "text/event-stream");
+
+    let events: Vec<_> = client.subscribe_events("missing").collect().await;
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0], Err(ApiError::NotFound)));
+}
+
+#[test]
+fn data_types_hash_test() {
+    use std::collections::HashSet;
+
+    let voting: Voting = serde_json::from_value(json!({
+        "id": "40f80454800b2bd7c172",
+        "choices": ["Kant", "Spinoza"]
+    }))
+    .unwrap();
+
+    let mut votings = HashSet::new();
+    votings.insert(voting.clone());
+    votings.insert(voting.clone());
+    assert_eq!(votings.len(), 1);
+    assert!(votings.contains(&voting));
+
+    let results: ddclient_rs::VotingResults = serde_json::from_value(json!({
+        "tie": false,
+        "results": [],
+        "duels": [{
+            "left": {"index": 0, "choice": "Spinoza", "strength": 1},
+            "right": {"index": 1, "choice": "Kant", "strength": 2}
+        }]
+    }))
+    .unwrap();
+    let duels: HashSet<_> = results.duels.clone().unwrap().into_iter().collect();
+    assert_eq!(duels.len(), 1);
+    assert_eq!(results.clone(), results);
+}
+
+#[tokio::test]
+async fn api_version_test() {
+    use ddclient_rs::{v1, ApiVersion};
+
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings/40f80454800b2bd7c172/results");
+        then.status(404)
+            .json_body(json!({"code":404,"message":"Not Found"}));
+    });
+
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .api_version(ApiVersion::V1)
+        .try_build()
+        .unwrap();
+    assert_eq!(client.api_version(), ApiVersion::V1);
+
+    let result: Result<v1::VotingResults, ApiError> =
+        client.get_voting_results("40f80454800b2bd7c172").await;
+    assert!(result.unwrap_err().is_not_found());
+    assert_eq!(
+        client
+            .last_error_context()
+            .and_then(|context| context.voting_id),
+        Some("40f80454800b2bd7c172".to_string())
+    );
+    mock.assert();
+}
+
+#[tokio::test]
+async fn raw_request_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/votings/40f80454800b2bd7c172/export")
+            .query_param("format", "csv")
+            .header("Authorization", "Bearer test-token")
+            .header("Accept", CONTENT_TYPE);
+        then.status(200)
+            .header("X-Ratelimit-Limit", "100")
+            .header("X-Ratelimit-Remaining", "99")
+            .header("X-Ratelimit-Reset", "1700000000")
+            .header("Retry-After", "0")
+            .header("X-Request-Id", "42")
+            .body("choice,wins\nSpinoza,1\n");
+    });
+
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .try_build()
+        .unwrap();
+
+    let request = client
+        .raw_request(
+            reqwest::Method::GET,
+            "v1/votings/40f80454800b2bd7c172/export",
+        )
+        .await
+        .unwrap()
+        .query(&[("format", "csv")]);
+    let response = client.send_raw(request).await.unwrap();
+
+    mock.assert();
+    assert_eq!(response.text().await.unwrap(), "choice,wins\nSpinoza,1\n");
+    assert_eq!(client.get_rate().unwrap().remaining, 99);
+    assert_eq!(client.get_last_request_id().as_deref(), Some("42"));
+}
+
+#[tokio::test]
+async fn with_meta_test() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(200)
+            .header("X-RateLimit-Limit", "100")
+            .header("X-RateLimit-Remaining", "98")
+            .header("X-RateLimit-Reset", "1700000000")
+            .header("Retry-After", "0")
+            .header("X-Request-Id", "42")
+            .json_body(json!({"id":"40f80454800b2bd7c172","choices":["Spinoza","Kant"]}));
+    });
+    server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/votings/40f80454800b2bd7c172/ballots/einstein");
+        then.status(200).json_body(json!({"revoted":true}));
+    });
+
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .try_build()
+        .unwrap();
+
+    let response = client
+        .get_voting_with_meta("40f80454800b2bd7c172", &RequestOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(response.status, 200);
+    assert_eq!(response.rate.as_ref().unwrap().remaining, 98);
+    assert_eq!(response.request_id().as_deref(), Some("42"));
+    assert_eq!(response.into_data().choices.len(), 2);
+
+    let response = client
+        .vote_with_meta(
+            "40f80454800b2bd7c172",
+            "einstein",
+            [("Spinoza", Rank::HIGHEST)],
+            &RequestOptions::new(),
+        )
+        .await
+        .unwrap();
+    assert!(response.data);
+    assert!(response.rate.is_none());
+}
+
+#[tokio::test]
+async fn get_rate_for_test() {
+    use ddclient_rs::EndpointFamily;
+
+    let server = MockServer::start();
+    let rate_mock = |path: &str, remaining: &str| {
+        server.mock(|when, then| {
+            when.method(GET).path(path);
+            then.status(200)
+                .header("X-RateLimit-Limit", "100")
+                .header("X-RateLimit-Remaining", remaining)
+                .header("X-RateLimit-Reset", "60")
+                .header("Retry-After", "0")
+                .json_body(json!({"id":"abc","choices":["Spinoza"],"tie":false,"results":[]}));
+        })
+    };
+    rate_mock("/v1/votings/abc", "90");
+    rate_mock("/v1/votings/abc/results", "10");
+
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .try_build()
+        .unwrap();
+    assert!(client.get_rate_for(EndpointFamily::Votings).is_none());
+
+    client.get_voting("abc").await.unwrap();
+    client.get_voting_results("abc").await.unwrap();
+
+    assert_eq!(client.get_rate().unwrap().remaining, 10);
+    assert_eq!(
+        client
+            .get_rate_for(EndpointFamily::Votings)
+            .unwrap()
+            .remaining,
+        90
+    );
+    assert_eq!(
+        client
+            .get_rate_for(EndpointFamily::Results)
+            .unwrap()
+            .remaining,
+        10
+    );
+    assert!(client.get_rate_for(EndpointFamily::Ballots).is_none());
+}
+
+#[tokio::test]
+async fn rate_updates_test() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/abc");
+        then.status(200)
+            .header("X-RateLimit-Limit", "100")
+            .header("X-RateLimit-Remaining", "90")
+            .header("X-RateLimit-Reset", "60")
+            .header("Retry-After", "0")
+            .json_body(json!({"id":"abc","choices":["Spinoza"]}));
+    });
+
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .try_build()
+        .unwrap();
+    let mut updates = client.rate_updates();
+    assert!(updates.borrow().is_none());
+
+    client.get_voting("abc").await.unwrap();
+
+    updates.changed().await.unwrap();
+    assert_eq!(updates.borrow_and_update().as_ref().unwrap().remaining, 90);
+}
+
+#[tokio::test]
+async fn rate_pacer_test() {
+    use ddclient_rs::RatePacer;
+
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/abc");
+        then.status(200)
+            .header("X-RateLimit-Limit", "100")
+            .header("X-RateLimit-Remaining", "5")
+            .header("X-RateLimit-Reset", "60")
+            .header("Retry-After", "0")
+            .json_body(json!({"id":"abc","choices":["Spinoza"]}));
+    });
+
+    let pacer = RatePacer::new();
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .rate_pacer(pacer.clone())
+        .try_build()
+        .unwrap();
+
+    client.get_voting("abc").await.unwrap();
+    assert_eq!(pacer.remaining(), Some(5));
+
+    // the pacer is shared with the application
+    pacer.acquire().await;
+    assert_eq!(pacer.remaining(), Some(4));
+}
+
+#[test]
+fn client_debug_test() {
+    let builder =
+        Client::builder("secret-token".to_string()).api_url("http://localhost".to_string());
+    assert!(!format!("{:?}", builder).contains("secret-token"));
+
+    let client = builder.try_build().unwrap();
+    let debug = format!("{:?}", client);
+    assert!(!debug.contains("secret-token"));
+    assert!(debug.starts_with("Client { token: [REDACTED], api_url: \"http://localhost/\""));
+}
+
+#[tokio::test]
+async fn strict_responses_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/votings/40f80454800b2bd7c172");
+        then.status(200).json_body(json!({
+            "id": "40f80454800b2bd7c172",
+            "choices": ["Hume", "Kant"],
+            "status": "open",
+            "created_at": "2023-12-01T00:00:00Z"
+        }));
+    });
+
+    // the lenient client ignores the unknown field
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .try_build()
+        .unwrap();
+    let voting = client.get_voting("40f80454800b2bd7c172").await.unwrap();
+    assert_eq!(voting.choices, vec!["Hume", "Kant"]);
+
+    let client = Client::builder("test-token".to_string())
+        .api_url(server.base_url())
+        .strict_responses(true)
+        .try_build()
+        .unwrap();
+    let err = client.get_voting("40f80454800b2bd7c172").await.unwrap_err();
+    assert!(matches!(
+        err,
+        ApiError::Client(ClientError::UnexpectedResponse(ref message))
+            if message == "unknown field `created_at`"
+    ));
+    mock.assert_hits(2);
+}
+
+#[tokio::test]
+async fn simulation_submit_test() {
+    use ddclient_rs::simulation::{Distribution, Simulation};
+    use reqwest::StatusCode;
+
+    let transport = MockTransport::new();
+    for _ in 0..3 {
+        transport.push(MockResponse::new(StatusCode::OK).json(&json!({"revoted":false})));
+    }
+    let client = Client::builder("test-token".to_string())
+        .transport(transport.clone())
+        .try_build()
+        .unwrap();
+
+    let simulation = Simulation::new(vec!["Hume".to_string(), "Kant".to_string()])
+        .distribution(Distribution::Polarized)
+        .voters(3)
+        .seed(42);
+    let results = simulation
+        .submit(
+            &client,
+            "40f80454800b2bd7c172",
+            &BulkOptions::new().concurrency(1),
+        )
+        .await;
+
+    assert_eq!(results.len(), 3);
+    assert!(results
+        .iter()
+        .all(|(_, result)| matches!(result, Ok(false))));
+
+    let requests = transport.requests();
+    assert!(requests[2]
+        .url
+        .path()
+        .ends_with("/v1/votings/40f80454800b2bd7c172/ballots/voter-3"));
+    assert_eq!(
+        requests[0].json(),
+        Some(json!({"ballot": simulation.ballots()[0].1}))
+    );
+}